@@ -1,3 +1,5 @@
+use crate::traits_generics::TableRenderer;
+
 // Accessing element - works for array, slice, and vector
 // - `[]` indexing - panic if out of bound
 // - `first` - return Option<&T>, None if caller is empty
@@ -54,11 +56,16 @@
 // - `slices.concat()` - returns a new vector by concatenating all slices
 fn joining_collections() {
     let slices = [[1, 2], [3, 4], [5, 6]];
+    println!("slices is:");
+    TableRenderer::new().print(slices).unwrap();
+
     let res = slices.concat();
-    println!("{:?}.concat() produces: {:?}", slices, res);
+    println!("slices.concat() produces:");
+    TableRenderer::new().print([&res]).unwrap();
 
     let res = slices.join(&0);
-    println!("{:?}.join(&0) produces: {:?}", slices, res);
+    println!("slices.join(&0) produces:");
+    TableRenderer::new().print([&res]).unwrap();
 }
 
 // Splitting
@@ -116,7 +123,6 @@ fn joining_collections() {
 // - `slice.shuffle(&mut rng)`
 
 fn sort_collections() {
-    #[derive(Debug)]
     struct Student {
         first_name: String,
         last_name: String,
@@ -137,10 +143,20 @@ fn sort_collections() {
         },
     ];
 
-    println!("Before sorting, students is: {:?}", students);
+    let table = TableRenderer::new().headers(&["first_name", "last_name"]);
+    let rows = |students: &[Student]| {
+        students
+            .iter()
+            .map(|s| (s.first_name.clone(), s.last_name.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    println!("Before sorting, students is:");
+    table.print(rows(&students)).unwrap();
     // use `.cmp` method
     students.sort_by(|a, b| a.first_name.cmp(&b.first_name));
-    println!("Aftersorting by first_name, students is: {:?}", students);
+    println!("Aftersorting by first_name, students is:");
+    table.print(rows(&students)).unwrap();
 
     // use a second field as a tiebreaker
     students.sort_by(|a, b| {
@@ -148,10 +164,8 @@ fn sort_collections() {
         let b_key = (&b.first_name, &b.last_name);
         a_key.cmp(&b_key)
     });
-    println!(
-        "Aftersorting by first_name and last_name, students is: {:?}",
-        students
-    );
+    println!("Aftersorting by first_name and last_name, students is:");
+    table.print(rows(&students)).unwrap();
 }
 pub fn use_collections() {
     joining_collections();
@@ -0,0 +1,265 @@
+// A typed `Language` for `Account`, instead of a `String` compared by hand
+// - with `language: String` a pattern can only bind it, `language: e` is a new variable `e`
+//   that matches anything, not a comparison against the `e` in scope
+// - with an enum, `language: Language::English` is a real pattern, and a typo like
+//   `Language::Englsh` is a compile error instead of an arm that never matches
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Language {
+    English,
+    Chinese,
+    Spanish,
+    French,
+    German,
+    Japanese,
+    // anything else, kept as the lower-cased text it was parsed from
+    Other(String),
+}
+
+impl Language {
+    // (variant, ISO 639-1 code, name) for every variant but `Other`
+    const KNOWN: [(Language, &'static str, &'static str); 6] = [
+        (Language::English, "en", "english"),
+        (Language::Chinese, "zh", "chinese"),
+        (Language::Spanish, "es", "spanish"),
+        (Language::French, "fr", "french"),
+        (Language::German, "de", "german"),
+        (Language::Japanese, "ja", "japanese"),
+    ];
+
+    // strict parsing: `None` rather than `Other` for anything not in `KNOWN`
+    // - accepts the code or the name, in any case: "en", "EN", "English"
+    pub fn known(s: &str) -> Option<Language> {
+        let s = s.trim();
+        Self::KNOWN
+            .iter()
+            .find(|(_, code, name)| code.eq_ignore_ascii_case(s) || name.eq_ignore_ascii_case(s))
+            .map(|(language, _, _)| language.clone())
+    }
+
+    pub fn code(&self) -> Option<&'static str> {
+        Self::KNOWN
+            .iter()
+            .find(|(language, _, _)| language == self)
+            .map(|(_, code, _)| *code)
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Language::Other(_))
+    }
+}
+
+// lenient parsing, like `String`'s own `FromStr` it cannot fail
+// - unknown text becomes `Other`, so `s.parse::<Language>()` always gives a value
+impl FromStr for Language {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Language::known(s).unwrap_or_else(|| Language::Other(s.trim().to_lowercase())))
+    }
+}
+
+// prints the name, which parses back to the same value
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Language::Other(s) => f.write_str(s),
+            known => {
+                let (_, _, name) = Self::KNOWN
+                    .iter()
+                    .find(|(language, _, _)| language == known)
+                    .expect("every variant but `Other` is in `KNOWN`");
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub name: String,
+    pub language: Language,
+}
+
+impl Account {
+    pub fn new(name: &str, language: Language) -> Self {
+        Account {
+            name: name.to_string(),
+            language,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    EmptyName,
+    DuplicateName(String),
+    UnknownLanguage(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::EmptyName => write!(f, "account name is empty"),
+            RegistryError::DuplicateName(name) => write!(f, "account {:?} already exists", name),
+            RegistryError::UnknownLanguage(code) => write!(f, "unknown language code {:?}", code),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(Debug, Default)]
+pub struct AccountRegistry {
+    accounts: HashMap<String, Account>,
+}
+
+impl AccountRegistry {
+    pub fn new() -> Self {
+        AccountRegistry::default()
+    }
+
+    // takes any `Language`, including an `Other` the caller built on purpose
+    pub fn register(&mut self, account: Account) -> Result<(), RegistryError> {
+        if account.name.trim().is_empty() {
+            return Err(RegistryError::EmptyName);
+        }
+        if self.accounts.contains_key(&account.name) {
+            return Err(RegistryError::DuplicateName(account.name));
+        }
+        self.accounts.insert(account.name.clone(), account);
+        Ok(())
+    }
+
+    // for untrusted input, e.g. a sign-up form: the code must be one we know
+    pub fn register_code(&mut self, name: &str, code: &str) -> Result<(), RegistryError> {
+        let language = Language::known(code)
+            .ok_or_else(|| RegistryError::UnknownLanguage(code.to_string()))?;
+        self.register(Account::new(name, language))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Account> {
+        self.accounts.remove(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    // sorted by name
+    pub fn speakers(&self, language: &Language) -> Vec<&Account> {
+        let mut speakers: Vec<_> = self
+            .accounts
+            .values()
+            .filter(|a| &a.language == language)
+            .collect();
+        speakers.sort_by(|a, b| a.name.cmp(&b.name));
+        speakers
+    }
+
+    // languages in `Ord` order (known ones first, in declaration order), names sorted within each
+    pub fn by_language(&self) -> BTreeMap<&Language, Vec<&Account>> {
+        let mut groups: BTreeMap<&Language, Vec<&Account>> = BTreeMap::new();
+        for account in self.accounts.values() {
+            groups.entry(&account.language).or_default().push(account);
+        }
+        for accounts in groups.values_mut() {
+            accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        groups
+    }
+}
+
+fn sample_registry() -> AccountRegistry {
+    let mut registry = AccountRegistry::new();
+    registry.register_code("sean", "en").unwrap();
+    registry.register_code("lucia", "es").unwrap();
+    registry.register_code("emma", "English").unwrap();
+    registry.register_code("mei", "zh").unwrap();
+    registry
+        .register(Account::new("worf", "klingon".parse().unwrap()))
+        .unwrap();
+    registry
+}
+
+pub fn use_accounts() {
+    // languages parse by name or by code, anything else is kept as `Other`
+    assert_eq!("en".parse(), Ok(Language::English));
+    println!("{:?}", "Klingon".parse::<Language>());
+
+    let mut registry = sample_registry();
+    println!("{}", registry.register_code("kirk", "tlh").unwrap_err());
+
+    for (language, accounts) in registry.by_language() {
+        let names: Vec<_> = accounts.iter().map(|a| a.name.as_str()).collect();
+        println!(
+            "{} ({}): {:?}",
+            language,
+            language.code().unwrap_or("?"),
+            names
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_round_trip() {
+        // every known language round-trips through `Display` and `FromStr`, by name and by code
+        for (language, code, _) in Language::KNOWN {
+            assert_eq!(language.to_string().parse(), Ok(language.clone()));
+            assert_eq!(code.parse(), Ok(language.clone()));
+            assert_eq!(language.code(), Some(code));
+        }
+        assert_eq!(" English ".parse(), Ok(Language::English));
+        assert_eq!("Klingon".parse(), Ok(Language::Other("klingon".into())));
+        assert_eq!(Language::known("tlh"), None);
+    }
+
+    #[test]
+    fn registry_errors() {
+        let mut registry = sample_registry();
+        assert_eq!(
+            registry.register_code("sean", "fr"),
+            Err(RegistryError::DuplicateName("sean".into()))
+        );
+        assert_eq!(
+            registry.register_code("kirk", "tlh"),
+            Err(RegistryError::UnknownLanguage("tlh".into()))
+        );
+        assert_eq!(
+            registry.register_code(" ", "en"),
+            Err(RegistryError::EmptyName)
+        );
+        assert_eq!(registry.len(), 5);
+    }
+
+    #[test]
+    fn registry_lookups() {
+        let registry = sample_registry();
+        assert_eq!(
+            registry.get("mei").map(|a| &a.language),
+            Some(&Language::Chinese)
+        );
+        let english: Vec<_> = registry
+            .speakers(&Language::English)
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(english, ["emma", "sean"]);
+    }
+}
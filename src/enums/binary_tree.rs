@@ -0,0 +1,594 @@
+// A recursive enum: the binary search tree from Programming Rust's enums chapter
+// - `BinaryTree` cannot hold a `BinaryTree` directly, its size would be infinite,
+//   so the recursion goes through a `Box`, which is just a pointer
+// - `Empty` is a null pointer at run time, `Option<Box<_>>` gets the same layout
+// - elements are kept in order and without duplicates, like a `BTreeSet`
+//
+// Iterators that are structs, not closures as in `create_iterator.rs`
+// - an in-order walk keeps the path of nodes still to visit on a stack, instead of recursing
+// - a second stack walks from the right for `DoubleEndedIterator`
+//
+// Nothing recurses down the tree, sorted input makes it a linked list as deep as it is long
+// - `insert`, `remove`, `height` and the iterators loop instead
+// - dropping is the one place the compiler would recurse for us, so `Drop` unlinks nodes onto a stack first
+// - the derived `Clone` and `Debug` still recurse
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+#[derive(Debug, Clone, Default)]
+pub enum BinaryTree<T> {
+    #[default]
+    Empty,
+    NonEmpty(Box<TreeNode<T>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeNode<T> {
+    element: T,
+    left: BinaryTree<T>,
+    right: BinaryTree<T>,
+}
+
+impl<T> BinaryTree<T> {
+    pub fn new() -> Self {
+        BinaryTree::Empty
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, BinaryTree::Empty)
+    }
+
+    // walks the whole tree, nodes do not store a count
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    // an empty tree has height 0, a single node 1
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((tree, depth)) = stack.pop() {
+            if let BinaryTree::NonEmpty(node) = tree {
+                height = height.max(depth);
+                stack.push((&node.left, depth + 1));
+                stack.push((&node.right, depth + 1));
+            }
+        }
+        height
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.node()?;
+        while let BinaryTree::NonEmpty(left) = &node.left {
+            node = left;
+        }
+        Some(&node.element)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.node()?;
+        while let BinaryTree::NonEmpty(right) = &node.right {
+            node = right;
+        }
+        Some(&node.element)
+    }
+
+    // `BinaryTree` implements `Drop`, so a pattern cannot move the box out of it
+    fn into_node(self) -> Option<Box<TreeNode<T>>> {
+        let tree = mem::ManuallyDrop::new(self);
+        match &*tree {
+            BinaryTree::Empty => None,
+            // SAFETY: `tree` is never dropped, so the box is read out exactly once and has one owner
+            BinaryTree::NonEmpty(node) => Some(unsafe { std::ptr::read(node) }),
+        }
+    }
+
+    fn node(&self) -> Option<&TreeNode<T>> {
+        match self {
+            BinaryTree::Empty => None,
+            BinaryTree::NonEmpty(node) => Some(node),
+        }
+    }
+
+    pub fn iter(&self) -> TreeIter<'_, T> {
+        let mut iter = TreeIter {
+            front: Vec::new(),
+            back: Vec::new(),
+        };
+        iter.push_left_edge(self);
+        iter.push_right_edge(self);
+        iter
+    }
+
+    // changing an element so that it sorts differently breaks the tree's order,
+    // `BTreeSet` has no `iter_mut` for that reason; use this for fields that do not affect `Ord`
+    pub fn iter_mut(&mut self) -> TreeIterMut<'_, T> {
+        let mut iter = TreeIterMut { stack: Vec::new() };
+        iter.push_left_edge(self);
+        iter
+    }
+}
+
+impl<T: Ord> BinaryTree<T> {
+    // `false` if an equal element is already there, like `BTreeSet::insert`
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut tree = self;
+        while let BinaryTree::NonEmpty(node) = tree {
+            tree = match value.cmp(&node.element) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return false,
+            };
+        }
+        *tree = BinaryTree::NonEmpty(Box::new(TreeNode {
+            element: value,
+            left: BinaryTree::Empty,
+            right: BinaryTree::Empty,
+        }));
+        true
+    }
+
+    // `Borrow` lets a `BinaryTree<String>` be searched with a `&str`
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut tree = self;
+        while let BinaryTree::NonEmpty(node) = tree {
+            tree = match value.cmp(node.element.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut tree = self;
+        loop {
+            let next = match tree.node() {
+                None => return None,
+                Some(node) => value.cmp(node.element.borrow()),
+            };
+            // `tree` is only borrowed mutably once we know which way to go
+            tree = match (next, tree) {
+                (Ordering::Equal, tree) => return Some(tree.remove_root()),
+                (Ordering::Less, BinaryTree::NonEmpty(node)) => &mut node.left,
+                (Ordering::Greater, BinaryTree::NonEmpty(node)) => &mut node.right,
+                (_, BinaryTree::Empty) => unreachable!(),
+            };
+        }
+    }
+
+    // `self` must be `NonEmpty`
+    // - with one child, the child takes the node's place
+    // - with two, the smallest element of the right subtree does
+    fn remove_root(&mut self) -> T {
+        let mut node = mem::take(self)
+            .into_node()
+            .expect("`remove_root` on an empty tree");
+        match (&node.left, &node.right) {
+            (BinaryTree::Empty, _) => *self = mem::take(&mut node.right),
+            (_, BinaryTree::Empty) => *self = mem::take(&mut node.left),
+            _ => {
+                let successor = node.right.pop_min().expect("right subtree is not empty");
+                let element = mem::replace(&mut node.element, successor);
+                *self = BinaryTree::NonEmpty(node);
+                return element;
+            }
+        }
+        node.element
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        let mut tree = self;
+        while !tree.node()?.left.is_empty() {
+            tree = match tree {
+                BinaryTree::NonEmpty(node) => &mut node.left,
+                BinaryTree::Empty => unreachable!(),
+            };
+        }
+        Some(tree.remove_root())
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        let mut tree = self;
+        while !tree.node()?.right.is_empty() {
+            tree = match tree {
+                BinaryTree::NonEmpty(node) => &mut node.right,
+                BinaryTree::Empty => unreachable!(),
+            };
+        }
+        Some(tree.remove_root())
+    }
+
+    // elements within `range`, in order
+    // - subtrees below the start are skipped on the way down, so this does not walk the whole tree
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> TreeRange<'_, T, R> {
+        let mut stack = Vec::new();
+        let mut tree = self;
+        while let BinaryTree::NonEmpty(node) = tree {
+            let above_start = match range.start_bound() {
+                Bound::Included(start) => node.element >= *start,
+                Bound::Excluded(start) => node.element > *start,
+                Bound::Unbounded => true,
+            };
+            if above_start {
+                stack.push(&**node);
+                tree = &node.left;
+            } else {
+                tree = &node.right;
+            }
+        }
+        TreeRange { stack, range }
+    }
+}
+
+// children are taken out onto a stack before their parent is freed
+// - each node's own drop then sees two `Empty` children, so the recursion is one level deep
+impl<T> Drop for BinaryTree<T> {
+    fn drop(&mut self) {
+        fn unlink<T>(tree: &mut BinaryTree<T>, stack: &mut Vec<BinaryTree<T>>) {
+            if let BinaryTree::NonEmpty(node) = tree {
+                for child in [mem::take(&mut node.left), mem::take(&mut node.right)] {
+                    if !child.is_empty() {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        let mut stack = Vec::new();
+        unlink(self, &mut stack);
+        while let Some(mut tree) = stack.pop() {
+            unlink(&mut tree, &mut stack);
+            // `tree` is dropped here, with no children left to recurse into
+        }
+    }
+}
+
+pub struct TreeIter<'a, T> {
+    // nodes whose element and right subtree are still to be visited, the top is the next element
+    front: Vec<&'a TreeNode<T>>,
+    // the mirror image for `next_back`: nodes whose element and left subtree are still to be visited
+    back: Vec<&'a TreeNode<T>>,
+}
+
+impl<'a, T> TreeIter<'a, T> {
+    fn push_left_edge(&mut self, mut tree: &'a BinaryTree<T>) {
+        while let BinaryTree::NonEmpty(node) = tree {
+            self.front.push(node);
+            tree = &node.left;
+        }
+    }
+
+    fn push_right_edge(&mut self, mut tree: &'a BinaryTree<T>) {
+        while let BinaryTree::NonEmpty(node) = tree {
+            self.back.push(node);
+            tree = &node.right;
+        }
+    }
+
+    // the two ends meet when both are about to return the same node, which is then the last one
+    fn take_last_if_met(&mut self, node: &TreeNode<T>) {
+        let met =
+            |stack: &Vec<&TreeNode<T>>| stack.last().is_some_and(|top| std::ptr::eq(*top, node));
+        if met(&self.front) || met(&self.back) {
+            self.front.clear();
+            self.back.clear();
+        }
+    }
+}
+
+impl<'a, T> Iterator for TreeIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.front.pop()?;
+        self.take_last_if_met(node);
+        if !self.back.is_empty() {
+            self.push_left_edge(&node.right);
+        }
+        Some(&node.element)
+    }
+}
+
+impl<T> DoubleEndedIterator for TreeIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        self.take_last_if_met(node);
+        if !self.front.is_empty() {
+            self.push_right_edge(&node.left);
+        }
+        Some(&node.element)
+    }
+}
+
+pub struct TreeIterMut<'a, T> {
+    // a node is split into its fields: `left` is walked right away, the other two wait here
+    stack: Vec<(&'a mut T, &'a mut BinaryTree<T>)>,
+}
+
+impl<'a, T> TreeIterMut<'a, T> {
+    fn push_left_edge(&mut self, mut tree: &'a mut BinaryTree<T>) {
+        while let BinaryTree::NonEmpty(node) = tree {
+            let TreeNode {
+                element,
+                left,
+                right,
+            } = &mut **node;
+            self.stack.push((element, right));
+            tree = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for TreeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let (element, right) = self.stack.pop()?;
+        self.push_left_edge(right);
+        Some(element)
+    }
+}
+
+// the owning iterator takes nodes apart as it goes, moving each element out
+pub struct TreeIntoIter<T> {
+    stack: Vec<Box<TreeNode<T>>>,
+}
+
+impl<T> TreeIntoIter<T> {
+    fn push_left_edge(&mut self, mut tree: BinaryTree<T>) {
+        while let Some(mut node) = tree.into_node() {
+            tree = mem::take(&mut node.left);
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<T> Iterator for TreeIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut node = self.stack.pop()?;
+        self.push_left_edge(mem::take(&mut node.right));
+        Some(node.element)
+    }
+}
+
+pub struct TreeRange<'a, T, R> {
+    stack: Vec<&'a TreeNode<T>>,
+    range: R,
+}
+
+impl<'a, T: Ord, R: RangeBounds<T>> Iterator for TreeRange<'a, T, R> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        let below_end = match self.range.end_bound() {
+            Bound::Included(end) => node.element <= *end,
+            Bound::Excluded(end) => node.element < *end,
+            Bound::Unbounded => true,
+        };
+        if !below_end {
+            // everything after this is larger still
+            self.stack.clear();
+            return None;
+        }
+        let mut tree = &node.right;
+        while let BinaryTree::NonEmpty(next) = tree {
+            self.stack.push(next);
+            tree = &next.left;
+        }
+        Some(&node.element)
+    }
+}
+
+impl<T> IntoIterator for BinaryTree<T> {
+    type Item = T;
+    type IntoIter = TreeIntoIter<T>;
+
+    fn into_iter(self) -> TreeIntoIter<T> {
+        let mut iter = TreeIntoIter { stack: Vec::new() };
+        iter.push_left_edge(self);
+        iter
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BinaryTree<T> {
+    type Item = &'a T;
+    type IntoIter = TreeIter<'a, T>;
+
+    fn into_iter(self) -> TreeIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut BinaryTree<T> {
+    type Item = &'a mut T;
+    type IntoIter = TreeIterMut<'a, T>;
+
+    fn into_iter(self) -> TreeIterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = BinaryTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+pub fn use_binary_tree() {
+    let mut tree: BinaryTree<i32> = [50, 30, 70, 20, 40, 60, 80, 35, 45, 65]
+        .into_iter()
+        .collect();
+    println!("{} values, height {}", tree.len(), tree.height());
+
+    // in order from either end
+    println!("{:?}", tree.iter().rev().collect::<Vec<_>>());
+    println!("{:?}", tree.range(33..=60).collect::<Vec<_>>());
+
+    // removing a node with two children pulls up its successor
+    assert_eq!(tree.remove(&30), Some(30));
+
+    // `&mut` iteration, fine here since adding the same amount keeps the order
+    for n in &mut tree {
+        *n += 1;
+    }
+
+    // owned iteration moves the strings out, and `Borrow` lets us search with `&str`
+    let words: BinaryTree<String> = "the quick brown fox jumps over the lazy dog"
+        .split(' ')
+        .map(String::from)
+        .collect();
+    println!("fox? {}", words.contains("fox"));
+    let owned: Vec<String> = words.into_iter().collect();
+    println!("{:?}", owned);
+    println!("{:?}", tree);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // sorted input makes a list as deep as it is long, run on a stack far too small to recurse that deep
+    #[test]
+    fn degenerate_tree_does_not_recurse() {
+        let n = 10_000;
+        let run = move || {
+            let mut tree = BinaryTree::new();
+            for i in 0..n {
+                assert!(tree.insert(i));
+            }
+            assert!(!tree.insert(n - 1));
+            assert_eq!(tree.height(), n);
+            assert_eq!(tree.len(), n);
+            assert_eq!(tree.remove(&(n - 1)), Some(n - 1));
+            assert_eq!(tree.pop_max(), Some(n - 2));
+            assert_eq!(tree.pop_min(), Some(0));
+            assert!(tree.contains(&(n / 2)));
+            assert_eq!(tree.into_iter().count(), n - 3);
+
+            let mut descending = BinaryTree::new();
+            for i in (0..n).rev() {
+                descending.insert(i);
+            }
+            assert_eq!(descending.remove(&0), Some(0));
+            assert_eq!(descending.iter().next(), Some(&1));
+            // dropped here, still 9999 nodes deep
+        };
+        std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(run)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    fn sample() -> BinaryTree<i32> {
+        [50, 30, 70, 20, 40, 60, 80, 35, 45, 65]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn lookups() {
+        let mut tree = sample();
+        assert!(!tree.insert(40));
+        assert_eq!(tree.len(), 10);
+        assert_eq!(tree.height(), 4);
+        assert_eq!((tree.min(), tree.max()), (Some(&20), Some(&80)));
+        assert!(tree.contains(&65) && !tree.contains(&66));
+    }
+
+    #[test]
+    fn iterates_from_both_ends() {
+        let tree = sample();
+        let sorted = [20, 30, 35, 40, 45, 50, 60, 65, 70, 80];
+        assert!(tree.iter().eq(sorted.iter()));
+        assert!(tree.iter().rev().eq(sorted.iter().rev()));
+        // from both ends at once without overlap
+        for split in 0..=sorted.len() {
+            let mut iter = tree.iter();
+            let front: Vec<_> = iter.by_ref().take(split).copied().collect();
+            let mut back: Vec<_> = iter.rev().copied().collect();
+            back.reverse();
+            assert_eq!([front, back].concat(), sorted);
+        }
+        let mut iter = tree.iter();
+        let zipped: Vec<_> =
+            std::iter::from_fn(|| Some((iter.next()?, iter.next_back()?))).collect();
+        assert_eq!(zipped.len(), 5);
+        assert_eq!(zipped[4], (&45, &50));
+    }
+
+    #[test]
+    fn ranges() {
+        let tree = sample();
+        let in_range: Vec<_> = tree.range(33..=60).collect();
+        assert_eq!(in_range, [&35, &40, &45, &50, &60]);
+        assert_eq!(tree.range(..30).count(), 1);
+        assert_eq!(tree.range(81..).next(), None);
+    }
+
+    #[test]
+    fn mutable_and_owned_iteration() {
+        let mut tree = sample();
+        for n in &mut tree {
+            *n += 1;
+        }
+        assert!(tree.contains(&36) && !tree.contains(&35));
+
+        let mut words: BinaryTree<String> = "the quick brown fox jumps over the lazy dog"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        words.extend(["a".to_string(), "quick".to_string()]);
+        assert!(words.contains("fox"));
+        let owned: Vec<String> = words.into_iter().collect();
+        assert_eq!(
+            owned,
+            ["a", "brown", "dog", "fox", "jumps", "lazy", "over", "quick", "the"]
+        );
+    }
+
+    #[test]
+    fn removal_keeps_order() {
+        let mut tree = BinaryTree::new();
+        for x in [50, 30, 70, 20, 40, 60, 80, 35, 45, 65] {
+            tree.insert(x);
+        }
+        assert_eq!(tree.height(), 4);
+        // removing a node with two children pulls up its successor
+        assert_eq!(tree.remove(&30), Some(30));
+        assert_eq!(tree.remove(&30), None);
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(tree.remove(&99), None);
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            [20, 35, 40, 45, 60, 65, 70, 80]
+        );
+        assert_eq!(tree.pop_min(), Some(20));
+        assert_eq!(tree.pop_max(), Some(80));
+    }
+}
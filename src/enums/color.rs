@@ -0,0 +1,453 @@
+// Color models, starting from the HSL triples in `match_array`
+// - `Rgb` holds 8-bit sRGB channels, `Hsl` and `Hsv` hold `f64`s, `Hex` is `Rgb` written as `#rrggbb`
+// - `From` is implemented both ways between `Rgb` and each of the others, so `.into()` converts
+//
+// Rounding
+// - float channels are clamped to their range first, then rounded half away from zero with `f64::round`
+// - hue is kept in `[0, 360)`, saturation, lightness and value in `[0, 1]`
+// - grays have no hue, we report a hue of 0 and a saturation of 0
+// - `Rgb -> Hsl -> Rgb` and `Rgb -> Hsv -> Rgb` give back the exact same bytes
+// - `Hsl -> Rgb -> Hsl` is only close, since 8-bit channels quantize the color, and hue is unstable near gray
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hex(pub Rgb);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorParseError {
+    BadHex(String),
+    BadHsl(String),
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorParseError::BadHex(s) => write!(f, "expected `#rrggbb`, got {:?}", s),
+            ColorParseError::BadHsl(s) => write!(f, "expected `hsl(h, s%, l%)`, got {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+fn to_byte(unit: f64) -> u8 {
+    (unit.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn to_unit(byte: u8) -> f64 {
+    byte as f64 / 255.0
+}
+
+// `rem_euclid` keeps negative hues positive, e.g. -30 becomes 330
+fn normalize_hue(h: f64) -> f64 {
+    let h = h.rem_euclid(360.0);
+    // `rem_euclid` can round up to exactly 360.0 for tiny negative inputs
+    if h >= 360.0 {
+        0.0
+    } else {
+        h
+    }
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    // hue in degrees plus the max and min channel, shared by the HSL and HSV conversions
+    fn hue_max_min(self) -> (f64, f64, f64) {
+        let (r, g, b) = (to_unit(self.r), to_unit(self.g), to_unit(self.b));
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (normalize_hue(h), max, min)
+    }
+
+    // builds RGB from a hue and the chroma, `m` is added to every channel
+    fn from_hue_chroma(h: f64, chroma: f64, m: f64) -> Rgb {
+        let h = normalize_hue(h) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Rgb::new(to_byte(r + m), to_byte(g + m), to_byte(b + m))
+    }
+
+    // WCAG 2.x relative luminance, from 0 for black to 1 for white
+    // - weights the linear channels, since the eye is most sensitive to green
+    pub fn luminance(self) -> f64 {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| srgb_to_linear(to_unit(c)));
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    // from 1:1 for identical colors to 21:1 for black on white, the order of arguments does not matter
+    // - WCAG AA asks for at least 4.5 for body text
+    pub fn contrast_ratio(self, other: Rgb) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        let (lighter, darker) = if a >= b { (a, b) } else { (b, a) };
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    // `t = 0` gives `self`, `t = 1` gives `other`
+    // - interpolates the gamma-encoded bytes, which is what most tools do
+    pub fn mix(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| to_byte(to_unit(a) + (to_unit(b) - to_unit(a)) * t);
+        Rgb::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+
+    // interpolates in linear light, which is physically correct and avoids the dark band
+    // that `mix` gives halfway between saturated colors
+    pub fn mix_linear(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| {
+            let (a, b) = (srgb_to_linear(to_unit(a)), srgb_to_linear(to_unit(b)));
+            to_byte(linear_to_srgb(a + (b - a) * t))
+        };
+        Rgb::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+}
+
+// sRGB transfer function, IEC 61966-2-1
+// - channels are stored gamma-encoded, light adds up linearly, so convert before doing math on light
+pub fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl Hsl {
+    pub fn new(h: f64, s: f64, l: f64) -> Self {
+        Hsl {
+            h: normalize_hue(h),
+            s: s.clamp(0.0, 1.0),
+            l: l.clamp(0.0, 1.0),
+        }
+    }
+
+    // the `[u8; 3]` triples `match_array` takes, each byte spread over its channel's range
+    // - so `[_, _, 0]` is black and `[_, _, 255]` is white, as `match_array` assumes
+    pub fn from_bytes([h, s, l]: [u8; 3]) -> Self {
+        Hsl::new(h as f64 / 256.0 * 360.0, to_unit(s), to_unit(l))
+    }
+}
+
+impl Hsv {
+    pub fn new(h: f64, s: f64, v: f64) -> Self {
+        Hsv {
+            h: normalize_hue(h),
+            s: s.clamp(0.0, 1.0),
+            v: v.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl From<Rgb> for Hsl {
+    fn from(rgb: Rgb) -> Hsl {
+        let (h, max, min) = rgb.hue_max_min();
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        let s = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Hsl::new(h, s, l)
+    }
+}
+
+impl From<Hsl> for Rgb {
+    fn from(hsl: Hsl) -> Rgb {
+        let chroma = (1.0 - (2.0 * hsl.l - 1.0).abs()) * hsl.s;
+        Rgb::from_hue_chroma(hsl.h, chroma, hsl.l - chroma / 2.0)
+    }
+}
+
+impl From<Rgb> for Hsv {
+    fn from(rgb: Rgb) -> Hsv {
+        let (h, max, min) = rgb.hue_max_min();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv::new(h, s, max)
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(hsv: Hsv) -> Rgb {
+        let chroma = hsv.v * hsv.s;
+        Rgb::from_hue_chroma(hsv.h, chroma, hsv.v - chroma)
+    }
+}
+
+impl From<Rgb> for Hex {
+    fn from(rgb: Rgb) -> Hex {
+        Hex(rgb)
+    }
+}
+
+impl From<Hex> for Rgb {
+    fn from(hex: Hex) -> Rgb {
+        hex.0
+    }
+}
+
+// `{:x}` would drop leading zeros, `{:02x}` keeps each channel two digits wide
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0.r, self.0.g, self.0.b)
+    }
+}
+
+// `hsl(210, 50%, 40%)`, the CSS syntax
+impl fmt::Display for Hsl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "hsl({}, {}%, {}%)",
+            self.h.round(),
+            (self.s * 100.0).round(),
+            (self.l * 100.0).round()
+        )
+    }
+}
+
+// `#rrggbb`, either case
+impl FromStr for Hex {
+    type Err = ColorParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || ColorParseError::BadHex(s.to_string());
+        let digits = s.trim().strip_prefix('#').ok_or_else(bad)?;
+        if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(bad());
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| bad());
+        Ok(Hex(Rgb::new(channel(0)?, channel(2)?, channel(4)?)))
+    }
+}
+
+// `hsl(210, 50%, 40%)`, `hsl(210deg 50% 40%)` or `hsl(-150, 50%, 40%)`
+impl FromStr for Hsl {
+    type Err = ColorParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || ColorParseError::BadHsl(s.to_string());
+        let inner = s
+            .trim()
+            .strip_prefix("hsl(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(bad)?;
+        let parts = inner
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+        let [h, s_pct, l_pct] = parts[..] else {
+            return Err(bad());
+        };
+        let h = h.strip_suffix("deg").unwrap_or(h);
+        let percent = |p: &str| -> Result<f64, ColorParseError> {
+            let v: f64 = p
+                .strip_suffix('%')
+                .ok_or_else(bad)?
+                .parse()
+                .map_err(|_| bad())?;
+            if (0.0..=100.0).contains(&v) {
+                Ok(v / 100.0)
+            } else {
+                Err(bad())
+            }
+        };
+        let h: f64 = h.parse().map_err(|_| bad())?;
+        if !h.is_finite() {
+            return Err(bad());
+        }
+        Ok(Hsl::new(h, percent(s_pct)?, percent(l_pct)?))
+    }
+}
+
+fn use_conversions() {
+    let steel_blue: Rgb = "#4682b4".parse::<Hex>().unwrap().into();
+    let hsl = Hsl::from(steel_blue);
+    println!("{} is {}", Hex::from(steel_blue), hsl);
+
+    let parsed: Hsl = "hsl(207deg 44% 49%)".parse().unwrap();
+    println!(
+        "{} ~ {}",
+        Hex::from(Rgb::from(parsed)),
+        Hex::from(steel_blue)
+    );
+    if let Err(e) = "#12345".parse::<Hex>() {
+        println!("{}", e);
+    }
+}
+
+fn use_contrast_and_blending() {
+    // #767676 is the lightest gray that passes AA on white
+    let gray = Rgb::new(0x76, 0x76, 0x76);
+    println!(
+        "{} on white: {:.2}",
+        Hex::from(gray),
+        gray.contrast_ratio(Rgb::WHITE)
+    );
+
+    let red = Rgb::new(255, 0, 0);
+    let green = Rgb::new(0, 255, 0);
+    // halfway in linear light is brighter than halfway in bytes
+    println!(
+        "{} vs {}",
+        Hex::from(red.mix(green, 0.5)),
+        Hex::from(red.mix_linear(green, 0.5))
+    );
+}
+
+pub fn use_color() {
+    use_conversions();
+    use_contrast_and_blending();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a small xorshift generator, so the round trip checks are repeatable without extra crates
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn unit(&mut self) -> f64 {
+            (self.next() >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    #[test]
+    fn rgb_round_trips_exactly() {
+        // every 3rd value per channel, exact round trip through both models
+        for r in (0..=255).step_by(3) {
+            for g in (0..=255).step_by(3) {
+                for b in (0..=255).step_by(3) {
+                    let rgb = Rgb::new(r, g, b);
+                    assert_eq!(Rgb::from(Hsl::from(rgb)), rgb);
+                    assert_eq!(Rgb::from(Hsv::from(rgb)), rgb);
+                    assert_eq!(Rgb::from(Hex::from(rgb)), rgb);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hsl_round_trips_within_tolerance() {
+        // compared after one trip through 8-bit RGB
+        // - hue is meaningless for grays and poorly defined for nearly gray colors, so skip those
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..100_000 {
+            let hsl = Hsl::new(rng.unit() * 360.0, rng.unit(), rng.unit());
+            let back = Hsl::from(Rgb::from(hsl));
+            assert!(
+                (back.l - hsl.l).abs() <= 0.5 / 255.0 + 1e-9,
+                "{:?} {:?}",
+                hsl,
+                back
+            );
+            let chroma = (1.0 - (2.0 * hsl.l - 1.0).abs()) * hsl.s;
+            if chroma > 0.1 {
+                let dh = (back.h - hsl.h).abs();
+                assert!(dh.min(360.0 - dh) < 3.0, "{:?} {:?}", hsl, back);
+            }
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        let steel_blue: Rgb = "#4682b4".parse::<Hex>().unwrap().into();
+        assert_eq!(steel_blue, Rgb::new(70, 130, 180));
+
+        let hsl = Hsl::from(steel_blue);
+        assert_eq!(hsl.to_string(), "hsl(207, 44%, 49%)");
+        let hsv = Hsv::from(steel_blue);
+        assert!((hsv.v - 180.0 / 255.0).abs() < 1e-12);
+
+        let parsed: Hsl = "hsl(207deg 44% 49%)".parse().unwrap();
+        assert_eq!(Hsl::from(Rgb::from(parsed)).to_string(), hsl.to_string());
+        assert!("hsl(1, 2, 3)".parse::<Hsl>().is_err());
+        assert!("#12345".parse::<Hex>().is_err());
+
+        // `match_array`'s special cases
+        assert_eq!(Rgb::from(Hsl::from_bytes([0, 0, 0])), Rgb::BLACK);
+        assert_eq!(Rgb::from(Hsl::from_bytes([90, 200, 255])), Rgb::WHITE);
+    }
+
+    #[test]
+    fn contrast_and_blending() {
+        assert!((Rgb::BLACK.contrast_ratio(Rgb::WHITE) - 21.0).abs() < 1e-9);
+        assert_eq!(Rgb::WHITE.contrast_ratio(Rgb::WHITE), 1.0);
+        let gray = Rgb::new(0x76, 0x76, 0x76);
+        assert!(gray.contrast_ratio(Rgb::WHITE) >= 4.5);
+        assert!(Rgb::new(0x77, 0x77, 0x77).contrast_ratio(Rgb::WHITE) < 4.5);
+
+        let red = Rgb::new(255, 0, 0);
+        let green = Rgb::new(0, 255, 0);
+        assert_eq!(red.mix(green, 0.5), Rgb::new(128, 128, 0));
+        assert_eq!(red.mix_linear(green, 0.5), Rgb::new(188, 188, 0));
+        assert_eq!(red.mix(green, 0.0), red);
+    }
+}
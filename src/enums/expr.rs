@@ -0,0 +1,844 @@
+// Arithmetic expressions as an enum tree
+// - `Expr` is recursive through `Box` and `Vec`, an enum cannot contain itself directly
+//   because its size would be infinite
+// - parsing, printing, evaluating and simplifying are all one `match` over the variants
+//
+// Precedence climbing, from loosest to tightest
+// - `+ -` 1, `* / %` 2, unary `-` 3, `^` 4 (right associative)
+// - so `-2^2` is `-(2^2)` and `2^3^2` is `2^(3^2)`, as in maths
+use crate::iterators::parse_float;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::iter::Peekable;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+const UNARY_PRECEDENCE: u8 = 3;
+// numbers, variables, calls and anything in parentheses never need parentheses around them
+const ATOM_PRECEDENCE: u8 = u8::MAX;
+// deeper expressions are rejected instead of overflowing the stack
+// - printing, evaluating and simplifying recurse too, so the limit is on the height of the tree
+//   as well as on how deeply the input nests
+const MAX_DEPTH: usize = 256;
+
+impl BinaryOp {
+    fn from_char(c: char) -> Option<BinaryOp> {
+        match c {
+            '+' => Some(BinaryOp::Add),
+            '-' => Some(BinaryOp::Sub),
+            '*' => Some(BinaryOp::Mul),
+            '/' => Some(BinaryOp::Div),
+            '%' => Some(BinaryOp::Rem),
+            '^' => Some(BinaryOp::Pow),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+            BinaryOp::Rem => '%',
+            BinaryOp::Pow => '^',
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 2,
+            BinaryOp::Pow => 4,
+        }
+    }
+
+    fn right_associative(self) -> bool {
+        self == BinaryOp::Pow
+    }
+
+    fn apply(self, a: f64, b: f64) -> Result<f64, EvalError> {
+        match self {
+            BinaryOp::Add => Ok(a + b),
+            BinaryOp::Sub => Ok(a - b),
+            BinaryOp::Mul => Ok(a * b),
+            BinaryOp::Div | BinaryOp::Rem if b == 0.0 => Err(EvalError::DivisionByZero),
+            BinaryOp::Div => Ok(a / b),
+            BinaryOp::Rem => Ok(a % b),
+            BinaryOp::Pow => Ok(a.powf(b)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+// shorthands for building trees by hand, they hide the `Box::new`s
+impl Expr {
+    pub fn var(name: &str) -> Expr {
+        Expr::Var(name.to_string())
+    }
+
+    pub fn neg(e: Expr) -> Expr {
+        Expr::Unary(UnaryOp::Neg, Box::new(e))
+    }
+
+    pub fn binary(op: BinaryOp, l: Expr, r: Expr) -> Expr {
+        Expr::Binary(op, Box::new(l), Box::new(r))
+    }
+
+    pub fn call(name: &str, args: Vec<Expr>) -> Expr {
+        Expr::Call(name.to_string(), args)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    InvalidNumber(String),
+    Expected { expected: char, found: Option<char> },
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // byte offset into the input
+    pub offset: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at offset {}: ", self.offset)?;
+        match &self.kind {
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected {:?}", c),
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseErrorKind::InvalidNumber(s) => write!(f, "invalid number {:?}", s),
+            ParseErrorKind::Expected {
+                expected,
+                found: Some(c),
+            } => write!(f, "expected {:?}, found {:?}", expected, c),
+            ParseErrorKind::Expected {
+                expected,
+                found: None,
+            } => write!(f, "expected {:?}, found end of input", expected),
+            ParseErrorKind::TooDeep => write!(f, "nested deeper than {} levels", MAX_DEPTH),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'c, I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+    // bytes handed out by the underlying iterator so far, including a peeked char
+    consumed: &'c Cell<usize>,
+    // `parse_expr` calls in progress
+    depth: usize,
+}
+
+// a parsed subtree and its height, a leaf is 1
+type Parsed = Result<(Expr, usize), ParseError>;
+
+impl<I: Iterator<Item = char>> Parser<'_, I> {
+    // byte offset of the next char, for error messages
+    fn offset(&mut self) -> usize {
+        let peeked = self.chars.peek().map_or(0, |c| c.len_utf8());
+        self.consumed.get() - peeked
+    }
+
+    fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            offset: self.offset(),
+            kind,
+        }
+    }
+
+    // next char that is not whitespace, without consuming it
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            found => Err(self.error(ParseErrorKind::Expected { expected, found })),
+        }
+    }
+
+    fn parse_expr(&mut self, min_precedence: u8) -> Parsed {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(ParseErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let parsed = self.parse_binary(min_precedence);
+        self.depth -= 1;
+        parsed
+    }
+
+    // `1 + 1 + ...` grows the tree without nesting any calls, so the height is checked here too
+    fn node(&mut self, expr: Expr, height: usize) -> Parsed {
+        if height > MAX_DEPTH {
+            return Err(self.error(ParseErrorKind::TooDeep));
+        }
+        Ok((expr, height))
+    }
+
+    // operators binding at least as tightly as `min_precedence`
+    fn parse_binary(&mut self, min_precedence: u8) -> Parsed {
+        let (mut lhs, mut height) = self.parse_unary()?;
+        while let Some(op) = self.peek().and_then(BinaryOp::from_char) {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.chars.next();
+            // a left associative operator must not take another of its own level on the right
+            let next = if op.right_associative() {
+                precedence
+            } else {
+                precedence + 1
+            };
+            let (rhs, rhs_height) = self.parse_expr(next)?;
+            (lhs, height) = self.node(Expr::binary(op, lhs, rhs), height.max(rhs_height) + 1)?;
+        }
+        Ok((lhs, height))
+    }
+
+    fn parse_unary(&mut self) -> Parsed {
+        if self.peek() == Some('-') {
+            self.chars.next();
+            // only `^` binds tighter than unary minus
+            let (operand, height) = self.parse_expr(UNARY_PRECEDENCE + 1)?;
+            return self.node(Expr::neg(operand), height + 1);
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Parsed {
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => self.parse_num(),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                if self.peek() != Some('(') {
+                    return Ok((Expr::Var(name), 1));
+                }
+                self.chars.next();
+                let mut args = Vec::new();
+                let mut height = 0;
+                if self.peek() == Some(')') {
+                    self.chars.next();
+                    return Ok((Expr::Call(name, args), 1));
+                }
+                loop {
+                    let (arg, arg_height) = self.parse_expr(0)?;
+                    args.push(arg);
+                    height = height.max(arg_height);
+                    match self.peek() {
+                        Some(',') => {
+                            self.chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                self.expect(')')?;
+                self.node(Expr::Call(name, args), height + 1)
+            }
+            Some('(') => {
+                self.chars.next();
+                let inner = self.parse_expr(0)?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(c) => Err(self.error(ParseErrorKind::UnexpectedChar(c))),
+            None => Err(self.error(ParseErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    // `parse_float` from `iterators/adapters.rs` reads the whole literal, digits, `.` and an exponent
+    // - so anything `Display` prints for a finite `f64` reads back, however large
+    fn parse_num(&mut self) -> Parsed {
+        let start = self.offset();
+        match parse_float(&mut self.chars) {
+            Ok(value) => Ok((Expr::Num(value), 1)),
+            Err(literal) => Err(ParseError {
+                offset: start,
+                kind: ParseErrorKind::InvalidNumber(literal),
+            }),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    // `inspect` counts bytes as the parser pulls chars, so errors can say where they happened
+    let consumed = Cell::new(0);
+    let chars = input
+        .chars()
+        .inspect(|c| consumed.set(consumed.get() + c.len_utf8()));
+    let mut parser = Parser {
+        chars: chars.peekable(),
+        consumed: &consumed,
+        depth: 0,
+    };
+    let (expr, _) = parser.parse_expr(0)?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(c) => Err(parser.error(ParseErrorKind::UnexpectedChar(c))),
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            // printed with a leading `-`, so it reads back like a unary minus
+            Expr::Num(n) if n.is_sign_negative() => UNARY_PRECEDENCE,
+            Expr::Num(_) | Expr::Var(_) | Expr::Call(..) => ATOM_PRECEDENCE,
+            Expr::Unary(..) => UNARY_PRECEDENCE,
+            Expr::Binary(op, ..) => op.precedence(),
+        }
+    }
+}
+
+fn write_operand(f: &mut fmt::Formatter, e: &Expr, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", e)
+    } else {
+        write!(f, "{}", e)
+    }
+}
+
+// the pretty-printer, with only the parentheses needed to parse back to the same tree
+// - `(a + b) * c` keeps them, `a + (b * c)` loses them
+// - `a - (b - c)` keeps them since `-` is left associative, `a^(b^c)` loses them
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Var(name) => f.write_str(name),
+            Expr::Unary(UnaryOp::Neg, e) => {
+                f.write_char('-')?;
+                write_operand(f, e, e.precedence() < UNARY_PRECEDENCE)
+            }
+            Expr::Binary(op, l, r) => {
+                let p = op.precedence();
+                let (lp, rp) = (l.precedence(), r.precedence());
+                let left = lp < p || (lp == p && op.right_associative());
+                // a `-` on the right is always read as unary minus, so `2^-x` needs nothing
+                let right =
+                    rp != UNARY_PRECEDENCE && (rp < p || (rp == p && !op.right_associative()));
+                write_operand(f, l, left)?;
+                if *op == BinaryOp::Pow {
+                    f.write_char('^')?;
+                } else {
+                    write!(f, " {} ", op.symbol())?;
+                }
+                write_operand(f, r, right)
+            }
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                f.write_char(')')
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(self, n: usize) -> bool {
+        match self {
+            Arity::Exactly(m) => n == m,
+            Arity::AtLeast(m) => n >= m,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exactly(n) => write!(f, "{}", n),
+            Arity::AtLeast(n) => write!(f, "at least {}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    UnknownVariable(String),
+    UnknownFunction(String),
+    WrongArity {
+        name: String,
+        expected: Arity,
+        got: usize,
+    },
+    DivisionByZero,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::UnknownVariable(name) => write!(f, "unknown variable {:?}", name),
+            EvalError::UnknownFunction(name) => write!(f, "unknown function {:?}", name),
+            EvalError::WrongArity {
+                name,
+                expected,
+                got,
+            } => write!(f, "{} takes {} argument(s), {} given", name, expected, got),
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+pub type Function = Box<dyn Fn(&[f64]) -> f64>;
+
+// variables and functions an expression is evaluated against
+// - functions are assumed pure, `simplify` calls them on constant arguments ahead of time
+#[derive(Default)]
+pub struct Env {
+    vars: HashMap<String, f64>,
+    functions: HashMap<String, (Arity, Function)>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env::default()
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut env = Env::new();
+        env.set("pi", std::f64::consts::PI);
+        env.set("e", std::f64::consts::E);
+        // the cast gives the array one element type, function items each have their own
+        let unary = [
+            ("sin", f64::sin as fn(f64) -> f64),
+            ("cos", f64::cos),
+            ("tan", f64::tan),
+            ("sqrt", f64::sqrt),
+            ("abs", f64::abs),
+            ("ln", f64::ln),
+            ("log10", f64::log10),
+            ("exp", f64::exp),
+            ("floor", f64::floor),
+            ("ceil", f64::ceil),
+            ("round", f64::round),
+        ];
+        for (name, f) in unary {
+            env.define(name, Arity::Exactly(1), move |args| f(args[0]));
+        }
+        env.define("pow", Arity::Exactly(2), |args| args[0].powf(args[1]));
+        env.define("hypot", Arity::Exactly(2), |args| args[0].hypot(args[1]));
+        env.define("min", Arity::AtLeast(1), |args| {
+            args.iter().copied().fold(f64::INFINITY, f64::min)
+        });
+        env.define("max", Arity::AtLeast(1), |args| {
+            args.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        });
+        env
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.vars.get(name).copied()
+    }
+
+    // `f` is only called with a slice whose length `arity` accepts
+    pub fn define<F>(&mut self, name: &str, arity: Arity, f: F)
+    where
+        F: Fn(&[f64]) -> f64 + 'static,
+    {
+        self.functions
+            .insert(name.to_string(), (arity, Box::new(f)));
+    }
+
+    fn call(&self, name: &str, args: &[f64]) -> Result<f64, EvalError> {
+        let (arity, f) = self
+            .functions
+            .get(name)
+            .ok_or_else(|| EvalError::UnknownFunction(name.to_string()))?;
+        if !arity.accepts(args.len()) {
+            return Err(EvalError::WrongArity {
+                name: name.to_string(),
+                expected: *arity,
+                got: args.len(),
+            });
+        }
+        Ok(f(args))
+    }
+}
+
+impl Expr {
+    pub fn eval(&self, env: &Env) -> Result<f64, EvalError> {
+        match self {
+            Expr::Num(n) => Ok(*n),
+            Expr::Var(name) => env
+                .get(name)
+                .ok_or_else(|| EvalError::UnknownVariable(name.clone())),
+            Expr::Unary(UnaryOp::Neg, e) => Ok(-e.eval(env)?),
+            Expr::Binary(op, l, r) => op.apply(l.eval(env)?, r.eval(env)?),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                env.call(name, &args)
+            }
+        }
+    }
+
+    // constant folding plus the identities that hold for every `f64`
+    // - `(1 + 2) * x` becomes `3 * x`, `x * 1 + 0` becomes `x`, `sqrt(16)` becomes `4`
+    // - `x * 0` is left alone since `x` may be infinite or NaN
+    // - anything that would fail or give a non-finite number is left for `eval` to report
+    pub fn simplify(&self, env: &Env) -> Expr {
+        match self {
+            Expr::Num(_) | Expr::Var(_) => self.clone(),
+            Expr::Unary(UnaryOp::Neg, e) => match e.simplify(env) {
+                Expr::Num(n) => Expr::Num(-n),
+                Expr::Unary(UnaryOp::Neg, inner) => *inner,
+                e => Expr::neg(e),
+            },
+            Expr::Binary(op, l, r) => {
+                let (l, r) = (l.simplify(env), r.simplify(env));
+                match (op, &l, &r) {
+                    (op, Expr::Num(a), Expr::Num(b)) => match op.apply(*a, *b) {
+                        Ok(v) if v.is_finite() => Expr::Num(v),
+                        _ => Expr::binary(*op, l, r),
+                    },
+                    (BinaryOp::Add, Expr::Num(z), _) if *z == 0.0 => r,
+                    (BinaryOp::Add | BinaryOp::Sub, _, Expr::Num(z)) if *z == 0.0 => l,
+                    (BinaryOp::Sub, Expr::Num(z), _) if *z == 0.0 => Expr::neg(r).simplify(env),
+                    (BinaryOp::Mul, Expr::Num(one), _) if *one == 1.0 => r,
+                    (BinaryOp::Mul | BinaryOp::Div | BinaryOp::Pow, _, Expr::Num(one))
+                        if *one == 1.0 =>
+                    {
+                        l
+                    }
+                    (BinaryOp::Pow, _, Expr::Num(z)) if *z == 0.0 => Expr::Num(1.0),
+                    _ => Expr::binary(*op, l, r),
+                }
+            }
+            Expr::Call(name, args) => {
+                let args: Vec<_> = args.iter().map(|arg| arg.simplify(env)).collect();
+                let constants: Option<Vec<f64>> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Expr::Num(n) => Some(*n),
+                        _ => None,
+                    })
+                    .collect();
+                match constants.map(|values| env.call(name, &values)) {
+                    Some(Ok(v)) if v.is_finite() => Expr::Num(v),
+                    _ => Expr::Call(name.clone(), args),
+                }
+            }
+        }
+    }
+}
+
+// one line of input: `name = expr` assigns, `:simplify expr` prints the simplified tree,
+// anything else is evaluated and also stored in `ans`
+fn calc_line(env: &mut Env, line: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    if let Some(rest) = line.strip_prefix(":simplify") {
+        return Ok(Some(parse(rest)?.simplify(env).to_string()));
+    }
+    if let Some((name, rest)) = line.split_once('=') {
+        let name = name.trim();
+        if let Ok(Expr::Var(name)) = parse(name) {
+            let value = parse(rest)?.eval(env)?;
+            env.set(&name, value);
+            return Ok(Some(format!("{} = {}", name, value)));
+        }
+    }
+    let value = parse(line)?.eval(env)?;
+    env.set("ans", value);
+    Ok(Some(value.to_string()))
+}
+
+// `calc 1 + 2` evaluates its arguments, plain `calc` reads lines from stdin until EOF
+pub fn calc_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut env = Env::with_builtins();
+    if !args.is_empty() {
+        if let Some(out) = calc_line(&mut env, &args.join(" "))? {
+            println!("{}", out);
+        }
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    // a prompt only when someone is typing, not when input is piped in
+    let interactive = stdin.is_terminal();
+    let prompt = || -> io::Result<()> {
+        if interactive {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        Ok(())
+    };
+    prompt()?;
+    for line in stdin.lock().lines() {
+        // a bad line is reported and the session goes on
+        match calc_line(&mut env, &line?) {
+            Ok(Some(out)) => println!("{}", out),
+            Ok(None) => {}
+            Err(e) => eprintln!("error: {}", e),
+        }
+        prompt()?;
+    }
+    Ok(())
+}
+
+pub fn use_expr() -> Result<(), Box<dyn Error>> {
+    let env = Env::with_builtins();
+
+    // precedence and associativity
+    for input in ["1 + 2 * 3", "2 ^ 3 ^ 2", "-2 ^ 2", "hypot(3, 4)"] {
+        println!("{} = {}", input, parse(input)?.eval(&env)?);
+    }
+
+    // only the parentheses that are needed
+    for input in [
+        "((1 + 2)) * (3 * x)",
+        "(a - b) - (c - d)",
+        "(-2) ^ x + 2 ^ (-x)",
+    ] {
+        println!("{} prints as {}", input, parse(input)?);
+    }
+    println!("{}", parse("sqrt(16) * x ^ (2 - 1)")?.simplify(&env));
+
+    // trees can be built by hand too
+    let by_hand = Expr::binary(
+        BinaryOp::Mul,
+        Expr::call("f", vec![Expr::var("x")]),
+        Expr::neg(Expr::Num(2.0)),
+    );
+    assert_eq!(by_hand.to_string(), "f(x) * -2");
+
+    let errors = ["1 +", "(1 + 2", "2 $ 3", "1.2.3 + 1", "2e + 1", "f(1, 2"];
+    for input in errors {
+        println!("{:?}: {}", input, parse(input).unwrap_err());
+    }
+    println!("{}", parse("sin(1, 2)")?.eval(&env).unwrap_err());
+
+    // what the `calc` subcommand does with each line
+    let mut env = Env::with_builtins();
+    for line in ["r = 2", "pi * r ^ 2", "ans / pi", ":simplify 2 * 3 * r + 0"] {
+        println!(
+            "> {}\n{}",
+            line,
+            calc_line(&mut env, line)?.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence_and_associativity() {
+        let env = Env::with_builtins();
+        let cases = [
+            ("1 + 2 * 3", 7.0),
+            ("(1 + 2) * 3", 9.0),
+            ("10 - 4 - 3", 3.0),
+            ("2 ^ 3 ^ 2", 512.0),
+            ("-2 ^ 2", -4.0),
+            ("2 ^ -1", 0.5),
+            ("7 % 4 * 2", 6.0),
+            ("max(1, 1.05, -3) + min(4, 2)", 3.05),
+            ("hypot(3, 4)", 5.0),
+        ];
+        for (input, expected) in cases {
+            let value = parse(input).unwrap().eval(&env).unwrap();
+            assert!((value - expected).abs() < 1e-12, "{} = {}", input, value);
+        }
+    }
+
+    #[test]
+    fn printing_round_trips() {
+        // only the parentheses that are needed, and the output parses back to the same tree
+        let printed = [
+            ("((1 + 2)) * (3 * x)", "(1 + 2) * (3 * x)"),
+            ("(a - b) - (c - d)", "a - b - (c - d)"),
+            ("(a ^ b) ^ c + a ^ (b ^ c)", "(a^b)^c + a^b^c"),
+            ("-(a * b) + (-a) * b", "-(a * b) + -a * b"),
+            ("(-2) ^ x + 2 ^ (-x)", "(-2)^x + 2^-x"),
+            ("sin( pi/2 )", "sin(pi / 2)"),
+        ];
+        for (input, expected) in printed {
+            let e = parse(input).unwrap();
+            assert_eq!(e.to_string(), expected);
+            assert_eq!(parse(&e.to_string()).unwrap(), e);
+        }
+    }
+
+    #[test]
+    fn simplify() {
+        let env = Env::with_builtins();
+        let simplified = [
+            ("(1 + 2) * x", "3 * x"),
+            ("x * 1 + 0", "x"),
+            ("0 - -y", "y"),
+            ("sqrt(16) * x ^ (2 - 1)", "4 * x"),
+            ("max(1, y) / 1", "max(1, y)"),
+            ("x * 0", "x * 0"),
+            ("1 / 0", "1 / 0"),
+        ];
+        for (input, expected) in simplified {
+            assert_eq!(parse(input).unwrap().simplify(&env).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_and_eval_errors() {
+        let env = Env::with_builtins();
+        assert_eq!(
+            parse("1 + ) ").unwrap_err(),
+            ParseError {
+                offset: 4,
+                kind: ParseErrorKind::UnexpectedChar(')')
+            }
+        );
+        assert_eq!(
+            parse("y + 1").unwrap().eval(&env),
+            Err(EvalError::UnknownVariable("y".into()))
+        );
+        assert_eq!(
+            parse("1 % 0").unwrap().eval(&env),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn calc_lines_share_an_env() {
+        let mut env = Env::with_builtins();
+        assert_eq!(
+            calc_line(&mut env, "r = 2").unwrap(),
+            Some("r = 2".to_string())
+        );
+        assert_eq!(
+            calc_line(&mut env, ":simplify 2 * 3 * r + 0").unwrap(),
+            Some("6 * r".to_string())
+        );
+    }
+
+    #[test]
+    fn numbers_beyond_u32_and_exponents() {
+        let env = Env::with_builtins();
+        assert_eq!(parse("5000000000").unwrap(), Expr::Num(5e9));
+        assert_eq!(
+            parse("4294967296 * 1").unwrap().eval(&env),
+            Ok(4294967296.0)
+        );
+        assert_eq!(parse("2.5e3").unwrap(), Expr::Num(2500.0));
+        assert_eq!(parse("1E-2 + .5").unwrap().eval(&env), Ok(0.51));
+        assert_eq!(
+            parse("1.2.3").unwrap_err(),
+            ParseError {
+                offset: 0,
+                kind: ParseErrorKind::InvalidNumber("1.2.3".into())
+            }
+        );
+        assert!(parse("2e").is_err());
+        assert!(parse(".").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let too_deep = |input: String| parse(&input).map(drop).unwrap_err().kind;
+        let parens = |n| format!("{}1{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(too_deep(parens(50_000)), ParseErrorKind::TooDeep);
+        assert_eq!(
+            too_deep(format!("{}1", "-".repeat(50_000))),
+            ParseErrorKind::TooDeep
+        );
+        assert_eq!(
+            too_deep("1 + ".repeat(50_000) + "1"),
+            ParseErrorKind::TooDeep
+        );
+        assert_eq!(
+            too_deep("2 ^ ".repeat(50_000) + "2"),
+            ParseErrorKind::TooDeep
+        );
+        assert_eq!(
+            too_deep(format!("{}x{}", "f(".repeat(50_000), ")".repeat(50_000))),
+            ParseErrorKind::TooDeep
+        );
+
+        // just inside the limit still parses, prints and evaluates
+        let env = Env::with_builtins();
+        assert_eq!(parse(&parens(MAX_DEPTH - 1)).unwrap(), Expr::Num(1.0));
+        let negs = parse(&format!("{}1", "-".repeat(MAX_DEPTH - 1))).unwrap();
+        assert_eq!(negs.eval(&env), Ok(-1.0));
+        assert_eq!(parse(&negs.to_string()).unwrap(), negs);
+    }
+
+    #[test]
+    fn simplified_numbers_read_back() {
+        let env = Env::with_builtins();
+        let e = parse("100000 * 100000 * x").unwrap().simplify(&env);
+        assert_eq!(e.to_string(), "10000000000 * x");
+        assert_eq!(parse(&e.to_string()).unwrap(), e);
+        let tiny = Expr::Num(1e-300);
+        assert_eq!(parse(&tiny.to_string()).unwrap(), tiny);
+    }
+}
@@ -0,0 +1,555 @@
+// 2D geometry grown from `match_tuple` and `Point`
+// - `Point2<T>` is a position, `Vector2<T>` a displacement: `point - point = vector`, `point + vector = point`
+// - generic over the coordinate type in the same way `dot_g` is: the operators it needs, plus `Default` for zero
+// - every predicate uses only `+`, `-`, `*` and comparisons, so it is exact for integer coordinates
+// - only the results that need division or `sqrt`, e.g. `distance` or an intersection point, convert to `f64`
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// any type with these operators can be a coordinate, `T::default()` is taken to be zero
+pub trait Coord:
+    Copy + Default + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+}
+
+// a blanket impl, so `i32`, `i64`, `f32`, `f64` and friends are all `Coord` without listing them
+impl<T> Coord for T where
+    T: Copy + Default + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quadrant {
+    First,
+    Second,
+    Third,
+    Fourth,
+}
+
+// where a point lies relative to the origin, replacing the `&'static str` that `match_tuple` returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Origin,
+    OnAxis(Axis),
+    In(Quadrant),
+}
+
+impl Placement {
+    // the same tuple match as `match_tuple`, generic and with all four quadrants
+    // - `partial_cmp` returns `None` for NaN, which we treat as zero
+    pub fn of<T: Coord>(x: T, y: T) -> Placement {
+        use Ordering::*;
+        let zero = T::default();
+        let sign = |v: T| v.partial_cmp(&zero).unwrap_or(Equal);
+        match (sign(x), sign(y)) {
+            (Equal, Equal) => Placement::Origin,
+            (_, Equal) => Placement::OnAxis(Axis::X),
+            (Equal, _) => Placement::OnAxis(Axis::Y),
+            (Greater, Greater) => Placement::In(Quadrant::First),
+            (Less, Greater) => Placement::In(Quadrant::Second),
+            (Less, Less) => Placement::In(Quadrant::Third),
+            (Greater, Less) => Placement::In(Quadrant::Fourth),
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Placement::Origin => "at the origin",
+            Placement::OnAxis(Axis::X) => "on the x axis",
+            Placement::OnAxis(Axis::Y) => "on the y axis",
+            Placement::In(Quadrant::First) => "in the first quadrant",
+            Placement::In(Quadrant::Second) => "in the second quadrant",
+            Placement::In(Quadrant::Third) => "in the third quadrant",
+            Placement::In(Quadrant::Fourth) => "in the fourth quadrant",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T> Vector2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Vector2 { x, y }
+    }
+}
+
+impl<T: Coord> Point2<T> {
+    pub fn origin() -> Self {
+        Point2::new(T::default(), T::default())
+    }
+
+    pub fn placement(self) -> Placement {
+        Placement::of(self.x, self.y)
+    }
+
+    pub fn distance_squared(self, other: Self) -> T {
+        (other - self).length_squared()
+    }
+
+    pub fn to_f64(self) -> Point2<f64>
+    where
+        T: Into<f64>,
+    {
+        Point2::new(self.x.into(), self.y.into())
+    }
+
+    // `Into<f64>` covers `i32`, `u32`, `f32` and smaller, but not `i64`, which may lose precision
+    pub fn distance(self, other: Self) -> f64
+    where
+        T: Into<f64>,
+    {
+        (other - self).length()
+    }
+}
+
+impl<T: Coord> Vector2<T> {
+    pub fn dot(self, other: Self) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    // the z component of the 3D cross product
+    // - positive if `other` is counter-clockwise from `self`, negative if clockwise, zero if parallel
+    pub fn cross(self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f64
+    where
+        T: Into<f64>,
+    {
+        let (x, y): (f64, f64) = (self.x.into(), self.y.into());
+        x.hypot(y)
+    }
+}
+
+// operator overloading, see `operator_overload.rs`
+impl<T: Coord> Sub for Point2<T> {
+    type Output = Vector2<T>;
+    fn sub(self, rhs: Self) -> Vector2<T> {
+        Vector2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> Add<Vector2<T>> for Point2<T> {
+    type Output = Point2<T>;
+    fn add(self, rhs: Vector2<T>) -> Point2<T> {
+        Point2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coord> Sub<Vector2<T>> for Point2<T> {
+    type Output = Point2<T>;
+    fn sub(self, rhs: Vector2<T>) -> Point2<T> {
+        Point2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> Add for Vector2<T> {
+    type Output = Vector2<T>;
+    fn add(self, rhs: Self) -> Self {
+        Vector2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coord> Sub for Vector2<T> {
+    type Output = Vector2<T>;
+    fn sub(self, rhs: Self) -> Self {
+        Vector2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+// scalar multiplication, `vector * 2`
+impl<T: Coord> Mul<T> for Vector2<T> {
+    type Output = Vector2<T>;
+    fn mul(self, rhs: T) -> Self {
+        Vector2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Coord + Neg<Output = T>> Neg for Vector2<T> {
+    type Output = Vector2<T>;
+    fn neg(self) -> Self {
+        Vector2::new(-self.x, -self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+// which way we turn going from `a` to `b` to `c`
+pub fn orientation<T: Coord>(a: Point2<T>, b: Point2<T>, c: Point2<T>) -> Orientation {
+    match (b - a).cross(c - a).partial_cmp(&T::default()) {
+        Some(Ordering::Greater) => Orientation::CounterClockwise,
+        Some(Ordering::Less) => Orientation::Clockwise,
+        _ => Orientation::Collinear,
+    }
+}
+
+// Shoelace formula, twice the signed area
+// - positive for counter-clockwise vertices, negative for clockwise
+// - twice the area so that integer coordinates stay exact, the area itself may end in `.5`
+pub fn signed_area2<T: Coord>(polygon: &[Point2<T>]) -> T {
+    let mut sum = T::default();
+    for (i, &p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        sum = sum + (p.x * q.y - q.x * p.y);
+    }
+    sum
+}
+
+pub fn polygon_area<T: Coord + Into<f64>>(polygon: &[Point2<T>]) -> f64 {
+    (signed_area2(polygon).into() / 2.0).abs()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Outside,
+    OnBoundary,
+}
+
+// is `p` on the closed segment `a`-`b`
+fn on_segment<T: Coord>(p: Point2<T>, a: Point2<T>, b: Point2<T>) -> bool {
+    let between = |v: T, lo: T, hi: T| {
+        let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+        lo <= v && v <= hi
+    };
+    orientation(a, b, p) == Orientation::Collinear
+        && between(p.x, a.x, b.x)
+        && between(p.y, a.y, b.y)
+}
+
+// Winding number test, works for concave polygons and either vertex order
+// - counts how many times the polygon winds around `p`, using only cross products
+pub fn point_in_polygon<T: Coord>(p: Point2<T>, polygon: &[Point2<T>]) -> Containment {
+    let mut winding = 0i32;
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if on_segment(p, a, b) {
+            return Containment::OnBoundary;
+        }
+        // an upward edge with `p` on its left, or a downward edge with `p` on its right
+        if a.y <= p.y {
+            if b.y > p.y && orientation(a, b, p) == Orientation::CounterClockwise {
+                winding += 1;
+            }
+        } else if b.y <= p.y && orientation(a, b, p) == Orientation::Clockwise {
+            winding -= 1;
+        }
+    }
+    if winding == 0 {
+        Containment::Outside
+    } else {
+        Containment::Inside
+    }
+}
+
+// Andrew's monotone chain, O(n log n)
+// - returns the hull counter-clockwise, starting from the lowest-leftmost point
+// - collinear points on the hull's edges are left out
+pub fn convex_hull<T: Coord>(points: &[Point2<T>]) -> Vec<Point2<T>> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| {
+        (a.x, a.y)
+            .partial_cmp(&(b.x, b.y))
+            .unwrap_or(Ordering::Equal)
+    });
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // pop while the last two points and `p` do not make a left turn
+    let turns_left = |hull: &[Point2<T>], p| {
+        orientation(hull[hull.len() - 2], hull[hull.len() - 1], p) == Orientation::CounterClockwise
+    };
+    let mut hull: Vec<Point2<T>> = Vec::with_capacity(sorted.len() + 1);
+    // lower hull, left to right
+    for &p in &sorted {
+        while hull.len() >= 2 && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // upper hull, right to left, never popping into the lower hull
+    let lower_len = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // the last point pushed is the first point again
+    hull.pop();
+    hull
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment<T> {
+    pub a: Point2<T>,
+    pub b: Point2<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection {
+    Disjoint,
+    Point(Point2<f64>),
+    // collinear segments sharing more than one point
+    Overlap(Segment<f64>),
+}
+
+impl<T: Coord> Segment<T> {
+    pub fn new(a: Point2<T>, b: Point2<T>) -> Self {
+        Segment { a, b }
+    }
+
+    // exact for integer coordinates
+    pub fn intersects(&self, other: &Segment<T>) -> bool {
+        let (p, q) = (self, other);
+        let o1 = orientation(p.a, p.b, q.a);
+        let o2 = orientation(p.a, p.b, q.b);
+        let o3 = orientation(q.a, q.b, p.a);
+        let o4 = orientation(q.a, q.b, p.b);
+        let crosses = o1 != o2 && o3 != o4 && ![o1, o2, o3, o4].contains(&Orientation::Collinear);
+        crosses
+            || on_segment(q.a, p.a, p.b)
+            || on_segment(q.b, p.a, p.b)
+            || on_segment(p.a, q.a, q.b)
+            || on_segment(p.b, q.a, q.b)
+    }
+
+    pub fn intersection(&self, other: &Segment<T>) -> SegmentIntersection
+    where
+        T: Into<f64>,
+    {
+        if !self.intersects(other) {
+            return SegmentIntersection::Disjoint;
+        }
+        let (p, q) = (self.to_f64(), other.to_f64());
+        let r = p.b - p.a;
+        let s = q.b - q.a;
+        let denom = r.cross(s);
+        if denom != 0.0 {
+            // solve `p.a + r * t = q.a + s * u` for `t`
+            let t = (q.a - p.a).cross(s) / denom;
+            return SegmentIntersection::Point(p.a + r * t);
+        }
+        // collinear: order the four endpoints along the line and keep the middle two
+        // - the axis comes from the longer segment, a point segment has no direction of its own
+        let d = if r.x.abs().max(r.y.abs()) >= s.x.abs().max(s.y.abs()) {
+            r
+        } else {
+            s
+        };
+        let along = |pt: Point2<f64>| {
+            if d.x.abs() >= d.y.abs() {
+                pt.x
+            } else {
+                pt.y
+            }
+        };
+        let mut ends = [p.a, p.b, q.a, q.b];
+        ends.sort_by(|m, n| along(*m).total_cmp(&along(*n)));
+        if ends[1] == ends[2] {
+            SegmentIntersection::Point(ends[1])
+        } else {
+            SegmentIntersection::Overlap(Segment::new(ends[1], ends[2]))
+        }
+    }
+
+    pub fn to_f64(self) -> Segment<f64>
+    where
+        T: Into<f64>,
+    {
+        Segment::new(self.a.to_f64(), self.b.to_f64())
+    }
+}
+
+fn use_points_and_vectors() {
+    let p = Point2::new(3, 4);
+    println!("{} is {}", p, p.placement());
+    println!("{} is {}", Point2::new(0, 7), Point2::new(0, 7).placement());
+    println!(
+        "distance to origin: {}",
+        Point2::<i32>::origin().distance(p)
+    );
+
+    let v = p - Point2::new(1, 1);
+    // `x` axis to `y` axis is a counter-clockwise turn
+    assert!(Vector2::new(1, 0).cross(Vector2::new(0, 1)) > 0);
+    println!("{} + {:?} * 2 = {}", p, v, p + v * 2);
+}
+
+fn use_polygons() {
+    // an L shape, counter-clockwise
+    let l_shape = [
+        Point2::new(0, 0),
+        Point2::new(4, 0),
+        Point2::new(4, 1),
+        Point2::new(1, 1),
+        Point2::new(1, 3),
+        Point2::new(0, 3),
+    ];
+    println!("area of the L: {}", polygon_area(&l_shape));
+
+    // the notch of the L is outside, even though it is inside the bounding box
+    println!(
+        "(2, 2) is {:?}",
+        point_in_polygon(Point2::new(2, 2), &l_shape)
+    );
+
+    let hull = convex_hull(&l_shape);
+    println!("hull of the L: {:?}", hull);
+}
+
+fn use_segments() {
+    let diagonal = Segment::new(Point2::new(0, 0), Point2::new(4, 4));
+    let cross = Segment::new(Point2::new(0, 4), Point2::new(4, 0));
+    println!("{:?}", diagonal.intersection(&cross));
+
+    let along = Segment::new(Point2::new(2, 2), Point2::new(9, 9));
+    println!("{:?}", diagonal.intersection(&along));
+}
+
+pub fn use_geometry() {
+    use_points_and_vectors();
+    use_polygons();
+    use_segments();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_and_vectors() {
+        let p = Point2::new(3, 4);
+        assert_eq!(p.placement(), Placement::In(Quadrant::First));
+        assert_eq!(
+            Point2::new(-2.5, -1.0).placement(),
+            Placement::In(Quadrant::Third)
+        );
+        assert_eq!(Point2::new(0, 7).placement(), Placement::OnAxis(Axis::Y));
+        assert_eq!(Point2::<i32>::origin().distance(p), 5.0);
+
+        let v = p - Point2::new(1, 1);
+        assert_eq!(v, Vector2::new(2, 3));
+        assert_eq!(p + v * 2, Point2::new(7, 10));
+        assert_eq!(v.dot(Vector2::new(1, 0)), 2);
+        assert!(Vector2::new(1, 0).cross(Vector2::new(0, 1)) > 0);
+    }
+
+    #[test]
+    fn l_shaped_polygon() {
+        let l_shape = [
+            Point2::new(0, 0),
+            Point2::new(4, 0),
+            Point2::new(4, 1),
+            Point2::new(1, 1),
+            Point2::new(1, 3),
+            Point2::new(0, 3),
+        ];
+        assert_eq!(signed_area2(&l_shape), 12);
+        assert_eq!(polygon_area(&l_shape), 6.0);
+
+        assert_eq!(
+            point_in_polygon(Point2::new(2, 2), &l_shape),
+            Containment::Outside
+        );
+        assert_eq!(
+            point_in_polygon(Point2::new(0, 2), &l_shape),
+            Containment::OnBoundary
+        );
+        let inside = point_in_polygon(Point2::new(0.5, 2.0), &l_shape.map(|p| p.to_f64()));
+        assert_eq!(inside, Containment::Inside);
+
+        assert_eq!(
+            convex_hull(&l_shape),
+            vec![
+                Point2::new(0, 0),
+                Point2::new(4, 0),
+                Point2::new(4, 1),
+                Point2::new(1, 3),
+                Point2::new(0, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn segment_intersections() {
+        let diagonal = Segment::new(Point2::new(0, 0), Point2::new(4, 4));
+        let cross = Segment::new(Point2::new(0, 4), Point2::new(4, 0));
+        assert_eq!(
+            diagonal.intersection(&cross),
+            SegmentIntersection::Point(Point2::new(2.0, 2.0))
+        );
+
+        let apart = Segment::new(Point2::new(5, 5), Point2::new(6, 9));
+        assert!(!diagonal.intersects(&apart));
+
+        let along = Segment::new(Point2::new(2, 2), Point2::new(9, 9));
+        assert_eq!(
+            diagonal.intersection(&along),
+            SegmentIntersection::Overlap(Segment::new(
+                Point2::new(2.0, 2.0),
+                Point2::new(4.0, 4.0)
+            ))
+        );
+
+        // touching at an endpoint
+        let touching = Segment::new(Point2::new(4, 4), Point2::new(8, 0));
+        assert_eq!(
+            diagonal.intersection(&touching),
+            SegmentIntersection::Point(Point2::new(4.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn point_segments_on_a_line() {
+        let point = Segment::new(Point2::new(0, 1), Point2::new(0, 1));
+        let line = Segment::new(Point2::new(0, 0), Point2::new(0, 2));
+        let expected = SegmentIntersection::Point(Point2::new(0.0, 1.0));
+        assert_eq!(point.intersection(&line), expected);
+        assert_eq!(line.intersection(&point), expected);
+        assert_eq!(point.intersection(&point), expected);
+    }
+}
@@ -0,0 +1,927 @@
+// The `Json` enum from the enums chapter of Programming Rust, made usable
+// - a strict RFC 8259 parser: no comments, no trailing commas, no leading zeros, no single quotes,
+//   no raw control characters in strings, and no duplicate keys in an object
+// - errors carry the line and column they were found at, both counted from 1
+// - `{}` prints compact JSON, `{:#}` prints it indented by two spaces
+// - RFC 6901 JSON Pointers such as "/users/0/name" to read and modify nested values
+//
+// Numbers are `f64`, like in JavaScript
+// - integers above 2^53 lose precision, and NaN and infinities have no JSON form, they print as `null`
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::mem;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(JsonObject),
+}
+
+// keys stay in insertion order, which is the order they are printed in
+// - a `Vec` of pairs with linear lookup, objects in real documents are small
+// - equality ignores the order, `{"a":1,"b":2}` equals `{"b":2,"a":1}` as RFC 8259 says
+#[derive(Debug, Clone, Default)]
+pub struct JsonObject {
+    entries: Vec<(String, Json)>,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        JsonObject::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    // an existing key keeps its position and the old value is returned
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Json>) -> Option<Json> {
+        let (key, value) = (key.into(), value.into());
+        match self.get_mut(&key) {
+            Some(old) => Some(mem::replace(old, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    // the keys after it move up, their order is kept
+    pub fn remove(&mut self, key: &str) -> Option<Json> {
+        let i = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Json)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Into<String>, V: Into<Json>> FromIterator<(K, V)> for JsonObject {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut object = JsonObject::new();
+        for (k, v) in iter {
+            object.insert(k, v);
+        }
+        object
+    }
+}
+
+impl Json {
+    pub fn parse(input: &str) -> Result<Json, JsonError> {
+        let mut parser = JsonParser {
+            input,
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        match parser.peek_char() {
+            None => Ok(value),
+            found => Err(parser.error(JsonErrorKind::Expected {
+                expected: "end of input",
+                found,
+            })),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Json::Null
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&JsonObject> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    // `None` unless `self` is an object with that key
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.as_object()?.get(key)
+    }
+}
+
+impl FromStr for Json {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Json::parse(s)
+    }
+}
+
+// parsing
+
+// deeper documents are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonErrorKind {
+    Expected {
+        expected: &'static str,
+        found: Option<char>,
+    },
+    InvalidNumber,
+    NumberOutOfRange,
+    InvalidEscape,
+    LoneSurrogate,
+    ControlCharacter(char),
+    DuplicateKey(String),
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: JsonErrorKind,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            JsonErrorKind::Expected {
+                expected,
+                found: Some(c),
+            } => write!(f, "expected {}, found {:?}", expected, c),
+            JsonErrorKind::Expected {
+                expected,
+                found: None,
+            } => write!(f, "expected {}, found end of input", expected),
+            JsonErrorKind::InvalidNumber => write!(f, "invalid number"),
+            JsonErrorKind::NumberOutOfRange => write!(f, "number out of range"),
+            JsonErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            JsonErrorKind::LoneSurrogate => write!(f, "unpaired UTF-16 surrogate in \\u escape"),
+            JsonErrorKind::ControlCharacter(c) => {
+                write!(f, "unescaped control character {:?} in string", c)
+            }
+            JsonErrorKind::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            JsonErrorKind::TooDeep => write!(f, "nested deeper than {} levels", MAX_DEPTH),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+struct JsonParser<'a> {
+    input: &'a str,
+    // byte offset of the next char
+    pos: usize,
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    // line and column are only worked out when an error happens
+    fn error_at(&self, pos: usize, kind: JsonErrorKind) -> JsonError {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        JsonError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+
+    fn error(&self, kind: JsonErrorKind) -> JsonError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn expected(&self, expected: &'static str) -> JsonError {
+        self.error(JsonErrorKind::Expected {
+            expected,
+            found: self.peek_char(),
+        })
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek_char() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    // only these four count as whitespace in JSON
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek_char(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.peek_char() {
+            Some('n') => self.parse_literal("null", Json::Null),
+            Some('t') => self.parse_literal("true", Json::Bool(true)),
+            Some('f') => self.parse_literal("false", Json::Bool(false)),
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some('[') => self.nested(Self::parse_array),
+            Some('{') => self.nested(Self::parse_object),
+            _ => Err(self.expected("a value")),
+        }
+    }
+
+    fn parse_literal(&mut self, word: &'static str, value: Json) -> Result<Json, JsonError> {
+        for expected in word.chars() {
+            if !self.eat(expected) {
+                return Err(self.expected(word));
+            }
+        }
+        Ok(value)
+    }
+
+    // -? (0 | [1-9][0-9]*) (\.[0-9]+)? ([eE][+-]?[0-9]+)?
+    fn parse_number(&mut self) -> Result<Json, JsonError> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while matches!(p.peek_char(), Some('0'..='9')) {
+                p.pos += 1;
+            }
+            p.pos > from
+        };
+        self.eat('-');
+        if !self.eat('0') && !digits(self) {
+            return Err(self.error(JsonErrorKind::InvalidNumber));
+        }
+        if self.eat('.') && !digits(self) {
+            return Err(self.error(JsonErrorKind::InvalidNumber));
+        }
+        if self.eat('e') || self.eat('E') {
+            let _ = self.eat('+') || self.eat('-');
+            if !digits(self) {
+                return Err(self.error(JsonErrorKind::InvalidNumber));
+            }
+        }
+        // the grammar above is a subset of what `f64::from_str` accepts
+        let n: f64 = self.input[start..self.pos]
+            .parse()
+            .map_err(|_| self.error_at(start, JsonErrorKind::InvalidNumber))?;
+        if n.is_infinite() {
+            return Err(self.error_at(start, JsonErrorKind::NumberOutOfRange));
+        }
+        Ok(Json::Number(n))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u16, JsonError> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error(JsonErrorKind::InvalidEscape))?;
+        self.pos += 4;
+        Ok(u16::from_str_radix(hex, 16).unwrap())
+    }
+
+    // after `\u`, including a following `\uXXXX` when the first half is a high surrogate
+    fn parse_unicode_escape(&mut self, escape_start: usize) -> Result<char, JsonError> {
+        let lone = |p: &Self| p.error_at(escape_start, JsonErrorKind::LoneSurrogate);
+        let high = self.parse_hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !(self.eat('\\') && self.eat('u')) {
+                    return Err(lone(self));
+                }
+                let low = self.parse_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(lone(self));
+                }
+                0x10000 + ((high as u32 - 0xD800) << 10) + (low as u32 - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(lone(self)),
+            _ => high as u32,
+        };
+        Ok(char::from_u32(code).expect("surrogates were ruled out above"))
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        if !self.eat('"') {
+            return Err(self.expected("a string"));
+        }
+        let mut s = String::new();
+        loop {
+            let at = self.pos;
+            match self.next_char() {
+                None => return Err(self.expected("closing '\"'")),
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.next_char() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode_escape(at)?,
+                        _ => return Err(self.error_at(at, JsonErrorKind::InvalidEscape)),
+                    };
+                    s.push(c);
+                }
+                Some(c) if c < ' ' => {
+                    return Err(self.error_at(at, JsonErrorKind::ControlCharacter(c)))
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, JsonError>,
+    ) -> Result<Json, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(JsonErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_array(&mut self) -> Result<Json, JsonError> {
+        self.eat('[');
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(Json::Array(items));
+            }
+            if !self.eat(',') {
+                return Err(self.expected("',' or ']'"));
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, JsonError> {
+        self.eat('{');
+        let mut object = JsonObject::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Json::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            let key_at = self.pos;
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if !self.eat(':') {
+                return Err(self.expected("':'"));
+            }
+            let value = self.parse_value()?;
+            if object.contains_key(&key) {
+                return Err(self.error_at(key_at, JsonErrorKind::DuplicateKey(key)));
+            }
+            object.entries.push((key, value));
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Json::Object(object));
+            }
+            if !self.eat(',') {
+                return Err(self.expected("',' or '}'"));
+            }
+        }
+    }
+}
+
+// serializing
+
+fn write_json_string<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{8}' => out.write_str("\\b")?,
+            '\u{c}' => out.write_str("\\f")?,
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+impl Json {
+    // `indent` of `None` is compact, `Some(n)` puts every item on its own line, `n` spaces per level
+    fn write_to<W: Write>(&self, out: &mut W, indent: Option<usize>, level: usize) -> fmt::Result {
+        let newline = |out: &mut W, level: usize| match indent {
+            Some(n) => write!(out, "\n{:width$}", "", width = n * level),
+            None => Ok(()),
+        };
+        match self {
+            Json::Null => out.write_str("null"),
+            Json::Bool(b) => write!(out, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(out, "{}", n),
+            Json::Number(_) => out.write_str("null"),
+            Json::String(s) => write_json_string(out, s),
+            Json::Array(items) if items.is_empty() => out.write_str("[]"),
+            Json::Array(items) => {
+                out.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    newline(out, level + 1)?;
+                    item.write_to(out, indent, level + 1)?;
+                }
+                newline(out, level)?;
+                out.write_char(']')
+            }
+            Json::Object(object) if object.is_empty() => out.write_str("{}"),
+            Json::Object(object) => {
+                out.write_char('{')?;
+                for (i, (key, value)) in object.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    newline(out, level + 1)?;
+                    write_json_string(out, key)?;
+                    out.write_str(if indent.is_some() { ": " } else { ":" })?;
+                    value.write_to(out, indent, level + 1)?;
+                }
+                newline(out, level)?;
+                out.write_char('}')
+            }
+        }
+    }
+
+    pub fn write_compact<W: Write>(&self, out: &mut W) -> fmt::Result {
+        self.write_to(out, None, 0)
+    }
+
+    pub fn write_pretty<W: Write>(&self, out: &mut W, indent: usize) -> fmt::Result {
+        self.write_to(out, Some(indent), 0)
+    }
+}
+
+// `{}` is compact, `{:#}` is pretty with two spaces
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            self.write_pretty(f, 2)
+        } else {
+            self.write_compact(f)
+        }
+    }
+}
+
+// JSON Pointer
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    // not empty and not starting with '/', or a '~' not followed by '0' or '1'
+    Syntax(String),
+    // the value the pointer ends in, or one on the way, does not exist
+    NotFound(String),
+    // the pointer goes through a value that is neither an array nor an object
+    NotAContainer(String),
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointerError::Syntax(p) => write!(f, "invalid JSON pointer {:?}", p),
+            PointerError::NotFound(p) => write!(f, "nothing at {:?}", p),
+            PointerError::NotAContainer(p) => {
+                write!(
+                    f,
+                    "{:?} goes through a value that is not an array or object",
+                    p
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+// "/a~1b/~0c" is ["a/b", "~c"], "" is the whole document
+fn pointer_tokens(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let syntax = || PointerError::Syntax(pointer.to_string());
+    let rest = pointer.strip_prefix('/').ok_or_else(syntax)?;
+    rest.split('/')
+        .map(|token| {
+            let mut escapes = token.split('~').skip(1);
+            if !escapes.all(|after| after.starts_with(['0', '1'])) {
+                return Err(syntax());
+            }
+            // `~1` first, so "~01" becomes "~1" and not "/"
+            Ok(token.replace("~1", "/").replace("~0", "~"))
+        })
+        .collect()
+}
+
+// "0" or digits without a leading zero, "-" (past the end) is handled by the callers
+fn array_index(token: &str) -> Option<usize> {
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    if token.is_empty() || !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl Json {
+    fn child(&self, token: &str) -> Option<&Json> {
+        match self {
+            Json::Object(object) => object.get(token),
+            Json::Array(items) => items.get(array_index(token)?),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, token: &str) -> Option<&mut Json> {
+        match self {
+            Json::Object(object) => object.get_mut(token),
+            Json::Array(items) => items.get_mut(array_index(token)?),
+            _ => None,
+        }
+    }
+
+    // `None` for a malformed pointer as well as a missing value
+    pub fn pointer(&self, pointer: &str) -> Option<&Json> {
+        pointer_tokens(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |value, token| value.child(token))
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Json> {
+        pointer_tokens(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+    }
+
+    // the array or object holding the last token, and that token
+    fn parent_mut(&mut self, pointer: &str) -> Result<(&mut Json, String), PointerError> {
+        let mut tokens = pointer_tokens(pointer)?;
+        let last = tokens
+            .pop()
+            .expect("callers handle the empty pointer themselves");
+        let parent = tokens
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+            .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?;
+        match parent {
+            Json::Array(_) | Json::Object(_) => Ok((parent, last)),
+            _ => Err(PointerError::NotAContainer(pointer.to_string())),
+        }
+    }
+
+    // replaces the value at `pointer` and returns the old one, or adds it
+    // - an object gets a new key, an array is appended to with "-" or an index equal to its length
+    // - "" replaces the whole document
+    pub fn set_pointer(
+        &mut self,
+        pointer: &str,
+        value: impl Into<Json>,
+    ) -> Result<Option<Json>, PointerError> {
+        let value = value.into();
+        if pointer.is_empty() {
+            return Ok(Some(mem::replace(self, value)));
+        }
+        let (parent, last) = self.parent_mut(pointer)?;
+        match parent {
+            Json::Object(object) => Ok(object.insert(last, value)),
+            Json::Array(items) => {
+                let index = match last.as_str() {
+                    "-" => items.len(),
+                    token => array_index(token)
+                        .filter(|&i| i <= items.len())
+                        .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?,
+                };
+                if index == items.len() {
+                    items.push(value);
+                    Ok(None)
+                } else {
+                    Ok(Some(mem::replace(&mut items[index], value)))
+                }
+            }
+            _ => unreachable!("`parent_mut` only returns containers"),
+        }
+    }
+
+    // removes the value at `pointer`, array items after it shift down by one
+    // - "" takes the whole document and leaves `null` behind
+    pub fn remove_pointer(&mut self, pointer: &str) -> Result<Json, PointerError> {
+        if pointer.is_empty() {
+            return Ok(mem::replace(self, Json::Null));
+        }
+        let not_found = || PointerError::NotFound(pointer.to_string());
+        let (parent, last) = self.parent_mut(pointer)?;
+        match parent {
+            Json::Object(object) => object.remove(&last).ok_or_else(not_found),
+            Json::Array(items) => match array_index(&last) {
+                Some(i) if i < items.len() => Ok(items.remove(i)),
+                _ => Err(not_found()),
+            },
+            _ => unreachable!("`parent_mut` only returns containers"),
+        }
+    }
+}
+
+// conversions from Rust values
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+macro_rules! json_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Json {
+                fn from(n: $t) -> Self {
+                    Json::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+// `i64`, `u64` and the like above 2^53 are rounded, see the note at the top
+json_from_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+// a `HashMap` has no order of its own, so keys are sorted to make the output stable
+impl<K: Into<String>, V: Into<Json>> From<HashMap<K, V>> for Json {
+    fn from(map: HashMap<K, V>) -> Self {
+        let mut entries: Vec<(String, Json)> =
+            map.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Json::Object(JsonObject { entries })
+    }
+}
+
+impl From<JsonObject> for Json {
+    fn from(object: JsonObject) -> Self {
+        Json::Object(object)
+    }
+}
+
+const CARAVAGGIO: &str = r#"
+    {
+        "name": "Caravaggio",
+        "born": 1571,
+        "works": ["The musicians", "The calling of St. Matthew"],
+        "patron": null,
+        "a/b": {"~c": true},
+        "escapes": "tab\there \u00e9 \ud83c\udfa8 \"quoted\""
+    }"#;
+
+pub fn use_json() -> Result<(), Box<dyn std::error::Error>> {
+    let mut doc: Json = CARAVAGGIO.parse()?;
+    println!("{:#}", doc);
+
+    // pointers, with `~1` for '/' and `~0` for '~' inside keys
+    println!("{:?}", doc.pointer("/a~1b/~0c"));
+    doc.set_pointer("/works/-", "Judith Beheading Holofernes")?;
+    doc.set_pointer("/died", 1610)?;
+    doc.remove_pointer("/works/0")?;
+    println!("{}", doc);
+
+    // strict: each of these is rejected, with where and why
+    let bad = [
+        "",
+        "[1, 2,]",
+        "{\"a\": 1,\n \"a\": 2}",
+        "[01]",
+        "{'a': 1}",
+        "[1.]",
+        "\"tab\there\"",
+        "\"\\ud800\"",
+        "[true, nul]",
+        "{\"a\" 1}",
+        "1e999",
+        "[1] // comment",
+    ];
+    for input in bad {
+        println!("{:?}: {}", input, Json::parse(input).unwrap_err());
+    }
+
+    // conversions
+    let mut scores = HashMap::new();
+    scores.insert("sean", vec![1.5, 2.0]);
+    scores.insert("lucia", vec![3.0]);
+    println!("{}", Json::from(scores));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_keep_order() {
+        let doc: Json = CARAVAGGIO.parse().unwrap();
+        let keys: Vec<_> = doc.as_object().unwrap().iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["name", "born", "works", "patron", "a/b", "escapes"]);
+        assert_eq!(Json::parse(&doc.to_string()).unwrap(), doc);
+        assert_eq!(Json::parse(&format!("{:#}", doc)).unwrap(), doc);
+        assert_eq!(
+            doc.pointer("/escapes").and_then(Json::as_str),
+            Some("tab\there é 🎨 \"quoted\"")
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let doc: Json = CARAVAGGIO.parse().unwrap();
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/born").and_then(Json::as_f64), Some(1571.0));
+        assert_eq!(
+            doc.pointer("/works/1").and_then(Json::as_str),
+            Some("The calling of St. Matthew")
+        );
+        assert_eq!(doc.pointer("/a~1b/~0c"), Some(&Json::Bool(true)));
+        assert_eq!(doc.pointer("/works/01"), None);
+        assert_eq!(doc.pointer("/works/2"), None);
+        assert_eq!(doc.pointer("works"), None);
+    }
+
+    #[test]
+    fn pointer_edits() {
+        let mut doc: Json = CARAVAGGIO.parse().unwrap();
+        doc.set_pointer("/works/-", "Judith Beheading Holofernes")
+            .unwrap();
+        doc.set_pointer("/patron", "Cardinal del Monte").unwrap();
+        doc.set_pointer("/died", 1610).unwrap();
+        assert_eq!(
+            doc.pointer("/works/2").and_then(Json::as_str),
+            Some("Judith Beheading Holofernes")
+        );
+        assert_eq!(
+            doc.remove_pointer("/works/0").unwrap(),
+            Json::from("The musicians")
+        );
+        if let Some(Json::Number(born)) = doc.pointer_mut("/born") {
+            *born += 1.0;
+        }
+        assert_eq!(doc.get("born"), Some(&Json::Number(1572.0)));
+        assert_eq!(
+            doc.set_pointer("/works/5", 0),
+            Err(PointerError::NotFound("/works/5".into()))
+        );
+        assert_eq!(
+            doc.set_pointer("/born/x", 0),
+            Err(PointerError::NotAContainer("/born/x".into()))
+        );
+        assert_eq!(
+            doc.remove_pointer("/a~2b"),
+            Err(PointerError::Syntax("/a~2b".into()))
+        );
+    }
+
+    #[test]
+    fn strict_parsing() {
+        let bad = [
+            "",
+            "[1, 2,]",
+            "{\"a\": 1,\n \"a\": 2}",
+            "[01]",
+            "{'a': 1}",
+            "[1.]",
+            "\"tab\there\"",
+            "\"\\ud800\"",
+            "[true, nul]",
+            "{\"a\" 1}",
+            "1e999",
+            "[1] // comment",
+        ];
+        for input in bad {
+            assert!(Json::parse(input).is_err(), "{:?}", input);
+        }
+        let err = Json::parse("{\n  \"a\": [1,\n    2,,\n  ]\n}").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 7));
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(Json::parse(&deep).unwrap_err().kind, JsonErrorKind::TooDeep);
+    }
+
+    #[test]
+    fn conversions() {
+        let mut scores = HashMap::new();
+        scores.insert("sean", vec![1.5, 2.0]);
+        scores.insert("lucia", vec![3.0]);
+        let json = Json::from(scores);
+        assert_eq!(json.to_string(), r#"{"lucia":[3],"sean":[1.5,2]}"#);
+        let record: JsonObject = [
+            ("id", Json::from(7u64)),
+            ("tags", Json::from(vec!["a", "b"])),
+            ("nickname", Json::from(None::<String>)),
+            ("ratio", Json::from(f64::NAN)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            Json::from(record).to_string(),
+            r#"{"id":7,"tags":["a","b"],"nickname":null,"ratio":null}"#
+        );
+    }
+}
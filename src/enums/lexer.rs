@@ -0,0 +1,438 @@
+// A hand-written lexer for a small C-like language
+// - built on the same character classes as `match_range`, with inclusive `..=` ranges
+// - every token carries its byte span plus the 1-based line and column where it starts
+// - identifiers, keywords and comments borrow from the source, so `Token<'a>` cannot outlive it
+// - the lexer is an `Iterator` of `Result`s and keeps going after an error, so one pass reports them all
+use crate::traits_generics::TableRenderer;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Digit,
+    Letter,
+    Whitespace,
+    Punctuation,
+    Other,
+}
+
+impl CharClass {
+    // `_` counts as a letter since it may start an identifier
+    pub fn of(c: char) -> CharClass {
+        match c {
+            '0'..='9' => CharClass::Digit,
+            'a'..='z' | 'A'..='Z' | '_' => CharClass::Letter,
+            ' ' | '\t' | '\n' | '\r' => CharClass::Whitespace,
+            '!'..='/' | ':'..='@' | '['..='`' | '{'..='~' => CharClass::Punctuation,
+            _ => CharClass::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyword {
+    If,
+    Else,
+    While,
+    For,
+    Return,
+    Int,
+    Float,
+    Void,
+}
+
+impl Keyword {
+    fn from_ident(ident: &str) -> Option<Keyword> {
+        Some(match ident {
+            "if" => Keyword::If,
+            "else" => Keyword::Else,
+            "while" => Keyword::While,
+            "for" => Keyword::For,
+            "return" => Keyword::Return,
+            "int" => Keyword::Int,
+            "float" => Keyword::Float,
+            "void" => Keyword::Void,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind<'a> {
+    Ident(&'a str),
+    Keyword(Keyword),
+    Int(i64),
+    Float(f64),
+    // escapes are already decoded, so this one owns its text
+    Str(String),
+    // operators and punctuation, e.g. `+`, `<=`, `&&`, `{`
+    Op(&'static str),
+    // the comment text without `//` or `/* */`
+    Comment(&'a str),
+}
+
+// byte offsets into the source, `source[start..end]` is the token's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub span: Span,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind {
+    InvalidChar(char),
+    UnterminatedString,
+    UnterminatedComment,
+    InvalidEscape(char),
+    InvalidNumber,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+    pub pos: Position,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.pos)?;
+        match &self.kind {
+            LexErrorKind::InvalidChar(c) => write!(f, "invalid character {:?}", c),
+            LexErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            LexErrorKind::UnterminatedComment => write!(f, "unterminated block comment"),
+            LexErrorKind::InvalidEscape(c) => write!(f, "invalid escape `\\{}`", c),
+            LexErrorKind::InvalidNumber => write!(f, "invalid number literal"),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+// longest first, so `<=` wins over `<` and `<<=` over `<<`
+const OPERATORS: &[&str] = &[
+    "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+=", "-=", "*=", "/=", "%=",
+    "++", "--", "->", "+", "-", "*", "/", "%", "=", "<", ">", "!", "&", "|", "^", "~", "(", ")",
+    "{", "}", "[", "]", ";", ",", ".", "?", ":",
+];
+
+pub struct Lexer<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Lexer {
+            source,
+            chars: source.char_indices().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.source.len(), |&(i, _)| i)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    // the char after the next one, without consuming anything
+    fn peek_second(&mut self) -> Option<char> {
+        let offset = self.offset();
+        self.source[offset..].chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn eat_while(&mut self, class: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&class) {
+            self.bump();
+        }
+    }
+
+    fn lex_ident(&mut self, start: usize) -> TokenKind<'a> {
+        self.eat_while(|c| matches!(CharClass::of(c), CharClass::Letter | CharClass::Digit));
+        let text = &self.source[start..self.offset()];
+        match Keyword::from_ident(text) {
+            Some(keyword) => TokenKind::Keyword(keyword),
+            None => TokenKind::Ident(text),
+        }
+    }
+
+    // `12`, `3.25`, `1e9`, `6.02e+23`
+    // - a `.` only belongs to the number if a digit follows, so `1.foo` is `1` `.` `foo`
+    fn lex_number(&mut self, start: usize) -> Result<TokenKind<'a>, LexErrorKind> {
+        let is_digit = |c| CharClass::of(c) == CharClass::Digit;
+        self.eat_while(is_digit);
+        let mut is_float = false;
+        if self.peek() == Some('.') && self.peek_second().is_some_and(is_digit) {
+            is_float = true;
+            self.bump();
+            self.eat_while(is_digit);
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            is_float = true;
+            self.bump();
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.bump();
+            }
+            if !self.peek().is_some_and(is_digit) {
+                return Err(LexErrorKind::InvalidNumber);
+            }
+            self.eat_while(is_digit);
+        }
+        // `123abc` is one bad token rather than `123` followed by `abc`
+        if self
+            .peek()
+            .is_some_and(|c| CharClass::of(c) == CharClass::Letter)
+        {
+            self.eat_while(|c| matches!(CharClass::of(c), CharClass::Letter | CharClass::Digit));
+            return Err(LexErrorKind::InvalidNumber);
+        }
+        let text = &self.source[start..self.offset()];
+        // an integer that does not fit in `i64` is an error too
+        let kind = if is_float {
+            text.parse().ok().map(TokenKind::Float)
+        } else {
+            text.parse().ok().map(TokenKind::Int)
+        };
+        kind.ok_or(LexErrorKind::InvalidNumber)
+    }
+
+    // the opening `"` has been consumed
+    // - an invalid escape is reported once the whole literal is read, so lexing resumes after it
+    fn lex_string(&mut self) -> Result<TokenKind<'a>, LexErrorKind> {
+        let mut text = String::new();
+        let mut bad_escape = None;
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(LexErrorKind::UnterminatedString),
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('0') => text.push('\0'),
+                    Some('\\') => text.push('\\'),
+                    Some('"') => text.push('"'),
+                    Some('\'') => text.push('\''),
+                    Some(c) => {
+                        bad_escape.get_or_insert(c);
+                    }
+                    None => return Err(LexErrorKind::UnterminatedString),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+        match bad_escape {
+            Some(c) => Err(LexErrorKind::InvalidEscape(c)),
+            None => Ok(TokenKind::Str(text)),
+        }
+    }
+
+    // the leading `/` has been consumed and the next char is `/` or `*`
+    fn lex_comment(&mut self, start: usize) -> Result<TokenKind<'a>, LexErrorKind> {
+        if self.bump() == Some('/') {
+            self.eat_while(|c| c != '\n');
+            return Ok(TokenKind::Comment(&self.source[start + 2..self.offset()]));
+        }
+        loop {
+            match self.bump() {
+                None => return Err(LexErrorKind::UnterminatedComment),
+                Some('*') if self.peek() == Some('/') => {
+                    self.bump();
+                    let end = self.offset();
+                    return Ok(TokenKind::Comment(&self.source[start + 2..end - 2]));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn lex_operator(&mut self, start: usize) -> Option<TokenKind<'a>> {
+        let rest = &self.source[start..];
+        let op = OPERATORS.iter().find(|op| rest.starts_with(*op))?;
+        for _ in 0..op.len() {
+            self.bump();
+        }
+        Some(TokenKind::Op(op))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.eat_while(|c| CharClass::of(c) == CharClass::Whitespace);
+        let start = self.offset();
+        let pos = Position {
+            line: self.line,
+            column: self.column,
+        };
+        let c = self.peek()?;
+
+        let kind = match CharClass::of(c) {
+            CharClass::Letter => Ok(self.lex_ident(start)),
+            CharClass::Digit => self.lex_number(start),
+            _ if c == '"' => {
+                self.bump();
+                self.lex_string()
+            }
+            _ if c == '/' && matches!(self.peek_second(), Some('/' | '*')) => {
+                self.bump();
+                self.lex_comment(start)
+            }
+            _ => self.lex_operator(start).ok_or_else(|| {
+                self.bump();
+                LexErrorKind::InvalidChar(c)
+            }),
+        };
+
+        let span = Span {
+            start,
+            end: self.offset(),
+        };
+        Some(match kind {
+            Ok(kind) => Ok(Token { kind, span, pos }),
+            Err(kind) => Err(LexError { kind, span, pos }),
+        })
+    }
+}
+
+// stops at the first error
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, LexError> {
+    Lexer::new(source).collect()
+}
+
+fn use_char_class() {
+    // the ends of each range are included
+    for c in ['0', '9', 'a', 'z', '~', 'é'] {
+        println!("{:?} is {:?}", c, CharClass::of(c));
+    }
+}
+
+fn use_tokenize() {
+    let source = "int main() {\n  // entry point\n  float x = 6.02e23 / 2;\n  return x >= 1.5 && s != \"a\\tb\";\n}";
+    let tokens = tokenize(source).unwrap();
+    TableRenderer::new()
+        .headers(&["pos", "kind"])
+        .print(tokens.iter().map(|t| (t.pos, format!("{:?}", t.kind))))
+        .unwrap();
+
+    // spans slice the original text
+    let span = tokens[1].span;
+    assert_eq!(&source[span.start..span.end], "main");
+}
+
+fn use_lex_errors() {
+    let source = "x = \"open\ny = 3 # 4;\nz = 12ab + \"\\q\";";
+    // the lexer keeps going after an error, so one pass reports all of them
+    for e in Lexer::new(source).filter_map(Result::err) {
+        println!("{}", e);
+    }
+}
+
+pub fn use_lexer() {
+    use_char_class();
+    use_tokenize();
+    use_lex_errors();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_class_ranges_include_their_ends() {
+        assert_eq!(CharClass::of('0'), CharClass::Digit);
+        assert_eq!(CharClass::of('9'), CharClass::Digit);
+        assert_eq!(CharClass::of('z'), CharClass::Letter);
+        assert_eq!(CharClass::of('Z'), CharClass::Letter);
+        assert_eq!(CharClass::of('~'), CharClass::Punctuation);
+        assert_eq!(CharClass::of('é'), CharClass::Other);
+    }
+
+    #[test]
+    fn tokenize_c_like_source() {
+        let source = "int main() {\n  // entry point\n  float x = 6.02e23 / 2;\n  return x >= 1.5 && s != \"a\\tb\";\n}";
+        let tokens = tokenize(source).unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Keyword(Keyword::Int));
+        assert_eq!(tokens[1].kind, TokenKind::Ident("main"));
+        assert_eq!(tokens[5].kind, TokenKind::Comment(" entry point"));
+        assert_eq!(tokens[5].pos, Position { line: 2, column: 3 });
+        assert_eq!(tokens[9].kind, TokenKind::Float(6.02e23));
+        assert_eq!(tokens[11].kind, TokenKind::Int(2));
+        assert!(tokens.contains(&Token {
+            kind: TokenKind::Op(">="),
+            span: Span { start: 66, end: 68 },
+            pos: Position {
+                line: 4,
+                column: 12
+            },
+        }));
+        assert!(tokens
+            .iter()
+            .any(|t| t.kind == TokenKind::Str("a\tb".to_string())));
+        for t in &tokens {
+            assert!(source.get(t.span.start..t.span.end).is_some());
+        }
+    }
+
+    #[test]
+    fn errors_do_not_stop_the_lexer() {
+        let source = "x = \"open\ny = 3 # 4;\nz = 12ab + \"\\q\";";
+        let errors = Lexer::new(source)
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
+        let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                LexErrorKind::UnterminatedString,
+                LexErrorKind::InvalidChar('#'),
+                LexErrorKind::InvalidNumber,
+                LexErrorKind::InvalidEscape('q'),
+            ]
+        );
+        assert_eq!(errors[1].pos, Position { line: 2, column: 7 });
+        assert_eq!(
+            tokenize("/* never closed").unwrap_err().kind,
+            LexErrorKind::UnterminatedComment
+        );
+    }
+}
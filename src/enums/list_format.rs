@@ -0,0 +1,245 @@
+// Renders a list in natural language, generalizing `match_slice`
+// - `match_slice` handles `[]`, `[a]`, `[a, b]` and `[a, .., b]`, but skips everyone in the middle
+// - `ListFormatter` writes every item: "a", "a and b", "a, b, and c", ...
+// - or truncates after `max_items`: "a, b, and 3 others"
+//
+// No intermediate allocation
+// - items are written straight to any `fmt::Write` with `write!`, one at a time
+// - which separator goes before an item depends on whether it is the last one shown,
+//   and `Peekable` tells us that with a single item of lookahead
+use std::fmt::{self, Display, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conjunction {
+    And,
+    Or,
+}
+
+impl Conjunction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Conjunction::And => "and",
+            Conjunction::Or => "or",
+        }
+    }
+}
+
+// a builder: each setter takes `self` by value and returns it, so calls chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListFormatter {
+    conjunction: Conjunction,
+    oxford_comma: bool,
+    max_items: Option<usize>,
+    quote: Option<char>,
+}
+
+impl Default for ListFormatter {
+    fn default() -> Self {
+        ListFormatter {
+            conjunction: Conjunction::And,
+            oxford_comma: true,
+            max_items: None,
+            quote: None,
+        }
+    }
+}
+
+impl ListFormatter {
+    pub fn new() -> Self {
+        ListFormatter::default()
+    }
+
+    pub fn conjunction(mut self, conjunction: Conjunction) -> Self {
+        self.conjunction = conjunction;
+        self
+    }
+
+    // the comma before the conjunction in lists of three or more, "a, b, and c"
+    pub fn oxford_comma(mut self, oxford_comma: bool) -> Self {
+        self.oxford_comma = oxford_comma;
+        self
+    }
+
+    // show at most `max` items, then "and N others"
+    // - `max` of zero is treated as one, since "and 3 others" alone says nothing
+    pub fn max_items(mut self, max: usize) -> Self {
+        self.max_items = Some(max.max(1));
+        self
+    }
+
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = Some(quote);
+        self
+    }
+
+    fn write_item<W: Write + ?Sized>(&self, out: &mut W, item: impl Display) -> fmt::Result {
+        match self.quote {
+            Some(q) => write!(out, "{}{}{}", q, item, q),
+            None => write!(out, "{}", item),
+        }
+    }
+
+    // comma and conjunction before the final entry of a list of `shown` entries
+    // - "a and b" has no comma, "a, b, and c" has one if `oxford_comma` is set
+    fn last_separator(&self, shown: usize) -> (&'static str, &'static str) {
+        let comma = if shown > 2 && self.oxford_comma {
+            ","
+        } else {
+            ""
+        };
+        (comma, self.conjunction.as_str())
+    }
+
+    pub fn write<W, I>(&self, out: &mut W, items: I) -> fmt::Result
+    where
+        W: Write + ?Sized,
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let mut items = items.into_iter().peekable();
+        let max = self.max_items.unwrap_or(usize::MAX);
+        let mut index = 0;
+        while let Some(item) = items.next() {
+            let more = items.peek().is_some();
+            // this is the last item we show, but not the last item there is
+            let truncating = more && index + 1 == max;
+            if index > 0 {
+                if more {
+                    out.write_str(", ")?;
+                } else {
+                    let (comma, conjunction) = self.last_separator(index + 1);
+                    write!(out, "{} {} ", comma, conjunction)?;
+                }
+            }
+            self.write_item(out, item)?;
+            if truncating {
+                // count what is left without collecting it
+                // - the "N others" entry counts as one more item for the comma rule
+                let rest = items.count();
+                let (comma, conjunction) = self.last_separator(index + 2);
+                let noun = if rest == 1 { "other" } else { "others" };
+                return write!(out, "{} {} {} {}", comma, conjunction, rest, noun);
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    // convenience for when a `String` is wanted anyway
+    pub fn format<I>(&self, items: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let mut out = String::new();
+        self.write(&mut out, items)
+            .expect("writing to a String cannot fail");
+        out
+    }
+}
+
+pub fn use_list_formatter() -> fmt::Result {
+    // any `Display` item and any `IntoIterator` work, here a range of integers
+    let formatter = ListFormatter::new().max_items(3);
+    println!("{}", formatter.format(1..=10));
+
+    // writes into an existing buffer, e.g. inside another `Display` impl
+    let mut greeting = String::from("hello, ");
+    ListFormatter::new().write(&mut greeting, ["sean", "lucia", "emma"])?;
+    println!("{}", greeting);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every combination we care about for lengths 0 to 5
+    #[test]
+    fn list_lengths_zero_to_five() {
+        let names = ["a", "b", "c", "d", "e"];
+        let cases: [(ListFormatter, [&str; 6]); 5] = [
+            (
+                ListFormatter::new(),
+                [
+                    "",
+                    "a",
+                    "a and b",
+                    "a, b, and c",
+                    "a, b, c, and d",
+                    "a, b, c, d, and e",
+                ],
+            ),
+            (
+                ListFormatter::new().oxford_comma(false),
+                [
+                    "",
+                    "a",
+                    "a and b",
+                    "a, b and c",
+                    "a, b, c and d",
+                    "a, b, c, d and e",
+                ],
+            ),
+            (
+                ListFormatter::new().conjunction(Conjunction::Or),
+                [
+                    "",
+                    "a",
+                    "a or b",
+                    "a, b, or c",
+                    "a, b, c, or d",
+                    "a, b, c, d, or e",
+                ],
+            ),
+            (
+                ListFormatter::new().max_items(2),
+                [
+                    "",
+                    "a",
+                    "a and b",
+                    "a, b, and 1 other",
+                    "a, b, and 2 others",
+                    "a, b, and 3 others",
+                ],
+            ),
+            (
+                ListFormatter::new()
+                    .max_items(1)
+                    .oxford_comma(false)
+                    .quote('"'),
+                [
+                    "",
+                    "\"a\"",
+                    "\"a\" and 1 other",
+                    "\"a\" and 2 others",
+                    "\"a\" and 3 others",
+                    "\"a\" and 4 others",
+                ],
+            ),
+        ];
+        for (formatter, expected) in cases {
+            for len in 0..=5 {
+                assert_eq!(
+                    formatter.format(&names[..len]),
+                    expected[len],
+                    "{:?} with {} items",
+                    formatter,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn any_display_items() {
+        let formatter = ListFormatter::new().max_items(3);
+        assert_eq!(formatter.format(1..=10), "1, 2, 3, and 7 others");
+
+        let mut greeting = String::from("hello, ");
+        ListFormatter::new()
+            .write(&mut greeting, ["sean", "lucia", "emma"])
+            .unwrap();
+        assert_eq!(greeting, "hello, sean, lucia, and emma");
+    }
+}
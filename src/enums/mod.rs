@@ -0,0 +1,18 @@
+mod accounts;
+mod binary_tree;
+mod color;
+mod expr;
+mod geometry;
+mod json;
+mod lexer;
+mod list_format;
+mod patterns;
+pub use accounts::*;
+pub use binary_tree::*;
+pub use color::*;
+pub use expr::*;
+pub use geometry::*;
+pub use json::*;
+pub use lexer::*;
+pub use list_format::*;
+pub use patterns::*;
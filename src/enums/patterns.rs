@@ -1,3 +1,4 @@
+use super::{Account, Axis, Language, Placement, Quadrant};
 use core::num;
 
 fn match_number(n: i32) {
@@ -14,14 +15,18 @@ fn match_number(n: i32) {
     println!("{}", n);
 }
 
-fn match_tuple(x: i32, y: i32) -> &'static str {
+// `Placement` from `geometry.rs` replaces the `&'static str` this used to return
+// - so callers match on an enum rather than compare strings
+fn match_tuple(x: i32, y: i32) -> Placement {
     use std::cmp::Ordering::*;
     let res = match (x.cmp(&0), y.cmp(&0)) {
-        (Equal, Equal) => "at the origin",
-        (_, Equal) => "on the x axis",
-        (Equal, _) => "on the y axis",
-        (Greater, Greater) => "in the first quadrant",
-        _ => "somewhere else",
+        (Equal, Equal) => Placement::Origin,
+        (_, Equal) => Placement::OnAxis(Axis::X),
+        (Equal, _) => Placement::OnAxis(Axis::Y),
+        (Greater, Greater) => Placement::In(Quadrant::First),
+        (Less, Greater) => Placement::In(Quadrant::Second),
+        (Less, Less) => Placement::In(Quadrant::Third),
+        (Greater, Less) => Placement::In(Quadrant::Fourth),
     };
 
     println!("{} and {}", x, y);
@@ -76,12 +81,6 @@ fn match_struct(p: Point) {
     println!("{:?}", p);
 }
 
-#[derive(Debug)]
-struct Account {
-    name: String,
-    language: String,
-}
-
 fn match_ref(a: Account) {
     match a {
         Account {
@@ -172,30 +171,40 @@ fn match_multiple_possibilities(c: Option<char>) {
 
 fn match_range(c: char) {
     match c {
-        // end-inclusive - different from the slice range syntax
-        '0'..'9' => println!("Digits"),
-        'a'..'z' | 'A'..'Z' => println!("Letters"),
+        // `..=` is end-inclusive, so '9' and 'z' match too
+        // - `'0'..'9'` is end-exclusive like the slice range syntax, so '9' would fall through to `_`
+        // - see `CharClass::of` in `lexer.rs` for the classes the lexer uses
+        '0'..='9' => println!("Digits"),
+        'a'..='z' | 'A'..='Z' => println!("Letters"),
         ' ' | '\n' | '\r' => println!("White spaces"),
         _ => println!("Punctuations?"),
     }
 }
 
 fn match_binding_with_at(a: Account) {
-    let e = "english".to_string();
-    let c = "chinese".to_string();
-
+    // with `language: String`, `language: e` did not compare against a variable `e`
+    // - it bound a new `e` to the field, so the first arm matched every account
+    // - a `Language` variant is a constant pattern, so each arm only matches its own language
     match &a {
         // with `var @ pattern` we can only create a single var and move/copy matched value to it
         // method call is not allowed
         acc @ Account {
             name: _,
-            language: e,
-        } => println!("An account in {}", acc.language),
+            language: Language::English,
+        } => println!("An english account: {}", acc.name),
 
         acc @ Account {
             name: _,
-            language: c,
-        } => println!("An account in {}", acc.language),
+            language: Language::Chinese,
+        } => println!("A chinese account: {}", acc.name),
+
+        // binding inside a variant is still allowed, `code` is the `String` in `Other`
+        Account {
+            name,
+            language: Language::Other(code),
+        } => println!("{} is in an unlisted language {:?}", name, code),
+
+        acc => println!("An account in {}", acc.language),
     }
 }
 
@@ -209,6 +218,8 @@ pub fn match_patterns() {
     println!("{}", match_tuple(0, 1));
     println!("{}", match_tuple(1, 1));
     println!("{}", match_tuple(-1, 1));
+    println!("{}", match_tuple(-1, -1));
+    assert_eq!(match_tuple(3, -4), Placement::of(3, -4));
 
     match_struct(Point {
         x: 1,
@@ -233,10 +244,12 @@ pub fn match_patterns() {
     match_slice(&["sean", "lucia"]);
     match_slice(&["sean", "lucia", "emma"]);
 
-    match_ref(Account {
-        name: "sean".into(),
-        language: "english".into(),
-    });
+    match_ref(Account::new("sean", Language::English));
+
+    match_binding_with_at(Account::new("sean", Language::English));
+    match_binding_with_at(Account::new("mei", Language::Chinese));
+    match_binding_with_at(Account::new("lucia", "es".parse().unwrap()));
+    match_binding_with_at(Account::new("worf", "klingon".parse().unwrap()));
 
     match_guard(Some(8), 8);
     match_guard(Some(1), 8);
@@ -248,6 +261,8 @@ pub fn match_patterns() {
     match_multiple_possibilities(None);
 
     match_range('1');
+    match_range('9');
+    match_range('z');
     match_range('c');
     match_range('C');
     match_range('\r');
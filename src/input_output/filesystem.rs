@@ -0,0 +1,757 @@
+// The `std::fs` functions listed in `osstr_path.rs`, behind a trait
+// - `RealFs` forwards to `std::fs`
+// - `InMemoryFs` keeps a tree of files, directories and symlinks in memory, so demos never touch the disk
+// - code that takes `&dyn Fs` works with either
+//
+// `std::fs::Metadata` and `DirEntry` cannot be built outside `std`, so the trait returns its own
+// - `Metadata` and `DirEntry` below carry the parts both backends can fill in
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    File,
+    Dir,
+    Symlink,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub file_type: FileType,
+    // bytes for a file, 0 for a directory, the target's length for a symlink
+    pub len: u64,
+}
+
+impl Metadata {
+    pub fn is_file(&self) -> bool {
+        self.file_type == FileType::File
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type == FileType::Dir
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    // the directory's path as given to `read_dir`, joined with the entry's name
+    pub path: PathBuf,
+    pub file_type: FileType,
+}
+
+impl DirEntry {
+    pub fn file_name(&self) -> &str {
+        self.path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("")
+    }
+}
+
+pub trait Fs {
+    // truncates an existing file, like `File::create`
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>>;
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    // sorted by name
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>>;
+    // follows symlinks
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+    // describes a symlink itself
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    // a file or a symlink, never a directory
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    // only an empty directory
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    // `link` points at `target`, which need not exist
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+}
+
+pub struct RealFs;
+
+fn metadata_of(m: fs::Metadata) -> Metadata {
+    let file_type = if m.file_type().is_symlink() {
+        FileType::Symlink
+    } else if m.is_dir() {
+        FileType::Dir
+    } else {
+        FileType::File
+    };
+    Metadata {
+        file_type,
+        len: if m.is_dir() { 0 } else { m.len() },
+    }
+}
+
+impl Fs for RealFs {
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(fs::File::create(path)?))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>> {
+        let mut entries = vec![];
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let file_type = if file_type.is_symlink() {
+                FileType::Symlink
+            } else if file_type.is_dir() {
+                FileType::Dir
+            } else {
+                FileType::File
+            };
+            entries.push(DirEntry {
+                path: entry.path(),
+                file_type,
+            });
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::metadata(path).map(metadata_of)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::symlink_metadata(path).map(metadata_of)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    #[cfg(unix)]
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    #[cfg(not(unix))]
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "symlinks are only supported on unix",
+        ))
+    }
+}
+
+// a file's bytes live behind an `Rc`, like an inode
+// - an open writer keeps writing to it even after the file is renamed or removed, as on unix
+#[derive(Debug, Clone)]
+enum Node {
+    File(Rc<RefCell<Vec<u8>>>),
+    Dir,
+    Symlink(PathBuf),
+}
+
+impl Node {
+    fn file_type(&self) -> FileType {
+        match self {
+            Node::File(_) => FileType::File,
+            Node::Dir => FileType::Dir,
+            Node::Symlink(_) => FileType::Symlink,
+        }
+    }
+}
+
+// symlinks followed while resolving one path before giving up, as Linux does
+const MAX_SYMLINKS: usize = 40;
+
+fn error(kind: io::ErrorKind, path: &Path, message: &str) -> io::Error {
+    io::Error::new(kind, format!("{}: {}", path.display(), message))
+}
+
+// every path is absolute and normalized, relative ones are taken from `/`
+// - `RefCell` because `Fs` methods take `&self`, like the `std::fs` functions that need no handle
+#[derive(Debug)]
+pub struct InMemoryFs {
+    nodes: RefCell<BTreeMap<PathBuf, Node>>,
+    read_only: RefCell<BTreeSet<PathBuf>>,
+}
+
+impl Default for InMemoryFs {
+    fn default() -> Self {
+        InMemoryFs::new()
+    }
+}
+
+impl InMemoryFs {
+    pub fn new() -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(PathBuf::from("/"), Node::Dir);
+        InMemoryFs {
+            nodes: RefCell::new(nodes),
+            read_only: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    // changes to `path` and everything under it fail with `PermissionDenied`, reads still work
+    pub fn set_read_only(&self, path: &Path) -> io::Result<()> {
+        let path = self.resolve(path, true)?;
+        self.read_only.borrow_mut().insert(path);
+        Ok(())
+    }
+
+    fn check_writable(&self, path: &Path) -> io::Result<()> {
+        if self.read_only.borrow().iter().any(|p| path.starts_with(p)) {
+            return Err(error(io::ErrorKind::PermissionDenied, path, "read-only"));
+        }
+        Ok(())
+    }
+
+    fn node(&self, path: &Path) -> Option<Node> {
+        self.nodes.borrow().get(path).cloned()
+    }
+
+    // an absolute path without `.`, `..` or symlinks, except a symlink in the last component when `follow_last` is off
+    // - every component but the last has to be an existing directory
+    fn resolve(&self, path: &Path, follow_last: bool) -> io::Result<PathBuf> {
+        let mut links = 0;
+        self.resolve_from(PathBuf::from("/"), path, follow_last, &mut links)
+    }
+
+    fn resolve_from(
+        &self,
+        mut resolved: PathBuf,
+        path: &Path,
+        follow_last: bool,
+        links: &mut usize,
+    ) -> io::Result<PathBuf> {
+        let components = path.components().collect::<Vec<_>>();
+        for (i, component) in components.iter().enumerate() {
+            let last = i + 1 == components.len();
+            match component {
+                Component::RootDir | Component::Prefix(_) => resolved = PathBuf::from("/"),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::Normal(name) => {
+                    let next = resolved.join(name);
+                    match self.node(&next) {
+                        Some(Node::Symlink(target)) if !last || follow_last => {
+                            *links += 1;
+                            if *links > MAX_SYMLINKS {
+                                return Err(error(
+                                    io::ErrorKind::Other,
+                                    path,
+                                    "too many levels of symbolic links",
+                                ));
+                            }
+                            // a relative target is relative to the link's directory
+                            resolved = self.resolve_from(resolved, &target, true, links)?;
+                        }
+                        Some(Node::File(_)) if !last => {
+                            return Err(error(
+                                io::ErrorKind::NotADirectory,
+                                path,
+                                "not a directory",
+                            ));
+                        }
+                        None if !last => {
+                            return Err(error(
+                                io::ErrorKind::NotFound,
+                                path,
+                                "no such file or directory",
+                            ));
+                        }
+                        _ => resolved = next,
+                    }
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn existing(&self, path: &Path, follow_last: bool) -> io::Result<(PathBuf, Node)> {
+        let resolved = self.resolve(path, follow_last)?;
+        match self.node(&resolved) {
+            Some(node) => Ok((resolved, node)),
+            None => Err(error(
+                io::ErrorKind::NotFound,
+                path,
+                "no such file or directory",
+            )),
+        }
+    }
+
+    // where a new entry for `path` goes; its parent has to be a directory
+    // - writing goes through a final symlink, `rename` and `symlink` replace or refuse the link itself
+    fn new_entry(&self, path: &Path, follow_last: bool) -> io::Result<PathBuf> {
+        let resolved = self.resolve(path, follow_last)?;
+        let parent = resolved.parent().unwrap_or(Path::new("/"));
+        match self.node(parent) {
+            Some(Node::Dir) => {}
+            Some(_) => return Err(error(io::ErrorKind::NotADirectory, path, "not a directory")),
+            None => {
+                return Err(error(
+                    io::ErrorKind::NotFound,
+                    path,
+                    "no such file or directory",
+                ))
+            }
+        }
+        self.check_writable(&resolved)?;
+        Ok(resolved)
+    }
+
+    fn open_for_write(&self, path: &Path) -> io::Result<Rc<RefCell<Vec<u8>>>> {
+        let resolved = self.new_entry(path, true)?;
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes.get(&resolved) {
+            Some(Node::File(data)) => {
+                data.borrow_mut().clear();
+                Ok(data.clone())
+            }
+            Some(_) => Err(error(io::ErrorKind::IsADirectory, path, "is a directory")),
+            None => {
+                let data = Rc::new(RefCell::new(vec![]));
+                nodes.insert(resolved, Node::File(data.clone()));
+                Ok(data)
+            }
+        }
+    }
+
+    fn children(&self, dir: &Path) -> Vec<(PathBuf, Node)> {
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|(p, _)| p.parent() == Some(dir))
+            .map(|(p, n)| (p.clone(), n.clone()))
+            .collect()
+    }
+}
+
+struct MemWriter {
+    data: Rc<RefCell<Vec<u8>>>,
+}
+
+impl Write for MemWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Fs for InMemoryFs {
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write + '_>> {
+        let data = self.open_for_write(path)?;
+        Ok(Box::new(MemWriter { data }))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.existing(path, true)? {
+            (_, Node::File(data)) => Ok(data.borrow().clone()),
+            _ => Err(error(io::ErrorKind::IsADirectory, path, "is a directory")),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let data = self.open_for_write(path)?;
+        data.borrow_mut().extend_from_slice(contents);
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>> {
+        match self.existing(path, true)? {
+            (resolved, Node::Dir) => Ok(self
+                .children(&resolved)
+                .into_iter()
+                .map(|(child, node)| DirEntry {
+                    path: path.join(child.file_name().unwrap()),
+                    file_type: node.file_type(),
+                })
+                .collect()),
+            _ => Err(error(io::ErrorKind::NotADirectory, path, "not a directory")),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let (_, node) = self.existing(path, true)?;
+        Ok(Metadata {
+            file_type: node.file_type(),
+            len: match &node {
+                Node::File(data) => data.borrow().len() as u64,
+                _ => 0,
+            },
+        })
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.existing(path, false)? {
+            (_, Node::Symlink(target)) => Ok(Metadata {
+                file_type: FileType::Symlink,
+                len: target.as_os_str().len() as u64,
+            }),
+            _ => self.metadata(path),
+        }
+    }
+
+    // the entry moves with everything under it, a symlink moves as a link
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (source, node) = self.existing(from, false)?;
+        self.check_writable(&source)?;
+        let dest = self.new_entry(to, false)?;
+        if source == dest {
+            return Ok(());
+        }
+        if dest.starts_with(&source) {
+            return Err(error(
+                io::ErrorKind::InvalidInput,
+                to,
+                "cannot move a directory into itself",
+            ));
+        }
+        match (self.node(&dest), &node) {
+            (None, _) => {}
+            (Some(Node::Dir), Node::Dir) if self.children(&dest).is_empty() => {}
+            (Some(Node::Dir), Node::Dir) => {
+                return Err(error(
+                    io::ErrorKind::DirectoryNotEmpty,
+                    to,
+                    "directory not empty",
+                ))
+            }
+            (Some(Node::Dir), _) => {
+                return Err(error(io::ErrorKind::IsADirectory, to, "is a directory"))
+            }
+            (Some(_), Node::Dir) => {
+                return Err(error(io::ErrorKind::NotADirectory, to, "not a directory"))
+            }
+            (Some(_), _) => {}
+        }
+        let mut nodes = self.nodes.borrow_mut();
+        let moved = nodes
+            .keys()
+            .filter(|p| p.starts_with(&source))
+            .cloned()
+            .collect::<Vec<_>>();
+        for old in moved {
+            let node = nodes.remove(&old).unwrap();
+            let new = dest.join(old.strip_prefix(&source).unwrap());
+            nodes.insert(new, node);
+        }
+        // read-only marks travel with the entries they protect
+        let mut read_only = self.read_only.borrow_mut();
+        let moved = read_only
+            .iter()
+            .filter(|p| p.starts_with(&source))
+            .cloned()
+            .collect::<Vec<_>>();
+        for old in moved {
+            read_only.remove(&old);
+            read_only.insert(dest.join(old.strip_prefix(&source).unwrap()));
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let (resolved, node) = self.existing(path, false)?;
+        if let Node::Dir = node {
+            return Err(error(io::ErrorKind::IsADirectory, path, "is a directory"));
+        }
+        self.check_writable(&resolved)?;
+        self.nodes.borrow_mut().remove(&resolved);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let (resolved, node) = self.existing(path, false)?;
+        if !matches!(node, Node::Dir) {
+            return Err(error(io::ErrorKind::NotADirectory, path, "not a directory"));
+        }
+        if resolved == Path::new("/") {
+            return Err(error(
+                io::ErrorKind::PermissionDenied,
+                path,
+                "cannot remove the root",
+            ));
+        }
+        if !self.children(&resolved).is_empty() {
+            return Err(error(
+                io::ErrorKind::DirectoryNotEmpty,
+                path,
+                "directory not empty",
+            ));
+        }
+        self.check_writable(&resolved)?;
+        self.nodes.borrow_mut().remove(&resolved);
+        Ok(())
+    }
+
+    // each missing directory on the way is created, existing ones, also through symlinks, are fine
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut partial = PathBuf::new();
+        for component in path.components() {
+            partial.push(component);
+            match self.resolve(&partial, true) {
+                Ok(resolved) => match self.node(&resolved) {
+                    Some(Node::Dir) => {}
+                    Some(_) => {
+                        return Err(error(
+                            io::ErrorKind::NotADirectory,
+                            &partial,
+                            "not a directory",
+                        ))
+                    }
+                    None => {
+                        self.check_writable(&resolved)?;
+                        self.nodes.borrow_mut().insert(resolved, Node::Dir);
+                    }
+                },
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let resolved = self.new_entry(link, false)?;
+        let mut nodes = self.nodes.borrow_mut();
+        if nodes.contains_key(&resolved) {
+            return Err(error(io::ErrorKind::AlreadyExists, link, "file exists"));
+        }
+        nodes.insert(resolved, Node::Symlink(target.to_path_buf()));
+        Ok(())
+    }
+}
+
+// everything the demo needs from a filesystem, through the trait
+fn tree(fs: &dyn Fs, dir: &Path, depth: usize, out: &mut String) -> io::Result<()> {
+    for entry in fs.read_dir(dir)? {
+        let marker = match entry.file_type {
+            FileType::Dir => "/",
+            FileType::Symlink => "@",
+            FileType::File => "",
+        };
+        out.push_str(&format!(
+            "{}{}{}\n",
+            "  ".repeat(depth),
+            entry.file_name(),
+            marker
+        ));
+        if entry.file_type == FileType::Dir {
+            tree(fs, &entry.path, depth + 1, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn sample_home() -> io::Result<InMemoryFs> {
+    let fs = InMemoryFs::new();
+    let p = Path::new;
+    fs.create_dir_all(p("/home/fwolfe/notes"))?;
+    fs.write(p("/home/fwolfe/notes/todo.txt"), b"write the rope\n")?;
+    {
+        let mut file = fs.create(p("/home/fwolfe/hello.txt"))?;
+        writeln!(file, "hello world")?;
+    }
+    fs.symlink(p("notes/todo.txt"), p("/home/fwolfe/todo"))?;
+    fs.symlink(p("/home/fwolfe"), p("/home/me"))?;
+    Ok(fs)
+}
+
+pub fn use_filesystem() -> io::Result<()> {
+    let fs = sample_home()?;
+    let p = Path::new;
+
+    // symlinks are followed, relative targets from the link's own directory
+    print!("{}", fs.read_to_string(p("/home/me/todo"))?);
+
+    // the error kinds `std::fs` would give
+    for path in ["/nope", "/home", "/home/fwolfe/hello.txt/x"] {
+        println!("{}: {:?}", path, fs.read(p(path)).unwrap_err().kind());
+    }
+
+    // a rename moves the whole subtree; the link `me` still points at the old path and now dangles
+    fs.rename(p("/home/fwolfe"), p("/home/frank"))?;
+    println!("/home/me exists: {}", fs.exists(p("/home/me")));
+    // the open-writer-survives-unlink rule
+    let mut writer = fs.create(p("/tmp.txt"))?;
+    fs.remove_file(p("/tmp.txt"))?;
+    writer.write_all(b"into the void")?;
+
+    let mut listing = String::new();
+    tree(&fs, p("/"), 0, &mut listing)?;
+    print!("{}", listing);
+
+    // the real thing, in the temp directory and not in the repo
+    let dir = std::env::temp_dir().join("programming_rust_fs");
+    RealFs.create_dir_all(&dir.join("sub"))?;
+    RealFs.write(&dir.join("sub/a.txt"), b"on disk")?;
+    println!("{}", RealFs.read_to_string(&dir.join("sub/a.txt"))?);
+    RealFs.remove_file(&dir.join("sub/a.txt"))?;
+    RealFs.remove_dir(&dir.join("sub"))?;
+    RealFs.remove_dir(&dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(path: &str) -> &Path {
+        Path::new(path)
+    }
+
+    fn kind(result: io::Result<()>) -> io::ErrorKind {
+        result.unwrap_err().kind()
+    }
+
+    #[test]
+    fn symlinks_are_followed() {
+        let fs = sample_home().unwrap();
+        // relative targets from the link's own directory
+        assert_eq!(
+            fs.read_to_string(p("/home/me/todo")).unwrap(),
+            "write the rope\n"
+        );
+        assert_eq!(
+            fs.read_to_string(p("home/me/notes/../hello.txt")).unwrap(),
+            "hello world\n"
+        );
+        assert_eq!(fs.metadata(p("/home/me/todo")).unwrap().len, 15);
+        assert_eq!(
+            fs.symlink_metadata(p("/home/me/todo")).unwrap().file_type,
+            FileType::Symlink
+        );
+        let mut listing = String::new();
+        tree(&fs, p("/home"), 0, &mut listing).unwrap();
+        assert_eq!(
+            listing,
+            "fwolfe/\n  hello.txt\n  notes/\n    todo.txt\n  todo@\nme@\n"
+        );
+    }
+
+    #[test]
+    fn std_fs_error_kinds() {
+        let fs = sample_home().unwrap();
+        assert_eq!(kind(fs.read(p("/nope")).map(drop)), io::ErrorKind::NotFound);
+        assert_eq!(
+            kind(fs.read(p("/home")).map(drop)),
+            io::ErrorKind::IsADirectory
+        );
+        assert_eq!(
+            kind(fs.write(p("/home/fwolfe/hello.txt/x"), b"")),
+            io::ErrorKind::NotADirectory
+        );
+        assert_eq!(
+            kind(fs.remove_dir(p("/home/fwolfe"))),
+            io::ErrorKind::DirectoryNotEmpty
+        );
+        assert_eq!(
+            kind(fs.remove_file(p("/home/fwolfe/notes"))),
+            io::ErrorKind::IsADirectory
+        );
+        assert_eq!(
+            kind(fs.symlink(p("x"), p("/home/me"))),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            kind(fs.rename(p("/home"), p("/home/fwolfe/home"))),
+            io::ErrorKind::InvalidInput
+        );
+        fs.symlink(p("/loop/a"), p("/loop")).unwrap();
+        assert_eq!(kind(fs.read(p("/loop/a")).map(drop)), io::ErrorKind::Other);
+        fs.set_read_only(p("/home/fwolfe/notes")).unwrap();
+        assert_eq!(
+            kind(fs.write(p("/home/me/todo"), b"")),
+            io::ErrorKind::PermissionDenied
+        );
+        assert!(fs.write(p("/home/fwolfe/other.txt"), b"fine").is_ok());
+    }
+
+    #[test]
+    fn rename_and_unlink() {
+        let fs = sample_home().unwrap();
+        // the link `me` still points at the old path and now dangles
+        fs.rename(p("/home/fwolfe"), p("/home/frank")).unwrap();
+        assert!(fs.exists(p("/home/frank/notes/todo.txt")));
+        assert!(!fs.exists(p("/home/me")));
+        fs.remove_file(p("/home/me")).unwrap();
+        // an open writer survives the unlink
+        let mut writer = fs.create(p("/tmp.txt")).unwrap();
+        fs.remove_file(p("/tmp.txt")).unwrap();
+        writer.write_all(b"into the void").unwrap();
+        assert!(!fs.exists(p("/tmp.txt")));
+    }
+
+    #[test]
+    fn real_fs_in_a_temp_dir() {
+        let dir = std::env::temp_dir().join(format!("programming_rust_fs_{}", std::process::id()));
+        RealFs.create_dir_all(&dir.join("sub")).unwrap();
+        RealFs.write(&dir.join("sub/a.txt"), b"on disk").unwrap();
+        assert_eq!(
+            RealFs.read_to_string(&dir.join("sub/a.txt")).unwrap(),
+            "on disk"
+        );
+        assert_eq!(RealFs.read_dir(&dir).unwrap()[0].file_type, FileType::Dir);
+        RealFs.remove_file(&dir.join("sub/a.txt")).unwrap();
+        RealFs.remove_dir(&dir.join("sub")).unwrap();
+        RealFs.remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn rename_replaces_a_final_symlink_itself() {
+        let fs = InMemoryFs::new();
+        fs.write(Path::new("/a"), b"a").unwrap();
+        fs.write(Path::new("/b"), b"b").unwrap();
+        fs.symlink(Path::new("/b"), Path::new("/link")).unwrap();
+        fs.rename(Path::new("/a"), Path::new("/link")).unwrap();
+        assert_eq!(fs.read_to_string(Path::new("/b")).unwrap(), "b");
+        let link = fs.symlink_metadata(Path::new("/link")).unwrap();
+        assert_eq!(link.file_type, FileType::File);
+        assert_eq!(fs.read_to_string(Path::new("/link")).unwrap(), "a");
+    }
+
+    #[test]
+    fn rename_keeps_read_only_marks() {
+        let fs = InMemoryFs::new();
+        fs.create_dir_all(Path::new("/d/ro")).unwrap();
+        fs.set_read_only(Path::new("/d/ro")).unwrap();
+        fs.rename(Path::new("/d"), Path::new("/e")).unwrap();
+        let err = fs.write(Path::new("/e/ro/f"), b"").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(fs.write(Path::new("/d/ro/f"), b"").is_err());
+        fs.create_dir_all(Path::new("/d/ro")).unwrap();
+        assert!(fs.write(Path::new("/d/ro/f"), b"").is_ok());
+    }
+}
@@ -1,2 +1,6 @@
+mod filesystem;
 mod osstr_path;
+mod writers;
+pub use filesystem::*;
 pub use osstr_path::*;
+pub use writers::*;
@@ -35,6 +35,7 @@
 // - `entry.file_name()`
 // - `entry.path()`
 // - `entry.file_type()` - return`std::io::Result<FileType>` which has `is_file` ,`is_dir` and `is_symlink` methods
+// - most of these are behind the `Fs` trait in `filesystem.rs`, with an in-memory backend for demos
 
 use std::ffi::OsStr;
 use std::path::Path;
@@ -0,0 +1,516 @@
+// `io::Write` adapters that wrap any other writer, so they stack: `CountingWriter<TeeWriter<File, Vec<u8>>>`
+// - each one is generic over what it wraps, like `BufWriter<W>`, with `get_ref`, `get_mut` and `into_inner`
+// - `write` may take fewer bytes than it was given, and every adapter passes on exactly what the inner writer took
+// - so `write_all` on the outside loops correctly however the inside splits the bytes
+// - `flush` always reaches the innermost writer
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// the same bytes to two writers
+// - `a` decides how much of `buf` is taken, `b` then gets all of that
+// - if `b` fails, `a` has bytes `b` does not
+pub struct TeeWriter<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        TeeWriter { a, b }
+    }
+
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.a, &self.b)
+    }
+
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.a, &mut self.b)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.a.write(buf)?;
+        self.b.write_all(&buf[..n])?;
+        Ok(n)
+    }
+
+    // both are flushed even if the first fails, the first error is returned
+    fn flush(&mut self) -> io::Result<()> {
+        let a = self.a.flush();
+        let b = self.b.flush();
+        a.and(b)
+    }
+}
+
+// counts the bytes and `\n`s that reached the inner writer
+pub struct CountingWriter<W> {
+    inner: W,
+    bytes: u64,
+    lines: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter {
+            inner,
+            bytes: 0,
+            lines: 0,
+        }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes += n as u64;
+        self.lines += buf[..n].iter().filter(|&&b| b == b'\n').count() as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// starts every line with whatever `prefix` returns at that moment, e.g. a timestamp
+// - the prefix is written when the line's first byte is, not when the previous line ends,
+//   so a timestamp says when the line started and no dangling prefix follows the last `\n`
+// - `write` takes at most one line at a time, the `\n` included
+pub struct LinePrefixWriter<W, F> {
+    inner: W,
+    prefix: F,
+    at_line_start: bool,
+}
+
+impl<W: Write, F: FnMut() -> String> LinePrefixWriter<W, F> {
+    pub fn new(inner: W, prefix: F) -> Self {
+        LinePrefixWriter {
+            inner,
+            prefix,
+            at_line_start: true,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, F: FnMut() -> String> Write for LinePrefixWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.at_line_start {
+            // the prefix is not part of `buf`, so it is not counted in what we return
+            self.inner.write_all((self.prefix)().as_bytes())?;
+            self.at_line_start = false;
+        }
+        let line_end = buf
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(buf.len(), |i| i + 1);
+        let n = self.inner.write(&buf[..line_end])?;
+        if n > 0 && buf[n - 1] == b'\n' {
+            self.at_line_start = true;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// time as `ThrottledWriter` sees it, so tests do not have to wait
+pub trait Clock {
+    // time since some fixed starting point
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+// `sleep` moves the time forward at once, and `advance` does so from outside
+// - `Cell`, since `Clock` methods take `&self` and a test holds a `&ManualClock` next to the writer's
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+    slept: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    // the total of all `sleep` calls
+    pub fn slept(&self) -> Duration {
+        self.slept.get()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        self.slept.set(self.slept.get() + duration);
+    }
+}
+
+// at most `rate` bytes per second, on average
+// - a token bucket: a byte costs a token, tokens come back at `rate` per second, up to `burst` of them
+// - `write` sleeps until it can send `min(buf.len(), burst)` bytes, so large writes go out in `burst`-sized pieces
+pub struct ThrottledWriter<W, C> {
+    inner: W,
+    clock: C,
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Duration,
+}
+
+impl<W: Write> ThrottledWriter<W, SystemClock> {
+    pub fn new(inner: W, bytes_per_second: u32) -> Self {
+        ThrottledWriter::with_clock(inner, bytes_per_second, SystemClock::new())
+    }
+}
+
+impl<W: Write, C: Clock> ThrottledWriter<W, C> {
+    // the bucket starts full with one second's worth of bytes
+    pub fn with_clock(inner: W, bytes_per_second: u32, clock: C) -> Self {
+        let rate = bytes_per_second.max(1) as f64;
+        ThrottledWriter {
+            inner,
+            last: clock.now(),
+            clock,
+            rate,
+            burst: rate,
+            tokens: rate,
+        }
+    }
+
+    // how many bytes may go out at once after a quiet spell
+    pub fn burst(mut self, bytes: u32) -> Self {
+        self.burst = bytes.max(1) as f64;
+        self.tokens = self.tokens.min(self.burst);
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn refill(&mut self) {
+        let now = self.clock.now();
+        let elapsed = now.saturating_sub(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+    }
+}
+
+impl<W: Write, C: Clock> Write for ThrottledWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.refill();
+        let want = (buf.len() as f64).min(self.burst);
+        if self.tokens < want {
+            self.clock
+                .sleep(Duration::from_secs_f64((want - self.tokens) / self.rate));
+            self.refill();
+        }
+        // `refill` can land a hair short of `want` through rounding, never below it by a whole byte
+        let allowed = (self.tokens.round() as usize).clamp(1, buf.len());
+        let n = self.inner.write(&buf[..allowed])?;
+        self.tokens -= n as f64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// misbehaves on purpose, to see how the code around it copes
+// - `max_write`: take at most this many bytes per `write`, a short write
+// - `fail_after`: after this many bytes every `write` is an error
+// - `interrupt_every`: every n-th `write` is `ErrorKind::Interrupted`, which `write_all` retries
+// - `fail_flush`: `flush` is an error
+pub struct FailingWriter<W> {
+    inner: W,
+    max_write: usize,
+    fail_after: Option<u64>,
+    interrupt_every: Option<u32>,
+    fail_flush: bool,
+    written: u64,
+    calls: u32,
+}
+
+impl<W: Write> FailingWriter<W> {
+    // does not fail until told to
+    pub fn new(inner: W) -> Self {
+        FailingWriter {
+            inner,
+            max_write: usize::MAX,
+            fail_after: None,
+            interrupt_every: None,
+            fail_flush: false,
+            written: 0,
+            calls: 0,
+        }
+    }
+
+    pub fn max_write(mut self, bytes: usize) -> Self {
+        self.max_write = bytes.max(1);
+        self
+    }
+
+    pub fn fail_after(mut self, bytes: u64) -> Self {
+        self.fail_after = Some(bytes);
+        self
+    }
+
+    pub fn interrupt_every(mut self, calls: u32) -> Self {
+        self.interrupt_every = Some(calls.max(1));
+        self
+    }
+
+    pub fn fail_flush(mut self, fail: bool) -> Self {
+        self.fail_flush = fail;
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for FailingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.calls += 1;
+        if self
+            .interrupt_every
+            .is_some_and(|k| self.calls.is_multiple_of(k))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "injected interrupt",
+            ));
+        }
+        let left = self
+            .fail_after
+            .map_or(u64::MAX, |limit| limit - self.written.min(limit));
+        if left == 0 && !buf.is_empty() {
+            return Err(io::Error::other(format!(
+                "injected failure after {} bytes",
+                self.written
+            )));
+        }
+        let allowed = buf
+            .len()
+            .min(self.max_write)
+            .min(left.try_into().unwrap_or(usize::MAX));
+        let n = self.inner.write(&buf[..allowed])?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.fail_flush {
+            return Err(io::Error::other("injected flush failure"));
+        }
+        self.inner.flush()
+    }
+}
+
+pub fn use_writers() -> io::Result<()> {
+    // a short-writing, interrupting writer under the others: `write_all` still gets every byte through
+    let text = "first line\nsecond line\nthird";
+    let flaky = FailingWriter::new(vec![]).max_write(3).interrupt_every(4);
+    let mut line = 0;
+    let prefixed = LinePrefixWriter::new(flaky, || {
+        line += 1;
+        format!("{:>2}| ", line)
+    });
+    let mut out = CountingWriter::new(TeeWriter::new(prefixed, vec![]));
+    out.write_all(text.as_bytes())?;
+    out.flush()?;
+    // the count is of what reached the tee, the prefixes are added below it
+    println!("{} bytes, {} newlines", out.bytes(), out.lines());
+    let (prefixed, _copy) = out.into_inner().into_inner();
+    println!(
+        "{}",
+        String::from_utf8_lossy(&prefixed.into_inner().into_inner())
+    );
+
+    // errors come through the stack unchanged
+    let mut out = CountingWriter::new(FailingWriter::new(vec![]).fail_after(10));
+    if let Err(e) = out.write_all(b"0123456789abc") {
+        println!("{} ({} bytes counted)", e, out.bytes());
+    }
+
+    // and to stdout, with the real clock: 40 bytes a second after the first 40
+    let start = Instant::now();
+    let mut stdout = LinePrefixWriter::new(
+        ThrottledWriter::new(io::stdout(), 40).burst(40),
+        move || format!("[{:>5.2}s] ", start.elapsed().as_secs_f64()),
+    );
+    for i in 1..=3 {
+        writeln!(stdout, "throttled line {}", i)?;
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_writes_through_the_stack() {
+        let text = "first line\nsecond line\nthird";
+        let flaky = FailingWriter::new(vec![]).max_write(3).interrupt_every(4);
+        let mut line = 0;
+        let prefixed = LinePrefixWriter::new(flaky, || {
+            line += 1;
+            format!("{:>2}| ", line)
+        });
+        let mut out = CountingWriter::new(TeeWriter::new(prefixed, vec![]));
+        out.write_all(text.as_bytes()).unwrap();
+        out.flush().unwrap();
+        assert_eq!((out.bytes(), out.lines()), (text.len() as u64, 2));
+        let (prefixed, copy) = out.into_inner().into_inner();
+        assert_eq!(copy, text.as_bytes());
+        let numbered = String::from_utf8(prefixed.into_inner().into_inner()).unwrap();
+        assert_eq!(numbered, " 1| first line\n 2| second line\n 3| third");
+    }
+
+    #[test]
+    fn errors_pass_through_unchanged() {
+        let mut out = CountingWriter::new(FailingWriter::new(vec![]).fail_after(10));
+        let err = out.write_all(b"0123456789abc").unwrap_err();
+        assert_eq!(err.to_string(), "injected failure after 10 bytes");
+        assert_eq!(out.bytes(), 10);
+        let mut tee = TeeWriter::new(vec![], FailingWriter::new(vec![]).fail_flush(true));
+        assert!(tee.flush().is_err());
+    }
+
+    #[test]
+    fn throttling_with_a_manual_clock() {
+        // 1000 bytes at 100 per second: the first 100 at once, then one second per 100
+        let clock = ManualClock::new();
+        let mut throttled = ThrottledWriter::with_clock(CountingWriter::new(vec![]), 100, &clock);
+        throttled.write_all(&[b'x'; 1000]).unwrap();
+        assert_eq!(throttled.get_ref().bytes(), 1000);
+        assert!(clock.slept().abs_diff(Duration::from_secs(9)) < Duration::from_millis(1));
+        // after a quiet spell a full burst goes out without waiting
+        clock.advance(Duration::from_secs(5));
+        let before = clock.slept();
+        throttled.write_all(&[b'y'; 100]).unwrap();
+        assert_eq!(clock.slept(), before);
+        // with a smaller burst the same 100 bytes go out 10 at a time
+        let mut small =
+            ThrottledWriter::with_clock(FailingWriter::new(vec![]), 100, &clock).burst(10);
+        small.write_all(&[b'z'; 100]).unwrap();
+        assert!(
+            (clock.slept() - before).abs_diff(Duration::from_millis(900))
+                < Duration::from_millis(1)
+        );
+        assert_eq!(small.into_inner().into_inner().len(), 100);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::traits_generics::TableRenderer;
 use std::collections::HashMap;
 use std::iter::Peekable;
 // `map` and `filter` returns `std::iter::Map` and `std::iter::Filter` type
@@ -38,7 +39,10 @@ fn use_flat_map() {
         // - in this case, a Vec<&str>
         .flat_map(|country| &major_cities[country])
         .collect::<Vec<_>>();
-    println!("{:?}", cities);
+    TableRenderer::new()
+        .headers(&["city"])
+        .print(cities.iter().map(|city| [city]))
+        .unwrap();
 }
 
 // `flatten`
@@ -55,7 +59,10 @@ fn use_flatten() {
     // - the element of calling iterator, i.e. `major_cities.values()`, must implement `IntoInterator`, i.e., iteratable
     // - in this case, a Vec<&str>
     let cities = major_cities.values().flatten().collect::<Vec<_>>();
-    println!("{:?}", cities);
+    TableRenderer::new()
+        .headers(&["city"])
+        .print(cities.iter().map(|city| [city]))
+        .unwrap();
 }
 
 // `flat_map` is equivalent to `map` + `flatten`
@@ -67,20 +74,32 @@ fn to_uppercase(s: &str) {
         .collect::<Vec<_>>();
 
     let res2 = s.chars().flat_map(char::to_uppercase).collect::<Vec<_>>();
-    println!("{:?} vs {:?}", res, res2);
+    // one row per char, the two columns should always agree
+    TableRenderer::new()
+        .headers(&["map + flatten", "flat_map"])
+        .print(res.iter().zip(&res2))
+        .unwrap();
 }
 
 // `take` and `take_while` -
 fn use_take() {
     let vec: Vec<f64> = std::iter::from_fn(|| Some(0.1)).take(10).collect();
-    println!("{:?}", vec);
+    TableRenderer::new()
+        .numbered(true)
+        .headers(&["value"])
+        .print(vec.iter().map(|n| [n]))
+        .unwrap();
 }
 
 fn use_take_while() {
     let vec: Vec<f64> = std::iter::successors(Some(1.0), |&z| Some(z + z * 0.05))
         .take_while(|&n| n < 2.0)
         .collect();
-    println!("{:?}", vec);
+    TableRenderer::new()
+        .numbered(true)
+        .headers(&["z"])
+        .print(vec.iter().map(|z| [z]))
+        .unwrap();
 }
 
 // `skip` and `skip_while` - complement to `take` and `take_while`
@@ -101,11 +120,46 @@ where
         tokens.next();
     }
 }
+// same idea for a floating point literal: digits, an optional `.` and an optional exponent
+// - `next_if` peeks and consumes in one step, so the char after the literal is left in place
+// - the text is collected and handed to `f64::from_str` rather than accumulated like `parse_number` does,
+//   which would overflow past `u32::MAX` and lose the leading zeros of a fraction such as `.05`
+// - on a malformed literal like `1.2.3` or `2e` the text read so far is returned as the error
+pub fn parse_float<I>(tokens: &mut Peekable<I>) -> Result<f64, String>
+where
+    I: Iterator<Item = char>,
+{
+    let mut literal = String::new();
+    while let Some(c) = tokens.next_if(|c| c.is_ascii_digit() || *c == '.') {
+        literal.push(c);
+    }
+    if let Some(e) = tokens.next_if(|c| *c == 'e' || *c == 'E') {
+        literal.push(e);
+        if let Some(sign) = tokens.next_if(|c| *c == '+' || *c == '-') {
+            literal.push(sign);
+        }
+        while let Some(d) = tokens.next_if(char::is_ascii_digit) {
+            literal.push(d);
+        }
+    }
+    literal.parse().map_err(|_| literal)
+}
+
 fn use_peek() {
+    // each call picks up where the previous one stopped, so the rows must be built in order
     let mut chars = "226153980,1766319049".chars().peekable();
-    println!("{}", parse_number(&mut chars));
-    println!("{:?}", chars.next());
-    println!("{}", parse_number(&mut chars));
+    let mut rows = vec![];
+    rows.push(("parse_number", parse_number(&mut chars).to_string()));
+    rows.push(("next", format!("{:?}", chars.next())));
+    rows.push(("parse_number", parse_number(&mut chars).to_string()));
+
+    let mut chars = "6.02e23 * 2.5".chars().peekable();
+    rows.push(("parse_float", format!("{:?}", parse_float(&mut chars))));
+    rows.push(("next", format!("{:?}", chars.next())));
+    TableRenderer::new()
+        .headers(&["call", "result"])
+        .print(rows)
+        .unwrap();
 }
 
 // `fuse` takes an iterator and prodoces one that will definitely continue to return `None` once has done so the first time
@@ -146,27 +200,25 @@ fn use_fuse() {
 
 fn use_reverse() {
     let meals = ["breakfast", "lunch", "dinner"];
-    println!("{:?}", meals);
     let rev_meals = meals.iter().rev().collect::<Vec<_>>();
-    println!("{:?}", rev_meals);
+    TableRenderer::new()
+        .headers(&["meals", "rev"])
+        .print(meals.iter().zip(&rev_meals))
+        .unwrap();
 
+    // `next` on the reversed copy and `next_back` on the original draw the same items
     let mut rev_meals_iter = rev_meals.iter();
     let mut meals_iter = meals.iter();
-    println!(
-        "{:?} == {:?}",
-        rev_meals_iter.next(),
-        meals_iter.next_back()
-    );
-    println!(
-        "{:?} == {:?}",
-        rev_meals_iter.next(),
-        meals_iter.next_back()
-    );
-    println!(
-        "{:?} == {:?}",
-        rev_meals_iter.next(),
-        meals_iter.next_back()
-    );
+    let rows = (0..meals.len()).map(|_| {
+        (
+            format!("{:?}", rev_meals_iter.next()),
+            format!("{:?}", meals_iter.next_back()),
+        )
+    });
+    TableRenderer::new()
+        .headers(&["rev.next()", "meals.next_back()"])
+        .print(rows)
+        .unwrap();
 }
 
 // `inspect` - apply a closure to a ref to each item of iterator and pass through
@@ -184,10 +236,11 @@ fn use_inspect() {
 // - result is `rev`ersible if both of its underlying iterators are
 fn use_chain() {
     let v = (1..4).chain(vec![4, 5, 6, 7]).collect::<Vec<_>>();
-    println!("{:?}", v);
-
     let v2 = (1..4).chain(vec![4, 5, 6, 7]).rev().collect::<Vec<_>>();
-    println!("{:?}", v2);
+    TableRenderer::new()
+        .headers(&["chain", "chain + rev"])
+        .print(v.iter().zip(&v2))
+        .unwrap();
 }
 
 // `enumerate`, `zip`
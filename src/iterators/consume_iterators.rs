@@ -1,19 +1,14 @@
 // simple accumulation: count, sum, product
 
+use crate::traits_generics::{factorial, triangle, TableRenderer};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
-fn triangle(n: u64) -> u64 {
-    (1..n).sum()
-}
-
-fn factorial(n: u64) -> u64 {
-    (1..n).product()
-}
-
+// `triangle` and `factorial` live in `traits_generics/num.rs`, generic over any `Num`
+// - they still end in `sum()` and `product()`, over `1..=n` rather than the `1..n` they used to miss `n` with
 fn use_simple_accumulation() {
-    println!("factorial(20) is {}", factorial(20));
-    println!("triangle(20) is {}", triangle(20));
+    println!("factorial(20) is {}", factorial(20u64));
+    println!("triangle(20) is {}", triangle(20u64));
 }
 
 // max, min
@@ -28,20 +23,20 @@ fn cmp(lhs: &i32, rhs: &i32) -> Ordering {
 fn use_max_min() {
     // max, min
     let v = vec![-2, -1, 0, 1, 2, 3];
-    println!("{:?} max: {:?}", v, v.iter().max());
-    println!("{:?} min: {:?}", v, v.iter().min());
+    println!("v is:");
+    TableRenderer::new().print([&v]).unwrap();
 
     // max_by, min_by
-    println!(
-        "{:?} max_by: {:?}",
-        v,
-        v.iter().max_by(|lhs, rhs| cmp(lhs, rhs))
-    );
-    println!(
-        "{:?} min_by: {:?}",
-        v,
-        v.iter().min_by(|lhs, rhs| cmp(lhs, rhs))
-    );
+    let results = [
+        ("max", v.iter().max()),
+        ("min", v.iter().min()),
+        ("max_by", v.iter().max_by(|lhs, rhs| cmp(lhs, rhs))),
+        ("min_by", v.iter().min_by(|lhs, rhs| cmp(lhs, rhs))),
+    ];
+    TableRenderer::new()
+        .headers(&["call", "result"])
+        .print(results.iter().map(|(call, n)| (call, format!("{:?}", n))))
+        .unwrap();
 
     // max_by_key, min_by_key
     let mut populations = HashMap::new();
@@ -51,15 +46,21 @@ fn use_max_min() {
     populations.insert("Boring", 7762);
     populations.insert("The Dalles", 15340);
 
+    // a `HashMap` iterates in no particular order, so sort by city
+    let mut rows = populations.iter().collect::<Vec<_>>();
+    rows.sort();
+    TableRenderer::new()
+        .headers(&["city", "population"])
+        .print(&rows)
+        .unwrap();
+
     println!(
-        "populations {:?} max_by_key pop is: {:?}",
-        populations,
+        "max_by_key pop is: {:?}",
         populations.iter().max_by_key(|&(name, pop)| pop)
     );
 
     println!(
-        "populations {:?} min_by_key pop is: {:?}",
-        populations,
+        "min_by_key pop is: {:?}",
         populations.iter().min_by_key(|&(name, pop)| pop)
     );
 }
@@ -124,16 +125,19 @@ fn use_position() {
     );
 
     let text = b"Xerxes";
+    TableRenderer::new()
+        .numbered(true)
+        .headers(&["byte"])
+        .print(text.iter().map(|b| [b]))
+        .unwrap();
     println!(
-        "The index of first b'e' from right in {:?} is: {:?}",
-        text,
+        "The index of first b'e' from right is: {:?}",
         // `iter()` return an iterator over `&u8`, not `u8`
         text.iter().rposition(|&c| c == b'e')
     );
 
     println!(
-        "The index of first b'z' from right in {:?} is: {:?}",
-        text,
+        "The index of first b'z' from right is: {:?}",
         text.iter().rposition(|&c| c == b'z')
     );
 }
@@ -153,13 +157,30 @@ fn use_position() {
 
 fn use_fold() {
     let a = [5, 6, 7, 8, 9, 10];
-    println!("Sum of {:?} is: {}", a, a.iter().fold(0, |n, i| n + i));
-    println!("Count of {:?} is: {}", a, a.iter().fold(0, |n, _| n + 1));
-    println!("Product of {:?} is: {}", a, a.iter().fold(1, |n, i| n * i));
-
-    println!("Sum of {:?} is: {}", a, a.iter().rfold(0, |n, i| n + i));
-    println!("Count of {:?} is: {}", a, a.iter().rfold(0, |n, _| n + 1));
-    println!("Product of {:?} is: {}", a, a.iter().rfold(1, |n, i| n * i));
+    println!("a is:");
+    TableRenderer::new().print([&a]).unwrap();
+
+    let rows = [
+        (
+            "sum",
+            a.iter().fold(0, |n, i| n + i),
+            a.iter().rfold(0, |n, i| n + i),
+        ),
+        (
+            "count",
+            a.iter().fold(0, |n, _| n + 1),
+            a.iter().rfold(0, |n, _| n + 1),
+        ),
+        (
+            "product",
+            a.iter().fold(1, |n, i| n * i),
+            a.iter().rfold(1, |n, i| n * i),
+        ),
+    ];
+    TableRenderer::new()
+        .headers(&["", "fold", "rfold"])
+        .print(rows)
+        .unwrap();
 }
 
 // `nth` and `nth_back()`, `last`
@@ -173,7 +194,12 @@ fn use_fold() {
 // - `iter.next_back()` if you need to iterator for later use
 fn use_nth() {
     let mut squares = (0..10).map(|n| n * n);
-    println!("{:?}", squares);
+    // the closure captures nothing, so the adapter is `Clone` and can be drawn from without using `squares` up
+    TableRenderer::new()
+        .numbered(true)
+        .headers(&["square"])
+        .print(squares.clone().map(|n| [n]))
+        .unwrap();
     println!("squares[4] is: {:?}", squares.nth(4));
     println!("squares[6] is: {:?}", squares.nth(6));
 
@@ -261,8 +287,11 @@ fn use_partition() {
     let (big_cities, small_cities): (Vec<(&str, i32)>, Vec<(&str, i32)>) =
         populations.iter().partition(|&&t| t.1 > 500_000);
 
-    println!("Big cities are: {:?}", big_cities);
-    println!("Small cities are: {:?}", small_cities);
+    let table = TableRenderer::new().headers(&["city", "population"]);
+    println!("Big cities are:");
+    table.print(&big_cities).unwrap();
+    println!("Small cities are:");
+    table.print(&small_cities).unwrap();
 }
 
 // `for_each` and `try_for_each`
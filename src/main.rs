@@ -33,6 +33,24 @@ use {
     utility_traits::*,
 };
 fn main() {
+    // subcommands, e.g. `cargo run -- catalog works.txt list`
+    // - with no arguments we fall through to the demos below
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(command) = args.first() {
+        let result: Result<(), Box<dyn std::error::Error>> = match command.as_str() {
+            "catalog" => catalog_command(&args[1..]),
+            "calc" => calc_command(&args[1..]),
+            "shapes" => shapes_command(&args[1..]),
+            "simulate" => simulate_command(&args[1..]),
+            _ => Err(format!("unknown subcommand {:?}", command).into()),
+        };
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // convert_integer_in_range();
     // convert_integer_out_of_range();
     // check_arithmetic_methods();
@@ -55,17 +73,43 @@ fn main() {
     // ref_to_ref();
     // ref_to_expr();
     // ref_safety();
+    // use_records();
+    // use_select();
+    // use_catalog().unwrap();
+    // use_interner();
     // control_flow_in_loop();
     // interior_mutability();
+    // sync_interior_mutability();
+    // use_robot_log().unwrap();
+    // use_metrics().unwrap();
+    // use_tracked_ref_cell();
     // match_patterns();
+    // use_lexer();
+    // use_geometry();
+    // use_color();
+    // use_list_formatter().unwrap();
+    // use_accounts();
+    // use_expr().unwrap();
+    // use_json().unwrap();
+    // use_binary_tree();
     // use_traits();
     // use_dot_g();
+    // use_num();
+    // use_shapes().unwrap();
+    // use_taf();
+    // use_string_sets();
+    // use_rope();
+    // use_world();
+    // use_tables();
     // operator_overload();
+    // use_linalg();
     // use_utility_traits();
     // use_fn_closure();
     // use_iterators();
     // use_adapters();
     // consume_iterators();
     // use_collections();
+    // use_writers().unwrap();
+    // use_filesystem().unwrap();
     use_osstr_path();
 }
@@ -0,0 +1,937 @@
+// Dense `Vector<T>` and `Matrix<T>` over any `Num`, the bound `dot_g` ends up with
+// - arithmetic operators are implemented on references, so `&a + &b` leaves both operands usable
+// - owned and mixed operands work too, `forward_owned!` forwards them to the reference impls
+// - an operator cannot return a `Result`, so a dimension mismatch panics with the `LinalgError` message
+// - `checked_add`, `checked_mul` and friends return the error instead
+// - LU decomposition, `determinant` and `solve` need division that does not truncate, so they are for `Float` only
+use crate::traits_generics::{Float, Num, Signed};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinalgError {
+    // vectors count as `n x 1` columns
+    DimensionMismatch {
+        op: &'static str,
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    BadLength {
+        rows: usize,
+        cols: usize,
+        len: usize,
+    },
+    RaggedRows {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    Singular,
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinalgError::DimensionMismatch { op, left, right } => write!(
+                f,
+                "cannot {} {}x{} and {}x{}",
+                op, left.0, left.1, right.0, right.1
+            ),
+            LinalgError::BadLength { rows, cols, len } => {
+                write!(
+                    f,
+                    "{}x{} matrix needs {} elements, got {}",
+                    rows,
+                    cols,
+                    rows * cols,
+                    len
+                )
+            }
+            LinalgError::RaggedRows {
+                row,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "row {} has {} elements, expected {}",
+                    row, found, expected
+                )
+            }
+            LinalgError::NotSquare { rows, cols } => {
+                write!(f, "{}x{} matrix is not square", rows, cols)
+            }
+            LinalgError::Singular => write!(f, "matrix is singular"),
+        }
+    }
+}
+
+impl Error for LinalgError {}
+
+fn expect<T>(result: Result<T, LinalgError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vector<T> {
+    data: Vec<T>,
+}
+
+impl<T: Num> Vector<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        Vector { data }
+    }
+
+    pub fn zeros(len: usize) -> Self {
+        Vector {
+            data: vec![T::ZERO; len],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    fn check_same_len(&self, op: &'static str, other: &Vector<T>) -> Result<(), LinalgError> {
+        if self.len() == other.len() {
+            Ok(())
+        } else {
+            Err(LinalgError::DimensionMismatch {
+                op,
+                left: (self.len(), 1),
+                right: (other.len(), 1),
+            })
+        }
+    }
+
+    // `dot_g` with the length check it was missing
+    pub fn dot(&self, other: &Vector<T>) -> Result<T, LinalgError> {
+        self.check_same_len("dot", other)?;
+        Ok(self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(&a, &b)| a * b)
+            .sum())
+    }
+
+    pub fn checked_add(&self, other: &Vector<T>) -> Result<Vector<T>, LinalgError> {
+        self.check_same_len("add", other)?;
+        Ok(self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(&a, &b)| a + b)
+            .collect())
+    }
+
+    pub fn checked_sub(&self, other: &Vector<T>) -> Result<Vector<T>, LinalgError> {
+        self.check_same_len("subtract", other)?;
+        Ok(self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(&a, &b)| a - b)
+            .collect())
+    }
+}
+
+impl<T: Float> Vector<T> {
+    pub fn norm(&self) -> T {
+        self.data.iter().map(|&x| x * x).sum::<T>().sqrt()
+    }
+}
+
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(data: Vec<T>) -> Self {
+        Vector { data }
+    }
+}
+
+impl<T> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Vector {
+            data: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Index<usize> for Vector<T> {
+    type Output = T;
+    fn index(&self, i: usize) -> &T {
+        &self.data[i]
+    }
+}
+
+impl<T> IndexMut<usize> for Vector<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        &mut self.data[i]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.data.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            // pass `{:.2}` and the like on to the elements
+            fmt::Display::fmt(x, f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: Num> Add<&Vector<T>> for &Vector<T> {
+    type Output = Vector<T>;
+    fn add(self, rhs: &Vector<T>) -> Vector<T> {
+        expect(self.checked_add(rhs))
+    }
+}
+
+impl<T: Num> Sub<&Vector<T>> for &Vector<T> {
+    type Output = Vector<T>;
+    fn sub(self, rhs: &Vector<T>) -> Vector<T> {
+        expect(self.checked_sub(rhs))
+    }
+}
+
+// `v * 2.0`; `2.0 * v` would need an impl on every primitive type, since `T` is not ours
+impl<T: Num> Mul<T> for &Vector<T> {
+    type Output = Vector<T>;
+    fn mul(self, k: T) -> Vector<T> {
+        self.data.iter().map(|&x| x * k).collect()
+    }
+}
+
+impl<T: Num> Mul<T> for Vector<T> {
+    type Output = Vector<T>;
+    fn mul(mut self, k: T) -> Vector<T> {
+        self *= k;
+        self
+    }
+}
+
+impl<T: Signed> Neg for &Vector<T> {
+    type Output = Vector<T>;
+    fn neg(self) -> Vector<T> {
+        self.data.iter().map(|&x| -x).collect()
+    }
+}
+
+impl<T: Signed> Neg for Vector<T> {
+    type Output = Vector<T>;
+    fn neg(mut self) -> Vector<T> {
+        self.data.iter_mut().for_each(|x| *x = -*x);
+        self
+    }
+}
+
+// the `*Assign` operators update in place, no new allocation
+impl<T: Num> AddAssign<&Vector<T>> for Vector<T> {
+    fn add_assign(&mut self, rhs: &Vector<T>) {
+        expect(self.check_same_len("add", rhs));
+        for (a, &b) in self.data.iter_mut().zip(&rhs.data) {
+            *a = *a + b;
+        }
+    }
+}
+
+impl<T: Num> SubAssign<&Vector<T>> for Vector<T> {
+    fn sub_assign(&mut self, rhs: &Vector<T>) {
+        expect(self.check_same_len("subtract", rhs));
+        for (a, &b) in self.data.iter_mut().zip(&rhs.data) {
+            *a = *a - b;
+        }
+    }
+}
+
+impl<T: Num> MulAssign<T> for Vector<T> {
+    fn mul_assign(&mut self, k: T) {
+        self.data.iter_mut().for_each(|x| *x = *x * k);
+    }
+}
+
+// row-major, element `(i, j)` is at `data[i * cols + j]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Num> Matrix<T> {
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, LinalgError> {
+        if data.len() != rows * cols {
+            return Err(LinalgError::BadLength {
+                rows,
+                cols,
+                len: data.len(),
+            });
+        }
+        Ok(Matrix { rows, cols, data })
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, LinalgError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != cols {
+                return Err(LinalgError::RaggedRows {
+                    row: i,
+                    expected: cols,
+                    found: row.len(),
+                });
+            }
+            data.extend_from_slice(row);
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data,
+        })
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::ZERO; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = T::ONE;
+        }
+        m
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn column(&self, j: usize) -> Vector<T> {
+        (0..self.rows).map(|i| self[(i, j)]).collect()
+    }
+
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut t = Matrix::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+        t
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+
+    fn check_same_shape(&self, op: &'static str, other: &Matrix<T>) -> Result<(), LinalgError> {
+        if self.shape() == other.shape() {
+            Ok(())
+        } else {
+            Err(LinalgError::DimensionMismatch {
+                op,
+                left: self.shape(),
+                right: other.shape(),
+            })
+        }
+    }
+
+    fn zip_with(&self, other: &Matrix<T>, f: impl Fn(T, T) -> T) -> Matrix<T> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(&other.data)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        }
+    }
+
+    pub fn checked_add(&self, other: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        self.check_same_shape("add", other)?;
+        Ok(self.zip_with(other, |a, b| a + b))
+    }
+
+    pub fn checked_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        self.check_same_shape("subtract", other)?;
+        Ok(self.zip_with(other, |a, b| a - b))
+    }
+
+    pub fn checked_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        if self.cols != other.rows {
+            return Err(LinalgError::DimensionMismatch {
+                op: "multiply",
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        // i-k-j order walks both `self` and `other` row by row
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                for j in 0..other.cols {
+                    product[(i, j)] = product[(i, j)] + a * other[(k, j)];
+                }
+            }
+        }
+        Ok(product)
+    }
+
+    pub fn checked_mul_vector(&self, v: &Vector<T>) -> Result<Vector<T>, LinalgError> {
+        if self.cols != v.len() {
+            return Err(LinalgError::DimensionMismatch {
+                op: "multiply",
+                left: self.shape(),
+                right: (v.len(), 1),
+            });
+        }
+        Ok((0..self.rows)
+            .map(|i| self.row(i).iter().zip(v.iter()).map(|(&a, &b)| a * b).sum())
+            .collect())
+    }
+}
+
+// `Matrix::from([[1, 2], [3, 4]])`, the shape is checked by the compiler
+impl<T: Num, const R: usize, const C: usize> From<[[T; C]; R]> for Matrix<T> {
+    fn from(rows: [[T; C]; R]) -> Self {
+        Matrix {
+            rows: R,
+            cols: C,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(
+            i < self.rows && j < self.cols,
+            "index ({}, {}) out of bounds",
+            i,
+            j
+        );
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(
+            i < self.rows && j < self.cols,
+            "index ({}, {}) out of bounds",
+            i,
+            j
+        );
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+// one line per row, columns right-aligned, `{:.3}` applies to every element
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells = self
+            .data
+            .iter()
+            .map(|x| match f.precision() {
+                Some(p) => format!("{:.*}", p, x),
+                None => x.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let width = cells.iter().map(String::len).max().unwrap_or(0);
+        for (i, row) in cells.chunks(self.cols.max(1)).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "[")?;
+            for (j, cell) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{:>width$}", cell, width = width)?;
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Num> Add<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn add(self, rhs: &Matrix<T>) -> Matrix<T> {
+        expect(self.checked_add(rhs))
+    }
+}
+
+impl<T: Num> Sub<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn sub(self, rhs: &Matrix<T>) -> Matrix<T> {
+        expect(self.checked_sub(rhs))
+    }
+}
+
+impl<T: Num> Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: &Matrix<T>) -> Matrix<T> {
+        expect(self.checked_mul(rhs))
+    }
+}
+
+impl<T: Num> Mul<&Vector<T>> for &Matrix<T> {
+    type Output = Vector<T>;
+    fn mul(self, rhs: &Vector<T>) -> Vector<T> {
+        expect(self.checked_mul_vector(rhs))
+    }
+}
+
+// `a + b`, `a + &b` and `&a + b` borrow their operands and call the `&a + &b` impls above
+// - the owned operands are dropped afterwards, an in-place version would need `AddAssign` for every op
+macro_rules! forward_owned {
+    ($($trait:ident :: $method:ident for $lhs:ident, $rhs:ident -> $out:ident;)*) => {$(
+        impl<T: Num> $trait<$rhs<T>> for $lhs<T> {
+            type Output = $out<T>;
+            fn $method(self, rhs: $rhs<T>) -> $out<T> {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<T: Num> $trait<&$rhs<T>> for $lhs<T> {
+            type Output = $out<T>;
+            fn $method(self, rhs: &$rhs<T>) -> $out<T> {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl<T: Num> $trait<$rhs<T>> for &$lhs<T> {
+            type Output = $out<T>;
+            fn $method(self, rhs: $rhs<T>) -> $out<T> {
+                self.$method(&rhs)
+            }
+        }
+    )*};
+}
+
+forward_owned! {
+    Add::add for Vector, Vector -> Vector;
+    Sub::sub for Vector, Vector -> Vector;
+    Add::add for Matrix, Matrix -> Matrix;
+    Sub::sub for Matrix, Matrix -> Matrix;
+    Mul::mul for Matrix, Matrix -> Matrix;
+    Mul::mul for Matrix, Vector -> Vector;
+}
+
+impl<T: Num> Mul<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, k: T) -> Matrix<T> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|&x| x * k).collect(),
+        }
+    }
+}
+
+impl<T: Num> Mul<T> for Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(mut self, k: T) -> Matrix<T> {
+        self *= k;
+        self
+    }
+}
+
+impl<T: Signed> Neg for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn neg(self) -> Matrix<T> {
+        self * (-T::ONE)
+    }
+}
+
+impl<T: Signed> Neg for Matrix<T> {
+    type Output = Matrix<T>;
+    fn neg(self) -> Matrix<T> {
+        self * (-T::ONE)
+    }
+}
+
+impl<T: Num> AddAssign<&Matrix<T>> for Matrix<T> {
+    fn add_assign(&mut self, rhs: &Matrix<T>) {
+        expect(self.check_same_shape("add", rhs));
+        for (a, &b) in self.data.iter_mut().zip(&rhs.data) {
+            *a = *a + b;
+        }
+    }
+}
+
+impl<T: Num> SubAssign<&Matrix<T>> for Matrix<T> {
+    fn sub_assign(&mut self, rhs: &Matrix<T>) {
+        expect(self.check_same_shape("subtract", rhs));
+        for (a, &b) in self.data.iter_mut().zip(&rhs.data) {
+            *a = *a - b;
+        }
+    }
+}
+
+// the product needs a new buffer anyway, so this one is not in place
+impl<T: Num> MulAssign<&Matrix<T>> for Matrix<T> {
+    fn mul_assign(&mut self, rhs: &Matrix<T>) {
+        *self = &*self * rhs;
+    }
+}
+
+impl<T: Num> MulAssign<T> for Matrix<T> {
+    fn mul_assign(&mut self, k: T) {
+        self.data.iter_mut().for_each(|x| *x = *x * k);
+    }
+}
+
+// `P * A = L * U` with partial pivoting
+// - `L` (unit diagonal, not stored) and `U` share one matrix
+// - `perm[i]` is the row of `A` that ended up in row `i`
+#[derive(Debug, Clone)]
+pub struct Lu<T> {
+    lu: Matrix<T>,
+    perm: Vec<usize>,
+    odd_swaps: bool,
+}
+
+impl<T: Float> Lu<T> {
+    pub fn determinant(&self) -> T {
+        let det = (0..self.lu.rows).map(|i| self.lu[(i, i)]).product::<T>();
+        if self.odd_swaps {
+            -det
+        } else {
+            det
+        }
+    }
+
+    pub fn solve(&self, b: &Vector<T>) -> Result<Vector<T>, LinalgError> {
+        let n = self.lu.rows;
+        if b.len() != n {
+            return Err(LinalgError::DimensionMismatch {
+                op: "solve",
+                left: self.lu.shape(),
+                right: (b.len(), 1),
+            });
+        }
+        // L y = P b, forward
+        let mut x: Vector<T> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            for k in 0..i {
+                x[i] = x[i] - self.lu[(i, k)] * x[k];
+            }
+        }
+        // U x = y, backward
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                x[i] = x[i] - self.lu[(i, k)] * x[k];
+            }
+            x[i] = x[i] / self.lu[(i, i)];
+        }
+        Ok(x)
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    pub fn lu(&self) -> Result<Lu<T>, LinalgError> {
+        if !self.is_square() {
+            return Err(LinalgError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        let n = self.rows;
+        // a pivot this small relative to its own row is rounding noise left by elimination, not a value
+        // - each row is measured against itself, so a well-conditioned `diag(1, 1e-17)` is not singular
+        let size = (0..n).fold(T::ZERO, |s, _| s + T::ONE);
+        let scales = (0..n)
+            .map(|i| {
+                let row = &self.data[i * n..(i + 1) * n];
+                row.iter()
+                    .fold(T::ZERO, |m, &x| if x.abs() > m { x.abs() } else { m })
+            })
+            .collect::<Vec<_>>();
+
+        let mut lu = self.clone();
+        let mut perm = (0..n).collect::<Vec<_>>();
+        let mut odd_swaps = false;
+        for k in 0..n {
+            let pivot = (k..n)
+                .max_by(|&a, &b| {
+                    let (a, b) = (lu[(a, k)].abs(), lu[(b, k)].abs());
+                    a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap();
+            if lu[(pivot, k)].abs() <= scales[perm[pivot]] * size * T::EPSILON {
+                return Err(LinalgError::Singular);
+            }
+            if pivot != k {
+                lu.swap_rows(pivot, k);
+                perm.swap(pivot, k);
+                odd_swaps = !odd_swaps;
+            }
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / lu[(k, k)];
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    lu[(i, j)] = lu[(i, j)] - factor * lu[(k, j)];
+                }
+            }
+        }
+        Ok(Lu {
+            lu,
+            perm,
+            odd_swaps,
+        })
+    }
+
+    // zero for a singular matrix, an error only when the matrix is not square
+    pub fn determinant(&self) -> Result<T, LinalgError> {
+        match self.lu() {
+            Ok(lu) => Ok(lu.determinant()),
+            Err(LinalgError::Singular) => Ok(T::ZERO),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn solve(&self, b: &Vector<T>) -> Result<Vector<T>, LinalgError> {
+        self.lu()?.solve(b)
+    }
+
+    // one decomposition, one solve per column of the identity
+    pub fn inverse(&self) -> Result<Matrix<T>, LinalgError> {
+        let lu = self.lu()?;
+        let n = self.rows;
+        let mut inv = Matrix::zeros(n, n);
+        for j in 0..n {
+            let mut e = Vector::zeros(n);
+            e[j] = T::ONE;
+            let x = lu.solve(&e)?;
+            for i in 0..n {
+                inv[(i, j)] = x[i];
+            }
+        }
+        Ok(inv)
+    }
+
+    // every element is compared relative to the largest element of either matrix
+    // - `Float::approx_eq` alone would never call the rounding noise left in a zero equal to zero
+    pub fn approx_eq(&self, other: &Matrix<T>) -> bool {
+        let scale = self
+            .data
+            .iter()
+            .chain(&other.data)
+            .fold(T::ZERO, |max, &x| if x.abs() > max { x.abs() } else { max });
+        let four = T::ONE + T::ONE + T::ONE + T::ONE;
+        self.shape() == other.shape()
+            && self
+                .data
+                .iter()
+                .zip(&other.data)
+                .all(|(&a, &b)| a == b || (a - b).abs() <= T::EPSILON * four * scale)
+    }
+}
+
+pub fn use_linalg() {
+    let u = Vector::new(vec![1, 2, 3]);
+    let v = Vector::new(vec![4, 5, 6]);
+    // references in, `u` and `v` are still ours afterwards
+    println!("u + v = {}, u . v = {:?}", &u + &v, u.dot(&v));
+    // `dot_g(&[1, 2, 3], &[4, 5])` would panic on the index, here it is an error
+    println!("{}", u.dot(&Vector::new(vec![4, 5])).unwrap_err());
+
+    let a = Matrix::from([[1, 2, 3], [4, 5, 6]]);
+    let b = Matrix::from([[7, 8], [9, 10], [11, 12]]);
+    println!("{}\n", &a * &b);
+    // `&a + &b` would panic with "cannot add 2x3 and 3x2"
+    println!("{}", a.checked_add(&b).unwrap_err());
+
+    // the first pivot is 0, so this only works with row swaps
+    let m = Matrix::from([[0.0, 2.0, 1.0], [1.0, 1.0, 1.0], [2.0, 1.0, 3.0]]);
+    let x = m.solve(&Vector::new(vec![7.0, 6.0, 13.0])).unwrap();
+    let inv = m.inverse().unwrap();
+    assert!((&m * &inv).approx_eq(&Matrix::identity(3)));
+
+    println!("{}\n", a);
+    println!("{:.3}\n", inv);
+    println!("x = {:.1}", x);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_ops() {
+        let u = Vector::new(vec![1, 2, 3]);
+        let v = Vector::new(vec![4, 5, 6]);
+        assert_eq!(&u + &v, Vector::new(vec![5, 7, 9]));
+        assert_eq!(&v - &u, Vector::new(vec![3, 3, 3]));
+        assert_eq!(&u * 2, Vector::new(vec![2, 4, 6]));
+        assert_eq!(-&u, Vector::new(vec![-1, -2, -3]));
+        assert_eq!(u.dot(&v), Ok(32));
+        let short = Vector::new(vec![4, 5]);
+        assert_eq!(
+            u.dot(&short).unwrap_err().to_string(),
+            "cannot dot 3x1 and 2x1"
+        );
+        let mut w = u.clone();
+        w += &v;
+        w -= &u;
+        w *= 10;
+        w[0] = 0;
+        assert_eq!(w, Vector::new(vec![0, 50, 60]));
+        assert_eq!(Vector::new(vec![3.0, 4.0]).norm(), 5.0);
+    }
+
+    #[test]
+    fn matrix_ops() {
+        let u = Vector::new(vec![1, 2, 3]);
+        let a = Matrix::from([[1, 2, 3], [4, 5, 6]]);
+        let b = Matrix::from([[7, 8], [9, 10], [11, 12]]);
+        assert_eq!(&a * &b, Matrix::from([[58, 64], [139, 154]]));
+        assert_eq!(&a * &u, Vector::new(vec![14, 32]));
+        assert_eq!(a.transpose(), Matrix::from([[1, 4], [2, 5], [3, 6]]));
+        assert_eq!(&a * &Matrix::identity(3), a);
+        assert_eq!(&(&a + &a) - &a, a);
+        assert_eq!(-&a * 2, Matrix::from([[-2, -4, -6], [-8, -10, -12]]));
+        let mut c = Matrix::from([[1, 1], [0, 1]]);
+        c *= &c.clone();
+        c += &Matrix::identity(2);
+        assert_eq!(c, Matrix::from([[2, 2], [0, 2]]));
+    }
+
+    #[test]
+    fn dimension_errors() {
+        let a = Matrix::from([[1, 2, 3], [4, 5, 6]]);
+        let b = Matrix::from([[7, 8], [9, 10], [11, 12]]);
+        assert_eq!(
+            a.checked_mul(&a).unwrap_err(),
+            LinalgError::DimensionMismatch {
+                op: "multiply",
+                left: (2, 3),
+                right: (2, 3)
+            }
+        );
+        assert_eq!(
+            a.checked_add(&b).unwrap_err().to_string(),
+            "cannot add 2x3 and 3x2"
+        );
+        assert_eq!(
+            Matrix::from_rows(vec![vec![1, 2], vec![3]]),
+            Err(LinalgError::RaggedRows {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Matrix::new(2, 2, vec![1, 2, 3]).unwrap_err().to_string(),
+            "2x2 matrix needs 4 elements, got 3"
+        );
+    }
+
+    #[test]
+    fn lu_with_row_swaps() {
+        // the first pivot is 0, so this only works with row swaps
+        let m = Matrix::from([[0.0, 2.0, 1.0], [1.0, 1.0, 1.0], [2.0, 1.0, 3.0]]);
+        let det = m.determinant().unwrap();
+        assert!(det.approx_eq(-3.0), "{}", det);
+        let x = m.solve(&Vector::new(vec![7.0, 6.0, 13.0])).unwrap();
+        assert!(
+            x.iter().zip([1.0, 2.0, 3.0]).all(|(&a, b)| a.approx_eq(b)),
+            "{}",
+            x
+        );
+        let inv = m.inverse().unwrap();
+        assert!((&m * &inv).approx_eq(&Matrix::identity(3)));
+    }
+
+    #[test]
+    fn lu_errors() {
+        let singular = Matrix::from([[1.0, 2.0], [2.0, 4.0]]);
+        assert_eq!(singular.determinant(), Ok(0.0));
+        assert_eq!(
+            singular.solve(&Vector::new(vec![1.0, 2.0])),
+            Err(LinalgError::Singular)
+        );
+        assert_eq!(
+            Matrix::<f64>::zeros(3, 2)
+                .lu()
+                .map(|_| ())
+                .unwrap_err()
+                .to_string(),
+            "3x2 matrix is not square"
+        );
+        assert_eq!(Matrix::<f64>::zeros(0, 0).determinant(), Ok(1.0));
+    }
+
+    #[test]
+    fn owned_and_mixed_operands() {
+        let a = Matrix::from([[1.0, 2.0], [3.0, 4.0]]);
+        let b = Matrix::from([[0.0, 1.0], [1.0, 0.0]]);
+        let v = Vector::from(vec![1.0, -1.0]);
+        let expected = &(&a * &b) * &v;
+        assert_eq!(a.clone() * b.clone() * v.clone(), expected);
+        assert_eq!(&a * b.clone() * &v, expected);
+        assert_eq!((a.clone() + &b) - a.clone(), b);
+        assert_eq!(&a - (a.clone() - b.clone()), b);
+        assert_eq!(v.clone() + v.clone() - &v, v);
+        assert_eq!(&v + (v.clone() * 2.0), v.clone() * 3.0);
+    }
+
+    #[test]
+    fn tiny_but_invertible_is_not_singular() {
+        let m = Matrix::from([[1.0, 0.0], [0.0, 1e-17]]);
+        assert_eq!(m.determinant(), Ok(1e-17));
+        let x = m.solve(&Vector::from(vec![2.0, 3e-17])).unwrap();
+        assert_eq!(x, Vector::from(vec![2.0, 3.0]));
+        let rank_one = Matrix::from([[0.1, 0.2], [0.3, 0.6]]);
+        assert_eq!(rank_one.determinant(), Ok(0.0));
+        assert_eq!(rank_one.lu().err(), Some(LinalgError::Singular));
+    }
+}
@@ -1,3 +1,6 @@
+mod linalg;
+pub use linalg::*;
+
 fn equivalence_compare() {
     // expression with no appropriate value like `0.0/0.0` must return NaN values
     // - NaN isunequal to every other, including NaN iteself
@@ -0,0 +1,505 @@
+// A `Catalog` of artists and their works, built on the `Table` alias from `ref_to_values.rs`
+// - methods that only look take `&self`, methods that change the table take `&mut self`
+// - listing hands out `&str` borrowed from the table, like `show` does, so nothing is copied to print
+
+// The text format is line based
+// - an artist is a line with no leading whitespace
+// - each work is an indented line below its artist
+// - blank lines and lines starting with `#` are ignored
+//
+//     Caravaggio
+//       The musicians
+//       The calling of St. Matthew
+use super::Table;
+use crate::input_output::{Fs, InMemoryFs, RealFs};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    InvalidName(String),
+    UnknownArtist(String),
+    UnknownWork { artist: String, work: String },
+    DuplicateArtist(String),
+    DuplicateWork { artist: String, work: String },
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::Io(e) => write!(f, "{}", e),
+            CatalogError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            CatalogError::InvalidName(name) => write!(f, "invalid name {:?}", name),
+            CatalogError::UnknownArtist(artist) => write!(f, "no artist named {:?}", artist),
+            CatalogError::UnknownWork { artist, work } => {
+                write!(f, "{:?} has no work named {:?}", artist, work)
+            }
+            CatalogError::DuplicateArtist(artist) => {
+                write!(f, "artist {:?} already exists", artist)
+            }
+            CatalogError::DuplicateWork { artist, work } => {
+                write!(f, "{:?} already has a work named {:?}", artist, work)
+            }
+        }
+    }
+}
+
+impl Error for CatalogError {}
+
+// `?` converts an `io::Error` into a `CatalogError` through this impl
+impl From<io::Error> for CatalogError {
+    fn from(e: io::Error) -> Self {
+        CatalogError::Io(e)
+    }
+}
+
+// a hit from `Catalog::search`, borrowed from the catalog
+#[derive(Debug, PartialEq)]
+pub enum Match<'a> {
+    Artist(&'a str),
+    Work { artist: &'a str, work: &'a str },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Catalog {
+    table: Table,
+}
+
+// artist and work names are stored trimmed and must not be empty
+// - a leading `#` would read back as a comment, so it is refused rather than lost on save
+// - removing and renaming clean their names too, so `remove_artist(" Cellini ")` finds what `add_artist` stored
+fn clean(name: &str) -> Result<&str, CatalogError> {
+    let name = name.trim();
+    if name.is_empty() || name.contains('\n') || name.starts_with('#') {
+        return Err(CatalogError::InvalidName(name.to_string()));
+    }
+    Ok(name)
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, CatalogError> {
+        let mut catalog = Catalog::new();
+        let mut current: Option<String> = None;
+        for (n, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| CatalogError::Parse {
+                line: n + 1,
+                message,
+            };
+            if line.starts_with(char::is_whitespace) {
+                let artist = current
+                    .as_ref()
+                    .ok_or_else(|| parse_error(format!("work {:?} has no artist", trimmed)))?;
+                catalog
+                    .add_work(artist, trimmed)
+                    .map_err(|e| parse_error(e.to_string()))?;
+            } else {
+                catalog
+                    .add_artist(trimmed)
+                    .map_err(|e| parse_error(e.to_string()))?;
+                current = Some(trimmed.to_string());
+            }
+        }
+        Ok(catalog)
+    }
+
+    // a missing file is an empty catalog, so the first `add` can create it
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CatalogError> {
+        Catalog::load_from(&RealFs, path.as_ref())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CatalogError> {
+        self.save_to(&RealFs, path.as_ref())
+    }
+
+    pub fn load_from(fs: &dyn Fs, path: &Path) -> Result<Self, CatalogError> {
+        match fs.read_to_string(path) {
+            Ok(text) => Catalog::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Catalog::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_to(&self, fs: &dyn Fs, path: &Path) -> Result<(), CatalogError> {
+        fs.write(path, self.to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    // looked up the way `add_artist` stores it, an invalid name is never in the catalog
+    pub fn contains_artist(&self, artist: &str) -> bool {
+        clean(artist).is_ok_and(|artist| self.table.contains_key(artist))
+    }
+
+    pub fn add_artist(&mut self, artist: &str) -> Result<(), CatalogError> {
+        let artist = clean(artist)?;
+        if self.table.contains_key(artist) {
+            return Err(CatalogError::DuplicateArtist(artist.to_string()));
+        }
+        self.table.insert(artist.to_string(), Vec::new());
+        Ok(())
+    }
+
+    // adds the artist too if it is not there yet
+    pub fn add_work(&mut self, artist: &str, work: &str) -> Result<(), CatalogError> {
+        let (artist, work) = (clean(artist)?, clean(work)?);
+        let works = self.table.entry(artist.to_string()).or_default();
+        if works.iter().any(|w| w == work) {
+            return Err(CatalogError::DuplicateWork {
+                artist: artist.to_string(),
+                work: work.to_string(),
+            });
+        }
+        works.push(work.to_string());
+        Ok(())
+    }
+
+    // returns the removed works
+    pub fn remove_artist(&mut self, artist: &str) -> Result<Vec<String>, CatalogError> {
+        let artist = clean(artist)?;
+        self.table
+            .remove(artist)
+            .ok_or_else(|| CatalogError::UnknownArtist(artist.to_string()))
+    }
+
+    pub fn remove_work(&mut self, artist: &str, work: &str) -> Result<(), CatalogError> {
+        let (artist, work) = (clean(artist)?, clean(work)?);
+        let works = self.works_mut(artist)?;
+        let index =
+            works
+                .iter()
+                .position(|w| w == work)
+                .ok_or_else(|| CatalogError::UnknownWork {
+                    artist: artist.to_string(),
+                    work: work.to_string(),
+                })?;
+        works.remove(index);
+        Ok(())
+    }
+
+    pub fn rename_artist(&mut self, from: &str, to: &str) -> Result<(), CatalogError> {
+        let to = clean(to)?;
+        if self.table.contains_key(to) {
+            return Err(CatalogError::DuplicateArtist(to.to_string()));
+        }
+        let works = self.remove_artist(from)?;
+        self.table.insert(to.to_string(), works);
+        Ok(())
+    }
+
+    pub fn rename_work(&mut self, artist: &str, from: &str, to: &str) -> Result<(), CatalogError> {
+        let (artist, from, to) = (clean(artist)?, clean(from)?, clean(to)?);
+        let works = self.works_mut(artist)?;
+        if works.iter().any(|w| w == to) {
+            return Err(CatalogError::DuplicateWork {
+                artist: artist.to_string(),
+                work: to.to_string(),
+            });
+        }
+        let work =
+            works
+                .iter_mut()
+                .find(|w| *w == from)
+                .ok_or_else(|| CatalogError::UnknownWork {
+                    artist: artist.to_string(),
+                    work: from.to_string(),
+                })?;
+        *work = to.to_string();
+        Ok(())
+    }
+
+    fn works_mut(&mut self, artist: &str) -> Result<&mut Vec<String>, CatalogError> {
+        let artist = clean(artist)?;
+        self.table
+            .get_mut(artist)
+            .ok_or_else(|| CatalogError::UnknownArtist(artist.to_string()))
+    }
+
+    // works in the order they were added
+    pub fn works(&self, artist: &str) -> Option<&[String]> {
+        self.table.get(clean(artist).ok()?).map(Vec::as_slice)
+    }
+
+    // `Table` is a `HashMap`, so iteration order is not guaranteed and we sort for listing
+    pub fn artists(&self) -> Vec<&str> {
+        let mut artists = self.table.keys().map(String::as_str).collect::<Vec<_>>();
+        artists.sort_by_key(|a| a.to_lowercase());
+        artists
+    }
+
+    // every artist and work whose name contains `query`, ignoring case
+    pub fn search(&self, query: &str) -> Vec<Match<'_>> {
+        let query = query.to_lowercase();
+        let mut hits = Vec::new();
+        for artist in self.artists() {
+            if artist.to_lowercase().contains(&query) {
+                hits.push(Match::Artist(artist));
+            }
+            for work in &self.table[artist] {
+                if work.to_lowercase().contains(&query) {
+                    hits.push(Match::Work { artist, work });
+                }
+            }
+        }
+        hits
+    }
+}
+
+// the same format `parse` reads, artists sorted so saved files diff cleanly
+impl fmt::Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for artist in self.artists() {
+            writeln!(f, "{}", artist)?;
+            for work in &self.table[artist] {
+                writeln!(f, "  {}", work)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// `From<Table>` lets the existing `ref_to_values` data move straight into a catalog
+impl From<Table> for Catalog {
+    fn from(table: Table) -> Self {
+        Catalog { table }
+    }
+}
+
+const CATALOG_USAGE: &str = "usage: catalog <file> add <artist> [work]
+       catalog <file> list
+       catalog <file> search <query>
+       catalog <file> remove <artist> [work]";
+
+// `cargo run -- catalog works.txt add Cellini "a salt cellar"`
+pub fn catalog_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (path, command, rest) = match args {
+        [path, command, rest @ ..] => (path, command.as_str(), rest),
+        _ => return Err(CATALOG_USAGE.into()),
+    };
+    let mut catalog = Catalog::load(path)?;
+    match (command, rest) {
+        ("add", [artist]) => catalog.add_artist(artist)?,
+        ("add", [artist, work]) => catalog.add_work(artist, work)?,
+        ("remove", [artist]) => {
+            catalog.remove_artist(artist)?;
+        }
+        ("remove", [artist, work]) => catalog.remove_work(artist, work)?,
+        ("list", []) => {
+            print!("{}", catalog);
+            return Ok(());
+        }
+        ("search", [query]) => {
+            for hit in catalog.search(query) {
+                match hit {
+                    Match::Artist(artist) => println!("{}", artist),
+                    Match::Work { artist, work } => println!("{}: {}", artist, work),
+                }
+            }
+            return Ok(());
+        }
+        _ => return Err(CATALOG_USAGE.into()),
+    }
+    catalog.save(path)?;
+    Ok(())
+}
+
+pub fn use_catalog() -> Result<(), CatalogError> {
+    let mut catalog = Catalog::new();
+    catalog.add_work("Gesualdo", "many madrigals")?;
+    catalog.add_work("Gesualdo", "Tenebre Responsoria")?;
+    catalog.add_work("Caravaggio", "The musicians")?;
+    catalog.add_work("Caravaggio", "The calling of St. Matthew")?;
+    catalog.add_artist("cellini")?;
+    catalog.rename_artist("cellini", "Cellini")?;
+    catalog.add_work("Cellini", "Perseus")?;
+    catalog.rename_work("Cellini", "Perseus", "Perseus with the head of Medusa")?;
+    catalog.add_work("Cellini", "a salt cellar")?;
+
+    println!("{:?}", catalog.search("MADRIGAL"));
+    println!("{}", catalog.add_artist("Cellini").unwrap_err());
+
+    // round trip through a file that only exists in memory
+    let fs = InMemoryFs::new();
+    let path = Path::new("/catalog.txt");
+    catalog.save_to(&fs, path)?;
+    let loaded = Catalog::load_from(&fs, path)?;
+    assert_eq!(loaded, catalog);
+    print!("{}", loaded);
+
+    catalog.remove_work("Gesualdo", "many madrigals")?;
+    catalog.remove_artist("Caravaggio")?;
+    println!("{}", Catalog::parse("  orphan work").unwrap_err());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Catalog {
+        let mut catalog = Catalog::new();
+        catalog.add_work("Gesualdo", "many madrigals").unwrap();
+        catalog.add_work("Gesualdo", "Tenebre Responsoria").unwrap();
+        catalog.add_work("Caravaggio", "The musicians").unwrap();
+        catalog.add_artist("cellini").unwrap();
+        catalog.rename_artist("cellini", "Cellini").unwrap();
+        catalog.add_work("Cellini", "Perseus").unwrap();
+        catalog
+    }
+
+    #[test]
+    fn artists_sort_ignoring_case_and_search_finds_both_kinds() {
+        let mut catalog = sample();
+        catalog.add_artist("bernini").unwrap();
+        assert_eq!(
+            catalog.artists(),
+            ["bernini", "Caravaggio", "Cellini", "Gesualdo"]
+        );
+        assert_eq!(
+            catalog.search("MADRIGAL"),
+            [Match::Work {
+                artist: "Gesualdo",
+                work: "many madrigals"
+            }]
+        );
+        assert_eq!(catalog.search("CELL"), [Match::Artist("Cellini")]);
+        assert_eq!(
+            catalog.search("s"),
+            [
+                Match::Work {
+                    artist: "Caravaggio",
+                    work: "The musicians"
+                },
+                Match::Work {
+                    artist: "Cellini",
+                    work: "Perseus"
+                },
+                Match::Artist("Gesualdo"),
+                Match::Work {
+                    artist: "Gesualdo",
+                    work: "many madrigals"
+                },
+                Match::Work {
+                    artist: "Gesualdo",
+                    work: "Tenebre Responsoria"
+                },
+            ][..]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut catalog = sample();
+        assert!(matches!(
+            catalog.add_artist("Cellini"),
+            Err(CatalogError::DuplicateArtist(_))
+        ));
+        assert!(matches!(
+            catalog.add_work("Cellini", "Perseus"),
+            Err(CatalogError::DuplicateWork { .. })
+        ));
+        assert!(matches!(
+            catalog.remove_artist("Bernini"),
+            Err(CatalogError::UnknownArtist(_))
+        ));
+        assert!(matches!(
+            catalog.remove_work("Cellini", "David"),
+            Err(CatalogError::UnknownWork { .. })
+        ));
+        assert!(matches!(
+            catalog.rename_artist("Gesualdo", "Caravaggio"),
+            Err(CatalogError::DuplicateArtist(_))
+        ));
+        assert!(matches!(
+            catalog.add_artist("  "),
+            Err(CatalogError::InvalidName(_))
+        ));
+        assert!(matches!(
+            Catalog::parse("# header\n  orphan work"),
+            Err(CatalogError::Parse { line: 2, .. })
+        ));
+        assert_eq!(catalog, sample());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let fs = InMemoryFs::new();
+        let path = Path::new("/catalog.txt");
+        // a missing file is an empty catalog
+        assert_eq!(Catalog::load_from(&fs, path).unwrap(), Catalog::new());
+        let catalog = sample();
+        catalog.save_to(&fs, path).unwrap();
+        assert_eq!(Catalog::load_from(&fs, path).unwrap(), catalog);
+        fs.create_dir_all(Path::new("/dir")).unwrap();
+        assert!(matches!(
+            Catalog::load_from(&fs, Path::new("/dir")),
+            Err(CatalogError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn names_that_would_read_back_as_comments_are_refused() {
+        let mut catalog = Catalog::new();
+        assert!(matches!(
+            catalog.add_work("#1 Hits", "Dream"),
+            Err(CatalogError::InvalidName(_))
+        ));
+        assert!(matches!(
+            catalog.add_work("Lennon", "  #9 Dream"),
+            Err(CatalogError::InvalidName(_))
+        ));
+        catalog.add_work("Lennon", "Number 9 Dream").unwrap();
+        assert_eq!(Catalog::parse(&catalog.to_string()).unwrap(), catalog);
+    }
+
+    #[test]
+    fn remove_and_rename_trim_like_add() {
+        let mut catalog = Catalog::new();
+        catalog.add_work(" Cellini ", " Perseus ").unwrap();
+        catalog
+            .rename_work("Cellini ", " Perseus", "Perseus with the head of Medusa")
+            .unwrap();
+        catalog.add_work("Cellini", "a salt cellar").unwrap();
+        catalog.remove_work(" Cellini", "a salt cellar ").unwrap();
+        catalog
+            .rename_artist(" Cellini ", "Benvenuto Cellini")
+            .unwrap();
+        assert_eq!(
+            catalog.works("Benvenuto Cellini").unwrap(),
+            ["Perseus with the head of Medusa"]
+        );
+        assert_eq!(
+            catalog.remove_artist("  Benvenuto Cellini").unwrap().len(),
+            1
+        );
+        assert!(catalog.is_empty());
+    }
+
+    #[test]
+    fn lookups_trim_like_add() {
+        let mut catalog = Catalog::new();
+        catalog.add_artist(" Monet ").unwrap();
+        assert!(catalog.contains_artist(" Monet "));
+        assert_eq!(catalog.works("Monet  "), Some(&[][..]));
+        assert!(!catalog.contains_artist("  "));
+        assert_eq!(catalog.works("#Monet"), None);
+    }
+}
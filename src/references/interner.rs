@@ -0,0 +1,270 @@
+// An interner hands out one shared copy per distinct value
+// - `ref_to_ref` shows `rry == rrx` compares the values while `std::ptr::eq` compares addresses
+// - once value-equal inputs always come back as the same address, `ptr::eq` is enough to compare them
+// - comparing and hashing an address is O(1) no matter how long the string is
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+// Every value is allocated once with `Box::into_raw`, so its address never changes
+// - the set holds raw pointers, not `Box`es: moving a `Box` asserts it is the only way to reach
+//   the value, which would invalidate every `&T` already handed out
+// - the `HashSet` may move the pointers around when it grows, but not what they point to
+// - we never remove an item, so the values live as long as the interner, and `Drop` frees them
+// - `RefCell` lets `intern` take `&self`, so many handles can be alive while we keep interning
+pub struct Interner<T: ?Sized> {
+    items: RefCell<HashSet<Item<T>>>,
+}
+
+// an owning pointer that hashes and compares by value, like the `Box<T>` it came from
+// - `Borrow<T>` lets the set look up with a plain `&T`
+struct Item<T: ?Sized>(NonNull<T>);
+
+impl<T: ?Sized> Item<T> {
+    fn new(boxed: Box<T>) -> Self {
+        // SAFETY: `Box::into_raw` never returns null
+        Item(unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) })
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Item<T> {
+    fn borrow(&self) -> &T {
+        // SAFETY: the pointer came from `Box::into_raw` and is only freed in `Interner::drop`
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Item<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Borrow::<T>::borrow(self) == Borrow::<T>::borrow(other)
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Item<T> {}
+
+impl<T: ?Sized + Hash> Hash for Item<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Borrow::<T>::borrow(self).hash(state)
+    }
+}
+
+// `Interned<'a, T>` is a `&'a T` that compares and hashes by address
+// - `'a` is the borrow of the interner, so a handle cannot outlive it
+pub struct Interned<'a, T: ?Sized>(&'a T);
+
+impl<T: ?Sized + Eq + Hash> Interner<T> {
+    pub fn new() -> Self {
+        Interner {
+            items: RefCell::new(HashSet::new()),
+        }
+    }
+
+    // number of distinct values, i.e. allocations made
+    pub fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.borrow().is_empty()
+    }
+
+    pub fn get(&self, value: &T) -> Option<Interned<'_, T>> {
+        let items = self.items.borrow();
+        let item = items.get(value)?;
+        Some(self.handle(item))
+    }
+
+    // Only allocates the first time a value is seen, e.g. `Interner<str>` copies `&str` into a `Box<str>` once
+    pub fn intern(&self, value: &T) -> Interned<'_, T>
+    where
+        for<'b> Box<T>: From<&'b T>,
+    {
+        if let Some(interned) = self.get(value) {
+            return interned;
+        }
+        self.insert(Box::from(value))
+    }
+
+    pub fn intern_owned(&self, value: T) -> Interned<'_, T>
+    where
+        T: Sized,
+    {
+        if let Some(interned) = self.get(&value) {
+            return interned;
+        }
+        self.insert(Box::new(value))
+    }
+
+    fn insert(&self, boxed: Box<T>) -> Interned<'_, T> {
+        let item = Item::new(boxed);
+        let handle = self.handle(&item);
+        self.items.borrow_mut().insert(item);
+        handle
+    }
+
+    // SAFETY: `item` owns an allocation that lives until the interner is dropped, see the notes on `Interner`
+    // - the `RefCell` guard only lives for the lookup, but the value lives as long as `&self`
+    fn handle(&self, item: &Item<T>) -> Interned<'_, T> {
+        Interned(unsafe { item.0.as_ref() })
+    }
+}
+
+impl<T: ?Sized> Drop for Interner<T> {
+    fn drop(&mut self) {
+        for item in self.items.get_mut().drain() {
+            // SAFETY: each pointer came from `Box::into_raw` and is in the set exactly once
+            drop(unsafe { Box::from_raw(item.0.as_ptr()) });
+        }
+    }
+}
+
+impl<T: ?Sized + Eq + Hash> Default for Interner<T> {
+    fn default() -> Self {
+        Interner::new()
+    }
+}
+
+impl<'a, T: ?Sized> Interned<'a, T> {
+    // the plain `&'a T`, for when we need the value itself
+    pub fn get(self) -> &'a T {
+        self.0
+    }
+}
+
+// `#[derive(Clone, Copy)]` would require `T: Copy`, but a handle is only a ref
+impl<T: ?Sized> Clone for Interned<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Interned<'_, T> {}
+
+// equal values share an address, so comparing addresses is comparing values
+// - only holds for handles from the same interner
+impl<T: ?Sized> PartialEq for Interned<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl<T: ?Sized> Eq for Interned<'_, T> {}
+
+// `Hash` must agree with `Eq`, so hash the address rather than the value
+impl<T: ?Sized> Hash for Interned<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+impl<T: ?Sized> Deref for Interned<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Interned<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Interned<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+fn use_string_interner() {
+    let interner = Interner::<str>::new();
+    let text = "let x = y + x * y - x";
+    let tokens = text
+        .split_whitespace()
+        .map(|word| interner.intern(word))
+        .collect::<Vec<_>>();
+
+    // 10 tokens, but only 7 distinct strings were allocated
+    println!("{} tokens, {} strings", tokens.len(), interner.len());
+
+    // same value, same address
+    assert!(std::ptr::eq(tokens[1].get(), tokens[5].get()));
+    // `Deref` still gives us the string
+    println!("{:?} is {}", tokens, &*tokens[3]);
+}
+
+fn use_owned_interner() {
+    let interner = Interner::new();
+    let a = interner.intern_owned(vec![1, 2, 3]);
+    let b = interner.intern_owned(vec![1, 2, 3]);
+    assert_eq!(a, b);
+
+    // - not working since `a` borrows `interner`, so it cannot be moved or dropped while `a` is alive
+    // drop(interner);
+    println!("{:?}", a);
+}
+
+pub fn use_interner() {
+    use_string_interner();
+    use_owned_interner();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_values_share_one_address() {
+        let interner = Interner::<str>::new();
+        let tokens = "let x = y + x * y - x"
+            .split_whitespace()
+            .map(|word| interner.intern(word))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 10);
+        assert_eq!(interner.len(), 7);
+        assert!(std::ptr::eq(tokens[1].get(), tokens[5].get()));
+        assert_eq!(tokens[1], tokens[5]);
+        assert_ne!(tokens[1], tokens[3]);
+        assert_eq!(&*tokens[3], "y");
+        assert_eq!(tokens[3].to_string(), "y");
+        // handles work as `HashSet` keys, hashed by address
+        let distinct = tokens.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), 7);
+        assert_eq!(interner.get("x"), Some(tokens[1]));
+        assert_eq!(interner.get("z"), None);
+    }
+
+    #[test]
+    fn owned_values_intern_once() {
+        let interner = Interner::new();
+        assert!(interner.is_empty());
+        let a = interner.intern_owned(vec![1, 2, 3]);
+        let b = interner.intern_owned(vec![1, 2, 3]);
+        let c = interner.intern_owned(vec![3, 2, 1]);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.len(), 2);
+    }
+
+    // run under `cargo +nightly miri test interner` to check the handles stay valid
+    #[test]
+    fn handles_survive_more_interning() {
+        let interner = Interner::<str>::new();
+        let first = interner.intern("first");
+        let words = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        let handles = words.iter().map(|w| interner.intern(w)).collect::<Vec<_>>();
+        assert_eq!(&*first, "first");
+        assert_eq!(interner.intern("first"), first);
+        assert_eq!(interner.len(), 101);
+        assert!(handles.iter().zip(&words).all(|(h, w)| &**h == w));
+
+        let owned = Interner::new();
+        let v = owned.intern_owned(vec![String::from("heap"); 3]);
+        assert_eq!(owned.intern_owned(vec![String::from("heap"); 3]), v);
+        assert_eq!(v.len(), 3);
+    }
+}
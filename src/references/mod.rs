@@ -1,9 +1,17 @@
+mod catalog;
+mod interner;
+mod records;
 mod ref_safety;
 mod ref_to_expr;
 mod ref_to_ref;
 mod ref_to_values;
+mod select;
 
+pub use catalog::*;
+pub use interner::*;
+pub use records::*;
 pub use ref_safety::*;
 pub use ref_to_expr::*;
 pub use ref_to_ref::*;
 pub use ref_to_values::*;
+pub use select::*;
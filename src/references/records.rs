@@ -0,0 +1,505 @@
+// A zero-copy parser for line-oriented records such as `key=value; key2="quoted"`
+// - extends `struct_containing_ref`: a struct holding `&'a str` must not outlive the input it points into
+// - values borrow from the input whenever possible, and only own when unescaping is needed
+// - see the `Cow` notes in `utility_traits.rs`
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Instant;
+
+#[derive(Debug, PartialEq)]
+pub enum RecordError {
+    MissingEquals { field: usize },
+    EmptyKey { field: usize },
+    UnterminatedQuote { field: usize },
+    BadEscape { field: usize, ch: char },
+    TrailingText { field: usize },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::MissingEquals { field } => write!(f, "field {}: missing `=`", field),
+            RecordError::EmptyKey { field } => write!(f, "field {}: empty key", field),
+            RecordError::UnterminatedQuote { field } => {
+                write!(f, "field {}: unterminated quoted value", field)
+            }
+            RecordError::BadEscape { field, ch } => {
+                write!(f, "field {}: unknown escape `\\{}`", field, ch)
+            }
+            RecordError::TrailingText { field } => {
+                write!(f, "field {}: text after closing quote", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+// `'a` ties every key and every borrowed value to the input line
+// - `Cow<'a, str>` is `Borrowed` for bare and plain quoted values, `Owned` only when escapes were rewritten
+///
+/// A record cannot outlive its buffer: `line` is dropped at the end of the block while `record` still borrows it
+///
+/// ```compile_fail,E0597
+/// let record;
+/// {
+///     let line = String::from("artist=Cellini; work=\"a salt cellar\"");
+///     record = parse_record(&line).unwrap();
+/// }
+/// assert_eq!(record.get("artist"), Some("Cellini"));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Record<'a> {
+    fields: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> Record<'a> {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&'a str, &str)> + '_ {
+        self.fields.iter().map(|(k, v)| (*k, v.as_ref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    // number of values that had to be copied out of the input
+    pub fn owned_count(&self) -> usize {
+        self.fields
+            .iter()
+            .filter(|(_, v)| matches!(v, Cow::Owned(_)))
+            .count()
+    }
+
+    // detach from the input buffer when a record has to outlive it
+    pub fn into_owned(self) -> OwnedRecord {
+        OwnedRecord {
+            fields: self
+                .fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.into_owned()))
+                .collect(),
+        }
+    }
+}
+
+// splits `line` on `;` that are not inside quotes
+// - returns slices of `line`, so nothing is copied
+fn split_fields(line: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                fields.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+// `raw` is the text between the quotes
+// - no backslash means the slice is already the value, so borrow it
+fn unescape(raw: &str, field: usize) -> Result<Cow<'_, str>, RecordError> {
+    if !raw.contains('\\') {
+        return Ok(Cow::Borrowed(raw));
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some(';') => out.push(';'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(ch) => return Err(RecordError::BadEscape { field, ch }),
+            None => return Err(RecordError::UnterminatedQuote { field }),
+        }
+    }
+    Ok(Cow::Owned(out))
+}
+
+fn parse_value(text: &str, field: usize) -> Result<Cow<'_, str>, RecordError> {
+    let Some(rest) = text.strip_prefix('"') else {
+        return Ok(Cow::Borrowed(text));
+    };
+    // find the closing quote, skipping escaped characters
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+                if !rest[i + 1..].trim().is_empty() {
+                    return Err(RecordError::TrailingText { field });
+                }
+                return unescape(&rest[..i], field);
+            }
+            _ => {}
+        }
+    }
+    Err(RecordError::UnterminatedQuote { field })
+}
+
+// `fn parse_record(line: &str) -> Result<Record, ..>` is elided to `fn parse_record<'a>(line: &'a str) -> Result<Record<'a>, ..>`
+pub fn parse_record(line: &str) -> Result<Record<'_>, RecordError> {
+    let mut fields = Vec::new();
+    for (field, part) in split_fields(line).into_iter().enumerate() {
+        let part = part.trim();
+        // allow a trailing `;` and blank fields
+        if part.is_empty() {
+            continue;
+        }
+        let (key, value) = part
+            .split_once('=')
+            .ok_or(RecordError::MissingEquals { field })?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(RecordError::EmptyKey { field });
+        }
+        fields.push((key, parse_value(value.trim(), field)?));
+    }
+    Ok(Record { fields })
+}
+
+// one record per non-empty line; `#` starts a comment line
+pub fn parse_records(text: &str) -> Result<Vec<Record<'_>>, (usize, RecordError)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(n, line)| parse_record(line).map_err(|e| (n + 1, e)))
+        .collect()
+}
+
+// The owned counterpart, copying every key and value into its own `String`
+// - no lifetime parameter, so it can outlive the input
+#[derive(Debug, PartialEq)]
+pub struct OwnedRecord {
+    fields: Vec<(String, String)>,
+}
+
+impl OwnedRecord {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+// The same format read in a single pass over the chars, pushing each key and value into a `String` as it goes
+// - independent of `split_fields`, `parse_value` and `unescape`, so `compare_parsers` measures
+//   copying every byte against borrowing them, not two variations of one parser
+pub fn parse_record_owned(line: &str) -> Result<OwnedRecord, RecordError> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    let mut field = 0;
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.peek() {
+            // allow a trailing `;` and blank fields
+            None => break,
+            Some(';') => {
+                chars.next();
+                field += 1;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let mut key = String::new();
+        loop {
+            match chars.next() {
+                Some('=') => break,
+                Some(';') | None => return Err(RecordError::MissingEquals { field }),
+                Some(c) => key.push(c),
+            }
+        }
+        key.truncate(key.trim_end().len());
+        if key.is_empty() {
+            return Err(RecordError::EmptyKey { field });
+        }
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => value.push(match chars.next() {
+                        Some(c @ ('"' | '\\' | ';')) => c,
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some(ch) => return Err(RecordError::BadEscape { field, ch }),
+                        None => return Err(RecordError::UnterminatedQuote { field }),
+                    }),
+                    Some(c) => value.push(c),
+                    None => return Err(RecordError::UnterminatedQuote { field }),
+                }
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_some_and(|&c| c != ';') {
+                return Err(RecordError::TrailingText { field });
+            }
+        } else {
+            // a bare value runs to the next `;`, unless the `;` is inside quotes
+            let mut in_quotes = false;
+            let mut escaped = false;
+            while let Some(c) = chars.next_if(|&c| in_quotes || escaped || c != ';') {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if in_quotes => escaped = true,
+                    '"' => in_quotes = !in_quotes,
+                    _ => {}
+                }
+                value.push(c);
+            }
+            value.truncate(value.trim_end().len());
+        }
+        fields.push((key, value));
+    }
+    Ok(OwnedRecord { fields })
+}
+
+fn use_parse_record() {
+    let line = r#"name=sean; city="New York"; quote="say \"hi\"";"#;
+    let record = parse_record(line).unwrap();
+    assert_eq!(record.get("name"), Some("sean"));
+    assert_eq!(record.get("city"), Some("New York"));
+    assert_eq!(record.get("quote"), Some(r#"say "hi""#));
+    // only `quote` needed unescaping, the rest point into `line`
+    assert_eq!(record.owned_count(), 1);
+    println!("{:?}", record);
+
+    for bad in ["name", r#"a=1; b="open"#, r#"a="\q""#] {
+        println!("{:?}: {}", bad, parse_record(bad).unwrap_err());
+    }
+}
+
+fn use_record_lifetime() {
+    let record;
+    {
+        let line = String::from("artist=Cellini; work=\"a salt cellar\"");
+        record = parse_record(&line).unwrap();
+        assert_eq!(record.get("artist"), Some("Cellini"));
+        // - `line` is dropped here, so `record` must not be used below, see the example on `Record`
+    }
+
+    // use `into_owned` to copy out what we need before the buffer goes away
+    let owned;
+    {
+        let line = String::from("artist=Cellini; work=\"a salt cellar\"");
+        owned = parse_record(&line).unwrap().into_owned();
+    }
+    assert_eq!(owned.get("work"), Some("a salt cellar"));
+}
+
+// a rough timing comparison against the owned parser
+// - run in release mode for meaningful numbers
+fn compare_parsers() {
+    let text = (0..20_000)
+        .map(|i| {
+            format!(
+                "id={}; name=\"user {}\"; note=\"line\\none\"; active=true\n",
+                i, i
+            )
+        })
+        .collect::<String>();
+
+    let start = Instant::now();
+    let borrowed = parse_records(&text).unwrap();
+    let borrowed_time = start.elapsed();
+
+    let start = Instant::now();
+    let owned = text
+        .lines()
+        .map(parse_record_owned)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let owned_time = start.elapsed();
+
+    assert_eq!(borrowed.len(), owned.len());
+    let copied = borrowed.iter().map(Record::owned_count).sum::<usize>();
+    println!(
+        "borrowed: {:?} ({} values copied), owned: {:?} ({} values copied)",
+        borrowed_time,
+        copied,
+        owned_time,
+        owned.iter().map(OwnedRecord::len).sum::<usize>()
+    );
+}
+
+pub fn use_records() {
+    use_parse_record();
+    use_record_lifetime();
+    compare_parsers();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::process::Command;
+
+    #[test]
+    fn values_borrow_unless_unescaped() {
+        let line = r#"name=sean; city="New York"; quote="say \"hi\"\n";"#;
+        let record = parse_record(line).unwrap();
+        assert_eq!(record.len(), 3);
+        assert_eq!(record.get("city"), Some("New York"));
+        assert_eq!(record.get("quote"), Some("say \"hi\"\n"));
+        assert_eq!(record.get("missing"), None);
+        assert_eq!(record.owned_count(), 1);
+        // borrowed values point into `line` itself
+        let city = record.fields().find(|(k, _)| *k == "city").unwrap().1;
+        let range = line.as_bytes().as_ptr_range();
+        assert!(range.contains(&city.as_ptr()));
+    }
+
+    #[test]
+    fn owned_parser_agrees() {
+        let line = r#"id=7; name="user 7"; note="a\tb"; ; "#;
+        let borrowed = parse_record(line).unwrap();
+        let owned = parse_record_owned(line).unwrap();
+        assert_eq!(borrowed.into_owned(), owned);
+        assert_eq!(owned.len(), 3);
+        assert_eq!(owned.get("note"), Some("a\tb"));
+    }
+
+    #[test]
+    fn parsers_agree() {
+        let lines = [
+            "name",
+            "a=1; =2",
+            r#"a=1; b="open"#,
+            r#"a="\q""#,
+            r#"a="x"y"#,
+            r#"a="x\"#,
+            r#"  spaced key  =  bare value  ; b = "q;uoted" ;; c=x"y;z" "#,
+            r#"path="C:\\dir"; semi="a\;b"; nl="1\n2""#,
+            "",
+        ];
+        for line in lines {
+            assert_eq!(
+                parse_record(line).map(Record::into_owned),
+                parse_record_owned(line),
+                "{:?}",
+                line
+            );
+        }
+    }
+
+    // doctests are not run for a binary crate, so the `compile_fail` example on `Record` is compiled here
+    // - this file only uses `std`, so `rustc` can build it alone as a module of a small program
+    fn compile(name: &str, example: &str) -> Result<(), String> {
+        let dir = std::env::temp_dir().join(format!("records_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join(file!());
+        let main = format!(
+            "#[path = {:?}]\nmod records;\nuse records::*;\nfn main() {{\n{}\n}}\n",
+            source, example
+        );
+        std::fs::write(dir.join("main.rs"), main).unwrap();
+        let output = Command::new("rustc")
+            .args(["--edition", "2021", "--emit", "metadata", "-A", "warnings"])
+            .arg("--out-dir")
+            .arg(&dir)
+            .arg(dir.join("main.rs"))
+            .output()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).into_owned())
+        }
+    }
+
+    #[test]
+    fn record_cannot_outlive_its_buffer() {
+        let example = include_str!("records.rs")
+            .lines()
+            .map(str::trim_start)
+            .skip_while(|l| *l != "/// ```compile_fail,E0597")
+            .skip(1)
+            .take_while(|l| *l != "/// ```")
+            .map(|l| l.trim_start_matches("///").trim_start_matches(' '))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(example.contains("parse_record(&line)"));
+        let err = compile("borrowed", &example).unwrap_err();
+        assert!(err.contains("E0597"), "{}", err);
+        // copied out with `into_owned` it compiles, so the error above is the lifetime and not the harness
+        compile(
+            "owned",
+            &example.replace(".unwrap();", ".unwrap().into_owned();"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        let cases = [
+            ("name", RecordError::MissingEquals { field: 0 }),
+            ("a=1; =2", RecordError::EmptyKey { field: 1 }),
+            (
+                r#"a=1; b="open"#,
+                RecordError::UnterminatedQuote { field: 1 },
+            ),
+            (r#"a="\q""#, RecordError::BadEscape { field: 0, ch: 'q' }),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_record(line), Err(expected), "{:?}", line);
+        }
+        assert!(matches!(
+            parse_record(r#"a="x"y"#),
+            Err(RecordError::TrailingText { field: 0 })
+        ));
+    }
+
+    #[test]
+    fn records_report_their_line() {
+        let text = "# people\nname=ann\n\nname=bob; age=3\n";
+        let records = parse_records(text).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].get("age"), Some("3"));
+        assert_eq!(
+            parse_records("a=1\nb\n"),
+            Err((2, RecordError::MissingEquals { field: 0 }))
+        );
+    }
+}
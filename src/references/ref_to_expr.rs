@@ -1,6 +1,4 @@
-fn factorial(n: usize) -> usize {
-    (1..n + 1).product()
-}
+use crate::traits_generics::factorial;
 
 pub fn ref_to_expr() {
     // a ref to expr - compiler creates a temp variable to hold the result of the expr
@@ -1,4 +1,6 @@
-type Table = std::collections::HashMap<String, Vec<String>>;
+use crate::traits_generics::TableRenderer;
+
+pub type Table = std::collections::HashMap<String, Vec<String>>;
 
 // show_into takes ownership
 fn show_into(table: Table) {
@@ -55,6 +57,15 @@ pub fn ref_to_values() {
     // println!("{:?}", table);
 
     show(&table2);
-    // cannot be used
-    println!("\n{:?}", table2);
+    // a `HashMap` iterates in no particular order, so sort by artist
+    let mut rows = table2
+        .iter()
+        .map(|(artist, works)| (artist, works.join("; ")))
+        .collect::<Vec<_>>();
+    rows.sort();
+    println!();
+    TableRenderer::new()
+        .headers(&["artist", "works"])
+        .print(rows)
+        .unwrap();
 }
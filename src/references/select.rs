@@ -0,0 +1,448 @@
+// Generic selection over slices, growing out of `ref_safety::smallest`
+// - `smallest(&[i32]) -> &i32` panics on an empty slice, these return `Option` instead
+// - every function takes `&[T]` and hands back `&T` or an index, so `T` need not be `Copy` or `Clone`
+// - elided lifetimes again: `fn min_ref<T>(v: &[T]) -> Option<&T>` means the result borrows from `v`
+use crate::traits_generics::TableRenderer;
+use std::cmp::Ordering;
+
+// `_by` variants take a comparison closure like `Iterator::max_by`/`min_by`
+// `_by_key` variants take a key closure like `Iterator::max_by_key`/`min_by_key`
+// - as in std, `min` returns the first of several equal elements and `max` returns the last
+
+pub fn argmin_by<T, F>(v: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut best = 0;
+    for i in 1..v.len() {
+        if compare(&v[i], &v[best]) == Ordering::Less {
+            best = i;
+        }
+    }
+    if v.is_empty() {
+        None
+    } else {
+        Some(best)
+    }
+}
+
+pub fn argmax_by<T, F>(v: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut best = 0;
+    for i in 1..v.len() {
+        if compare(&v[i], &v[best]) != Ordering::Less {
+            best = i;
+        }
+    }
+    if v.is_empty() {
+        None
+    } else {
+        Some(best)
+    }
+}
+
+pub fn argmin<T: Ord>(v: &[T]) -> Option<usize> {
+    argmin_by(v, T::cmp)
+}
+
+pub fn argmax<T: Ord>(v: &[T]) -> Option<usize> {
+    argmax_by(v, T::cmp)
+}
+
+pub fn argmin_by_key<T, K: Ord, F>(v: &[T], mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> K,
+{
+    argmin_by(v, |a, b| f(a).cmp(&f(b)))
+}
+
+pub fn argmax_by_key<T, K: Ord, F>(v: &[T], mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> K,
+{
+    argmax_by(v, |a, b| f(a).cmp(&f(b)))
+}
+
+pub fn min_ref<T: Ord>(v: &[T]) -> Option<&T> {
+    argmin(v).map(|i| &v[i])
+}
+
+pub fn max_ref<T: Ord>(v: &[T]) -> Option<&T> {
+    argmax(v).map(|i| &v[i])
+}
+
+pub fn min_ref_by<T, F>(v: &[T], compare: F) -> Option<&T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    argmin_by(v, compare).map(|i| &v[i])
+}
+
+pub fn max_ref_by<T, F>(v: &[T], compare: F) -> Option<&T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    argmax_by(v, compare).map(|i| &v[i])
+}
+
+pub fn min_ref_by_key<T, K: Ord, F>(v: &[T], f: F) -> Option<&T>
+where
+    F: FnMut(&T) -> K,
+{
+    argmin_by_key(v, f).map(|i| &v[i])
+}
+
+pub fn max_ref_by_key<T, K: Ord, F>(v: &[T], f: F) -> Option<&T>
+where
+    F: FnMut(&T) -> K,
+{
+    argmax_by_key(v, f).map(|i| &v[i])
+}
+
+// A max-heap of at most `k` refs, ordered by `compare`
+// - the root is the largest of the smallest `k` seen so far, so a new item only gets in if it beats the root
+// - O(n log k) time and O(k) extra space
+// - `std::collections::BinaryHeap` needs `Ord` on the items, so it cannot take a comparison closure
+struct BoundedHeap<'a, T, F> {
+    items: Vec<&'a T>,
+    k: usize,
+    compare: F,
+}
+
+impl<'a, T, F> BoundedHeap<'a, T, F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn push(&mut self, item: &'a T) {
+        if self.items.len() < self.k {
+            self.items.push(item);
+            self.sift_up(self.items.len() - 1);
+        } else if self.k > 0 && (self.compare)(item, self.items[0]) == Ordering::Less {
+            self.items[0] = item;
+            self.sift_down(0);
+        }
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if (self.compare)(self.items[i], self.items[parent]) != Ordering::Greater {
+                break;
+            }
+            self.items.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        let len = self.items.len();
+        loop {
+            let (left, right) = (2 * i + 1, 2 * i + 2);
+            let mut largest = i;
+            if left < len
+                && (self.compare)(self.items[left], self.items[largest]) == Ordering::Greater
+            {
+                largest = left;
+            }
+            if right < len
+                && (self.compare)(self.items[right], self.items[largest]) == Ordering::Greater
+            {
+                largest = right;
+            }
+            if largest == i {
+                break;
+            }
+            self.items.swap(i, largest);
+            i = largest;
+        }
+    }
+
+    // sorted ascending
+    fn into_sorted(mut self) -> Vec<&'a T> {
+        let compare = &mut self.compare;
+        self.items.sort_by(|a, b| compare(a, b));
+        self.items
+    }
+}
+
+// the `k` smallest items in ascending order, or all of them if `k >= v.len()`
+pub fn k_smallest_by<T, F>(v: &[T], k: usize, compare: F) -> Vec<&T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut heap = BoundedHeap {
+        items: Vec::with_capacity(k.min(v.len())),
+        k,
+        compare,
+    };
+    for item in v {
+        heap.push(item);
+    }
+    heap.into_sorted()
+}
+
+pub fn k_smallest<T: Ord>(v: &[T], k: usize) -> Vec<&T> {
+    k_smallest_by(v, k, T::cmp)
+}
+
+pub fn k_smallest_by_key<T, K: Ord, F>(v: &[T], k: usize, mut f: F) -> Vec<&T>
+where
+    F: FnMut(&T) -> K,
+{
+    k_smallest_by(v, k, |a, b| f(a).cmp(&f(b)))
+}
+
+// Quickselect over a `Vec<&T>`
+// - we shuffle refs rather than the items themselves, so `v` stays untouched and nothing is cloned
+// - median-of-three pivot keeps already sorted input from hitting the O(n^2) case
+// - a three-way partition keeps items equal to the pivot in the middle, so duplicates are finished in one pass
+//   instead of all landing on one side, which made all-equal input O(n^2)
+fn quickselect<'a, T, F>(refs: &mut [&'a T], n: usize, compare: &mut F) -> &'a T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (mut lo, mut hi) = (0, refs.len() - 1);
+    loop {
+        if lo == hi {
+            return refs[lo];
+        }
+        let mid = lo + (hi - lo) / 2;
+        // order refs[lo] <= refs[mid] <= refs[hi] and take the middle one
+        if compare(refs[mid], refs[lo]) == Ordering::Less {
+            refs.swap(mid, lo);
+        }
+        if compare(refs[hi], refs[lo]) == Ordering::Less {
+            refs.swap(hi, lo);
+        }
+        if compare(refs[hi], refs[mid]) == Ordering::Less {
+            refs.swap(mid, hi);
+        }
+        let pivot = refs[mid];
+
+        // Dutch national flag: [lo, lt) is less, [lt, i) is equal, (gt, hi] is greater than the pivot
+        let (mut lt, mut i, mut gt) = (lo, lo, hi);
+        while i <= gt {
+            match compare(refs[i], pivot) {
+                Ordering::Less => {
+                    refs.swap(i, lt);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    refs.swap(i, gt);
+                    // `gt` cannot go below `lo`, the pivot itself is never greater
+                    gt -= 1;
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+
+        if n < lt {
+            hi = lt - 1;
+        } else if n > gt {
+            lo = gt + 1;
+        } else {
+            return refs[n];
+        }
+    }
+}
+
+// the item that would be at index `n` if `v` were sorted, in expected O(n) time
+pub fn nth_element_by<T, F>(v: &[T], n: usize, mut compare: F) -> Option<&T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if n >= v.len() {
+        return None;
+    }
+    let mut refs = v.iter().collect::<Vec<_>>();
+    Some(quickselect(&mut refs, n, &mut compare))
+}
+
+pub fn nth_element<T: Ord>(v: &[T], n: usize) -> Option<&T> {
+    nth_element_by(v, n, T::cmp)
+}
+
+pub fn nth_element_by_key<T, K: Ord, F>(v: &[T], n: usize, mut f: F) -> Option<&T>
+where
+    F: FnMut(&T) -> K,
+{
+    nth_element_by(v, n, |a, b| f(a).cmp(&f(b)))
+}
+
+// For an even number of items this is the lower median
+// - a generic `T` cannot be averaged, and returning a ref means we must pick an actual element
+pub fn median_by<T, F>(v: &[T], compare: F) -> Option<&T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    nth_element_by(v, v.len().saturating_sub(1) / 2, compare)
+}
+
+pub fn median<T: Ord>(v: &[T]) -> Option<&T> {
+    median_by(v, T::cmp)
+}
+
+pub fn median_by_key<T, K: Ord, F>(v: &[T], mut f: F) -> Option<&T>
+where
+    F: FnMut(&T) -> K,
+{
+    median_by(v, |a, b| f(a).cmp(&f(b)))
+}
+
+fn use_min_max() {
+    let parabola = [9, 4, 1, 0, 1, 4, 9];
+    assert_eq!(min_ref(&parabola), Some(&0));
+    assert_eq!(max_ref(&parabola), Some(&9));
+    // first of equal minima, last of equal maxima - same as `Iterator::min`/`max`
+    println!(
+        "argmin {:?}, argmax {:?}",
+        argmin(&parabola),
+        argmax(&parabola)
+    );
+
+    // where `smallest` would panic
+    let empty: [i32; 0] = [];
+    println!("min of nothing: {:?}", min_ref(&empty));
+
+    // `f64` is not `Ord`, so use the `_by` variant with `total_cmp`
+    let temps = [21.5, -3.0, 17.25];
+    println!("hottest: {:?}", max_ref_by(&temps, f64::total_cmp));
+}
+
+fn use_select_non_copy() {
+    // `String` is neither `Copy` nor cheap to clone, and we only ever get refs back
+    let cities = vec![
+        ("Tokyo".to_string(), 37_400_068),
+        ("Delhi".to_string(), 28_514_000),
+        ("Shanghai".to_string(), 25_582_000),
+        ("Sao Paulo".to_string(), 21_650_000),
+        ("Mexico City".to_string(), 21_581_000),
+    ];
+    let (name, _) = max_ref_by_key(&cities, |(_, pop)| *pop).unwrap();
+    assert_eq!(name, "Tokyo");
+    // "Tokyo" and "Delhi" tie on length, the first one wins
+    let (name, _) = min_ref_by_key(&cities, |(name, _)| name.len()).unwrap();
+    println!("shortest name: {}", name);
+    let smallest_two = k_smallest_by_key(&cities, 2, |(_, pop)| *pop);
+    println!("two smallest: {}, {}", smallest_two[0].0, smallest_two[1].0);
+    let (name, _) = median_by_key(&cities, |(_, pop)| *pop).unwrap();
+    println!("median: {}", name);
+    TableRenderer::new()
+        .headers(&["city", "population"])
+        .print(&cities)
+        .unwrap();
+}
+
+fn use_k_smallest_and_nth() {
+    let v = vec![5, 3, 8, 1, 9, 2, 7, 4, 6, 0];
+    assert_eq!(k_smallest(&v, 3), vec![&0, &1, &2]);
+    // lower median for an even length
+    println!(
+        "3rd smallest {:?}, median {:?}",
+        nth_element(&v, 2),
+        median(&v)
+    );
+    // `v` is untouched, we only reordered refs
+    assert_eq!(v, vec![5, 3, 8, 1, 9, 2, 7, 4, 6, 0]);
+}
+
+pub fn use_select() {
+    use_min_max();
+    use_select_non_copy();
+    use_k_smallest_and_nth();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_ties_and_empty() {
+        let parabola = [9, 4, 1, 0, 1, 4, 9];
+        assert_eq!(min_ref(&parabola), Some(&0));
+        assert_eq!(max_ref(&parabola), Some(&9));
+        // first of equal minima, last of equal maxima
+        assert_eq!(argmin(&[3, 1, 1]), Some(1));
+        assert_eq!(argmax(&parabola), Some(6));
+        let empty: [i32; 0] = [];
+        assert_eq!(min_ref(&empty), None);
+        assert_eq!(max_ref(&empty), None);
+        assert_eq!(argmin(&empty), None);
+        assert_eq!(argmax(&empty), None);
+        let temps = [21.5, -3.0, 17.25];
+        assert_eq!(max_ref_by(&temps, f64::total_cmp), Some(&21.5));
+        assert_eq!(min_ref_by(&temps, f64::total_cmp), Some(&-3.0));
+    }
+
+    #[test]
+    fn by_key_on_non_copy_items() {
+        let cities = [
+            ("Tokyo".to_string(), 37_400_068),
+            ("Delhi".to_string(), 28_514_000),
+            ("Shanghai".to_string(), 25_582_000),
+            ("Sao Paulo".to_string(), 21_650_000),
+            ("Mexico City".to_string(), 21_581_000),
+        ];
+        assert_eq!(max_ref_by_key(&cities, |(_, pop)| *pop).unwrap().0, "Tokyo");
+        // "Tokyo" and "Delhi" tie on length, the first one wins
+        assert_eq!(
+            min_ref_by_key(&cities, |(name, _)| name.len()).unwrap().0,
+            "Tokyo"
+        );
+        let smallest_two = k_smallest_by_key(&cities, 2, |(_, pop)| *pop);
+        assert_eq!(smallest_two[0].0, "Mexico City");
+        assert_eq!(smallest_two[1].0, "Sao Paulo");
+        assert_eq!(
+            median_by_key(&cities, |(_, pop)| *pop).unwrap().0,
+            "Shanghai"
+        );
+    }
+
+    #[test]
+    fn k_smallest_edges() {
+        let v = vec![5, 3, 8, 1, 9, 2, 7, 4, 6, 0];
+        assert_eq!(k_smallest(&v, 3), vec![&0, &1, &2]);
+        assert_eq!(k_smallest(&v, 0), Vec::<&i32>::new());
+        assert_eq!(k_smallest(&v, 100).len(), v.len());
+        assert_eq!(k_smallest(&[2, 1, 2, 1], 3), vec![&1, &1, &2]);
+        assert_eq!(median(&v), Some(&4));
+        assert_eq!(median(&[2, 1, 3]), Some(&2));
+        assert_eq!(median::<i32>(&[]), None);
+        assert_eq!(v, vec![5, 3, 8, 1, 9, 2, 7, 4, 6, 0]);
+    }
+
+    #[test]
+    fn quickselect_matches_sorting() {
+        let inputs: [&[i32]; 6] = [
+            &[5],
+            &[2, 2, 2, 2, 2, 2, 2],
+            &[3, 1, 2, 3, 1, 2, 3, 1],
+            &[9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+            &[1, 5, 1, 5, 1, 5, 0, 9],
+            &[-4, 7, 7, 0, -4, 12, 7, 3, 0],
+        ];
+        for input in inputs {
+            let mut sorted = input.to_vec();
+            sorted.sort();
+            for (n, expected) in sorted.iter().enumerate() {
+                assert_eq!(nth_element(input, n), Some(expected), "{:?}[{}]", input, n);
+            }
+            assert_eq!(nth_element(input, input.len()), None);
+        }
+    }
+
+    #[test]
+    fn all_equal_input_is_linear() {
+        let v = vec![7u8; 100_000];
+        let mut comparisons = 0;
+        let median = median_by(&v, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(median, Some(&7));
+        assert!(comparisons < 4 * v.len(), "{} comparisons", comparisons);
+    }
+}
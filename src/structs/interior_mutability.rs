@@ -0,0 +1,222 @@
+// There are times when we need a little bit of mutable data inside an otherwise immutable value
+// - a mutable field of a immutable struct
+// - we can use `Cell` or `RefCell`
+
+use super::{Level, RobotLog};
+use std::cell::Cell;
+// build with `--features tracked-borrows` to swap in `TrackedRefCell`
+// - it has the same API, so `Robot` compiles unchanged, and a double borrow names every holder
+#[cfg(feature = "tracked-borrows")]
+use super::{TrackedRef as Ref, TrackedRefCell as RefCell};
+#[cfg(not(feature = "tracked-borrows"))]
+use std::cell::{Ref, RefCell};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Debug)]
+struct Robot {
+    hardware_error_count: Cell<u32>,
+    log: RefCell<RobotLog>,
+}
+
+impl Robot {
+    fn new(log_capacity: usize) -> Self {
+        let robot = Robot {
+            hardware_error_count: Cell::new(0),
+            log: RefCell::new(RobotLog::with_capacity(log_capacity)),
+        };
+        robot.write_log("Initialized");
+        robot
+    }
+
+    // `add_hardware_error` borrows an immutable ref to `self`
+    fn add_hardware_error(&self) {
+        // - use `Cell<T>`'s `.get()` to retrive value that is stored in a `Cell<T>`
+        // - `Cell<T>`'s `.get()` copy the value so <T> must be a `Copy` type
+        // - while `RefCell<T>`'s `.get()` return a ref to the value
+        let n = self.hardware_error_count.get();
+        // - use `Cell`'s `.set(new_value)` to assign a value to `Cell`, dropping previous one
+        self.hardware_error_count.set(n + 1);
+    }
+
+    fn write_log(&self, entry: &str) {
+        self.log_with(Level::Info, entry, &[]);
+    }
+
+    fn log_with(&self, level: Level, message: &str, fields: &[(&str, &str)]) {
+        // `.borrow()` return a ref to the value, panic if already mutably borrowed
+        // `.borrow_mut()` return a mutable ref to the value, panic if already borrowed
+
+        // `.try_borrow()` return a Result<a ref to the value, err>
+        // `.try_borrow_mut()` return a Result< a mutable ref to the value, err>
+
+        // - the `mut` keyword is still needed
+        let mut prev_log = self.log.borrow_mut();
+        // prev_log.push("found an hardware error".to_string());
+        prev_log.record(level, message, fields);
+    }
+
+    // `Ref` keeps the log borrowed for as long as the caller holds it
+    // - e.g. `robot.log_entries().at_least(Level::Warn).count()`
+    // - calling `write_log` while holding it would panic
+    fn log_entries(&self) -> Ref<'_, RobotLog> {
+        self.log.borrow()
+    }
+
+    fn print_log(&self) {
+        let curr_log = self.log.borrow();
+        for entry in curr_log.iter() {
+            println!("{}", entry);
+        }
+    }
+}
+pub fn interior_mutability() {
+    let robot = Robot::new(3);
+    println!("Robot is: {:?}", robot);
+    robot.print_log();
+
+    robot.add_hardware_error();
+    robot.write_log("found an hardware error");
+    println!("\nRobot is: {:?}", robot);
+    robot.print_log();
+
+    robot.log_with(Level::Warn, "battery low", &[("pct", "9")]);
+    robot.log_with(Level::Error, "motor stalled", &[("side", "left")]);
+    // capacity is 3, so "Initialized" has been pushed out
+    robot.print_log();
+}
+
+// `Cell` and `RefCell` are not thread safe, so `Robot` is `!Sync`
+// - sharing `&Robot` between threads is rejected at compile time
+// - not working since `Cell<u32>` cannot be shared between threads safely
+// let robot = Robot::new(16);
+// thread::scope(|s| {
+//     s.spawn(|| robot.add_hardware_error());
+// });
+//
+// The crate stops compiling if either robot changes sides
+// - `assert_sync::<SyncRobot>()` needs `SyncRobot: Sync`
+// - `<Robot as AmbiguousIfSync<_>>` has one candidate impl for a `!Sync` type and two for a `Sync` one,
+//   and two leave `_` ambiguous, which is an error; a plain `T: !Sync` bound does not exist on stable
+trait AmbiguousIfSync<A> {
+    fn check() {}
+}
+
+impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+impl<T: ?Sized + Sync> AmbiguousIfSync<u8> for T {}
+
+const _: fn() = || {
+    fn assert_sync<T: Sync>() {}
+    assert_sync::<SyncRobot>();
+    <Robot as AmbiguousIfSync<_>>::check();
+};
+
+// The thread-safe counterparts keep the same `&self` API
+// - `AtomicU32` replaces `Cell<u32>`, `fetch_add` reads and writes in one step so no update is lost
+// - `Mutex<T>` replaces `RefCell<T>`, `.lock()` blocks until other threads release it instead of panicking
+// - `.lock()` returns `Err` only if another thread panicked while holding the lock, i.e., the mutex is poisoned
+#[derive(Debug)]
+struct SyncRobot {
+    hardware_error_count: AtomicU32,
+    log: Mutex<Vec<String>>,
+}
+
+impl SyncRobot {
+    fn new() -> Self {
+        SyncRobot {
+            hardware_error_count: AtomicU32::new(0),
+            log: Mutex::new(vec!["Initialized".to_string()]),
+        }
+    }
+
+    fn add_hardware_error(&self) {
+        // - `Ordering::Relaxed` is enough for a counter nobody synchronizes on
+        self.hardware_error_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn hardware_error_count(&self) -> u32 {
+        self.hardware_error_count.load(Ordering::Relaxed)
+    }
+
+    fn write_log(&self, entry: &str) {
+        // - the guard unlocks the mutex when it is dropped, at the end of this function
+        let mut log = self.log.lock().unwrap();
+        log.push(entry.to_string());
+    }
+
+    fn log_len(&self) -> usize {
+        self.log.lock().unwrap().len()
+    }
+
+    fn print_log(&self) {
+        let log = self.log.lock().unwrap();
+        println!("{:?}", log);
+    }
+}
+
+// `thread::scope` lets spawned threads borrow `robot` since they are all joined before the scope ends
+pub fn sync_interior_mutability() {
+    const THREADS: u32 = 8;
+    const ERRORS_PER_THREAD: u32 = 10_000;
+
+    let robot = SyncRobot::new();
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let robot = &robot;
+            s.spawn(move || {
+                for _ in 0..ERRORS_PER_THREAD {
+                    robot.add_hardware_error();
+                }
+                robot.write_log(&format!("thread {} done", t));
+            });
+        }
+    });
+
+    // no increment is lost and every thread wrote exactly one entry
+    println!(
+        "hardware errors: {} of {}, log entries: {}",
+        robot.hardware_error_count(),
+        THREADS * ERRORS_PER_THREAD,
+        robot.log_len()
+    );
+    robot.print_log();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robot_log_keeps_the_newest_entries() {
+        let robot = Robot::new(3);
+        robot.add_hardware_error();
+        robot.write_log("found an hardware error");
+        robot.log_with(Level::Warn, "battery low", &[("pct", "9")]);
+        robot.log_with(Level::Error, "motor stalled", &[("side", "left")]);
+        assert_eq!(robot.hardware_error_count.get(), 1);
+        assert_eq!(robot.log_entries().len(), 3);
+        assert_eq!(robot.log_entries().dropped(), 1);
+        assert_eq!(robot.log_entries().at_least(Level::Warn).count(), 2);
+    }
+
+    #[test]
+    fn sync_robot_loses_no_updates() {
+        const THREADS: u32 = 8;
+        const ERRORS_PER_THREAD: u32 = 10_000;
+        let robot = SyncRobot::new();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let robot = &robot;
+                s.spawn(move || {
+                    for _ in 0..ERRORS_PER_THREAD {
+                        robot.add_hardware_error();
+                    }
+                    robot.write_log(&format!("thread {} done", t));
+                });
+            }
+        });
+        assert_eq!(robot.hardware_error_count(), THREADS * ERRORS_PER_THREAD);
+        assert_eq!(robot.log_len(), 1 + THREADS as usize);
+    }
+}
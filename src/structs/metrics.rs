@@ -0,0 +1,764 @@
+// A metrics registry generalizing `Robot::hardware_error_count: Cell<u32>`
+// - counters only go up, gauges go up and down, histograms count observations into fixed buckets
+// - metrics are registered by name and recorded through `&self`, like `add_hardware_error`
+// - `CellMetrics` uses `Cell`/`RefCell` and is for one thread, `AtomicMetrics` uses atomics and can be shared
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsError {
+    InvalidName(String),
+    AlreadyRegistered(String),
+    Unknown(String),
+    WrongKind { name: String, kind: MetricKind },
+    InvalidBuckets(String),
+}
+
+impl fmt::Display for MetricsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetricsError::InvalidName(name) => write!(f, "invalid metric name {:?}", name),
+            MetricsError::AlreadyRegistered(name) => {
+                write!(f, "metric {:?} is already registered", name)
+            }
+            MetricsError::Unknown(name) => write!(f, "no metric named {:?}", name),
+            MetricsError::WrongKind { name, kind } => write!(f, "{:?} is a {}", name, kind),
+            MetricsError::InvalidBuckets(name) => write!(
+                f,
+                "buckets of {:?} must be finite and strictly increasing",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MetricsError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl fmt::Display for MetricKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        })
+    }
+}
+
+// A point-in-time copy of every metric, detached from the registry
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricValue {
+    Counter(u64),
+    Gauge(f64),
+    // `buckets` holds `(upper bound, cumulative count)` pairs, the `+Inf` bucket is `count`
+    Histogram {
+        buckets: Vec<(f64, u64)>,
+        sum: f64,
+        count: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSnapshot {
+    pub name: String,
+    pub help: String,
+    pub value: MetricValue,
+}
+
+// sorted by name, since both registries keep metrics in a `BTreeMap`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Snapshot {
+    pub metrics: Vec<MetricSnapshot>,
+}
+
+impl Snapshot {
+    pub fn get(&self, name: &str) -> Option<&MetricValue> {
+        self.metrics
+            .iter()
+            .find(|m| m.name == name)
+            .map(|m| &m.value)
+    }
+
+    // Prometheus text exposition format, version 0.0.4
+    pub fn write_prometheus<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        for metric in &self.metrics {
+            let name = &metric.name;
+            if !metric.help.is_empty() {
+                let help = metric.help.replace('\\', "\\\\").replace('\n', "\\n");
+                writeln!(out, "# HELP {} {}", name, help)?;
+            }
+            match &metric.value {
+                MetricValue::Counter(n) => {
+                    writeln!(out, "# TYPE {} counter", name)?;
+                    writeln!(out, "{} {}", name, n)?;
+                }
+                MetricValue::Gauge(v) => {
+                    writeln!(out, "# TYPE {} gauge", name)?;
+                    writeln!(out, "{} {}", name, PromFloat(*v))?;
+                }
+                MetricValue::Histogram {
+                    buckets,
+                    sum,
+                    count,
+                } => {
+                    writeln!(out, "# TYPE {} histogram", name)?;
+                    for (le, n) in buckets {
+                        writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, PromFloat(*le), n)?;
+                    }
+                    writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count)?;
+                    writeln!(out, "{}_sum {}", name, PromFloat(*sum))?;
+                    writeln!(out, "{}_count {}", name, count)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Prometheus spells the special floats `NaN`, `+Inf` and `-Inf`
+struct PromFloat(f64);
+
+impl fmt::Display for PromFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            v if v.is_nan() => f.write_str("NaN"),
+            v if v == f64::INFINITY => f.write_str("+Inf"),
+            v if v == f64::NEG_INFINITY => f.write_str("-Inf"),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
+// Both backends implement this, so code that records metrics can be generic over them
+// - every method takes `&self`, which is the point of interior mutability
+pub trait Metrics {
+    fn register_counter(&self, name: &str, help: &str) -> Result<(), MetricsError>;
+    fn register_gauge(&self, name: &str, help: &str) -> Result<(), MetricsError>;
+    // `buckets` are upper bounds, an observation lands in the first bucket with `value <= bound`
+    fn register_histogram(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+    ) -> Result<(), MetricsError>;
+
+    fn inc_by(&self, name: &str, n: u64) -> Result<(), MetricsError>;
+    fn set(&self, name: &str, value: f64) -> Result<(), MetricsError>;
+    fn add(&self, name: &str, delta: f64) -> Result<(), MetricsError>;
+    fn observe(&self, name: &str, value: f64) -> Result<(), MetricsError>;
+
+    fn snapshot(&self) -> Snapshot;
+
+    // default methods are written once in terms of the required ones
+    fn inc(&self, name: &str) -> Result<(), MetricsError> {
+        self.inc_by(name, 1)
+    }
+
+    fn write_prometheus(&self, out: &mut dyn Write) -> io::Result<()> {
+        self.snapshot().write_prometheus(out)
+    }
+}
+
+// names follow the Prometheus rule `[a-zA-Z_:][a-zA-Z0-9_:]*`
+fn check_name(name: &str) -> Result<(), MetricsError> {
+    let mut chars = name.chars();
+    let valid_first =
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':');
+    if valid_first && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+        Ok(())
+    } else {
+        Err(MetricsError::InvalidName(name.to_string()))
+    }
+}
+
+fn check_buckets(name: &str, buckets: &[f64]) -> Result<(), MetricsError> {
+    let finite = buckets.iter().all(|b| b.is_finite());
+    let increasing = buckets.windows(2).all(|w| w[0] < w[1]);
+    if finite && increasing {
+        Ok(())
+    } else {
+        Err(MetricsError::InvalidBuckets(name.to_string()))
+    }
+}
+
+fn wrong_kind(name: &str, kind: MetricKind) -> MetricsError {
+    MetricsError::WrongKind {
+        name: name.to_string(),
+        kind,
+    }
+}
+
+// `counts[i]` is the number of observations in bucket `i` alone, snapshots make them cumulative
+fn cumulative(bounds: &[f64], counts: impl Iterator<Item = u64>) -> Vec<(f64, u64)> {
+    let mut total = 0;
+    bounds
+        .iter()
+        .zip(counts)
+        .map(|(&bound, n)| {
+            total += n;
+            (bound, total)
+        })
+        .collect()
+}
+
+// Single-threaded backend
+// - the map is in a `RefCell` because registering adds entries
+// - each value is in a `Cell`, so recording only needs `.borrow()` and never conflicts with another reader
+// - `Cell<T>` needs `T: Copy` for `.get()`, which `u64` and `f64` are
+#[derive(Debug)]
+enum CellMetric {
+    Counter(Cell<u64>),
+    Gauge(Cell<f64>),
+    Histogram {
+        bounds: Vec<f64>,
+        counts: Vec<Cell<u64>>,
+        sum: Cell<f64>,
+        count: Cell<u64>,
+    },
+}
+
+impl CellMetric {
+    fn kind(&self) -> MetricKind {
+        match self {
+            CellMetric::Counter(_) => MetricKind::Counter,
+            CellMetric::Gauge(_) => MetricKind::Gauge,
+            CellMetric::Histogram { .. } => MetricKind::Histogram,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CellMetrics {
+    metrics: RefCell<BTreeMap<String, (String, CellMetric)>>,
+}
+
+impl CellMetrics {
+    pub fn new() -> Self {
+        CellMetrics::default()
+    }
+
+    fn register(&self, name: &str, help: &str, metric: CellMetric) -> Result<(), MetricsError> {
+        check_name(name)?;
+        let mut metrics = self.metrics.borrow_mut();
+        if metrics.contains_key(name) {
+            return Err(MetricsError::AlreadyRegistered(name.to_string()));
+        }
+        metrics.insert(name.to_string(), (help.to_string(), metric));
+        Ok(())
+    }
+
+    // runs `f` on the metric while the map is borrowed
+    fn with<R>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&CellMetric) -> Result<R, MetricsError>,
+    ) -> Result<R, MetricsError> {
+        let metrics = self.metrics.borrow();
+        let (_, metric) = metrics
+            .get(name)
+            .ok_or_else(|| MetricsError::Unknown(name.to_string()))?;
+        f(metric)
+    }
+}
+
+impl Metrics for CellMetrics {
+    fn register_counter(&self, name: &str, help: &str) -> Result<(), MetricsError> {
+        self.register(name, help, CellMetric::Counter(Cell::new(0)))
+    }
+
+    fn register_gauge(&self, name: &str, help: &str) -> Result<(), MetricsError> {
+        self.register(name, help, CellMetric::Gauge(Cell::new(0.0)))
+    }
+
+    fn register_histogram(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+    ) -> Result<(), MetricsError> {
+        check_buckets(name, buckets)?;
+        let metric = CellMetric::Histogram {
+            bounds: buckets.to_vec(),
+            counts: buckets.iter().map(|_| Cell::new(0)).collect(),
+            sum: Cell::new(0.0),
+            count: Cell::new(0),
+        };
+        self.register(name, help, metric)
+    }
+
+    fn inc_by(&self, name: &str, n: u64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            CellMetric::Counter(c) => {
+                c.set(c.get() + n);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn set(&self, name: &str, value: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            CellMetric::Gauge(g) => {
+                g.set(value);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn add(&self, name: &str, delta: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            CellMetric::Gauge(g) => {
+                g.set(g.get() + delta);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn observe(&self, name: &str, value: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            CellMetric::Histogram {
+                bounds,
+                counts,
+                sum,
+                count,
+            } => {
+                // values above every bound only show up in the `+Inf` bucket, i.e., `count`
+                if let Some(i) = bounds.iter().position(|&b| value <= b) {
+                    counts[i].set(counts[i].get() + 1);
+                }
+                sum.set(sum.get() + value);
+                count.set(count.get() + 1);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let metrics = self.metrics.borrow();
+        Snapshot {
+            metrics: metrics
+                .iter()
+                .map(|(name, (help, metric))| MetricSnapshot {
+                    name: name.clone(),
+                    help: help.clone(),
+                    value: match metric {
+                        CellMetric::Counter(c) => MetricValue::Counter(c.get()),
+                        CellMetric::Gauge(g) => MetricValue::Gauge(g.get()),
+                        CellMetric::Histogram {
+                            bounds,
+                            counts,
+                            sum,
+                            count,
+                        } => MetricValue::Histogram {
+                            buckets: cumulative(bounds, counts.iter().map(Cell::get)),
+                            sum: sum.get(),
+                            count: count.get(),
+                        },
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+// Thread-safe backend
+// - `RwLock` replaces `RefCell`: recording takes the read lock, so threads record concurrently
+// - atomics replace `Cell`; there is no `AtomicF64`, so floats are stored as their bits in an `AtomicU64`
+// - updating a float is a compare-and-swap loop: retry if another thread changed it in between
+#[derive(Debug)]
+struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    fn new(value: f64) -> Self {
+        AtomicF64(AtomicU64::new(value.to_bits()))
+    }
+
+    fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    fn add(&self, delta: f64) {
+        let mut current = self.0.load(Ordering::Relaxed);
+        loop {
+            let new = (f64::from_bits(current) + delta).to_bits();
+            match self
+                .0
+                .compare_exchange_weak(current, new, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum AtomicMetric {
+    Counter(AtomicU64),
+    Gauge(AtomicF64),
+    Histogram {
+        bounds: Vec<f64>,
+        counts: Vec<AtomicU64>,
+        sum: AtomicF64,
+        count: AtomicU64,
+    },
+}
+
+impl AtomicMetric {
+    fn kind(&self) -> MetricKind {
+        match self {
+            AtomicMetric::Counter(_) => MetricKind::Counter,
+            AtomicMetric::Gauge(_) => MetricKind::Gauge,
+            AtomicMetric::Histogram { .. } => MetricKind::Histogram,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AtomicMetrics {
+    metrics: RwLock<BTreeMap<String, (String, AtomicMetric)>>,
+}
+
+impl AtomicMetrics {
+    pub fn new() -> Self {
+        AtomicMetrics::default()
+    }
+
+    fn register(&self, name: &str, help: &str, metric: AtomicMetric) -> Result<(), MetricsError> {
+        check_name(name)?;
+        let mut metrics = self.metrics.write().unwrap();
+        if metrics.contains_key(name) {
+            return Err(MetricsError::AlreadyRegistered(name.to_string()));
+        }
+        metrics.insert(name.to_string(), (help.to_string(), metric));
+        Ok(())
+    }
+
+    fn with<R>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&AtomicMetric) -> Result<R, MetricsError>,
+    ) -> Result<R, MetricsError> {
+        let metrics = self.metrics.read().unwrap();
+        let (_, metric) = metrics
+            .get(name)
+            .ok_or_else(|| MetricsError::Unknown(name.to_string()))?;
+        f(metric)
+    }
+}
+
+impl Metrics for AtomicMetrics {
+    fn register_counter(&self, name: &str, help: &str) -> Result<(), MetricsError> {
+        self.register(name, help, AtomicMetric::Counter(AtomicU64::new(0)))
+    }
+
+    fn register_gauge(&self, name: &str, help: &str) -> Result<(), MetricsError> {
+        self.register(name, help, AtomicMetric::Gauge(AtomicF64::new(0.0)))
+    }
+
+    fn register_histogram(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+    ) -> Result<(), MetricsError> {
+        check_buckets(name, buckets)?;
+        let metric = AtomicMetric::Histogram {
+            bounds: buckets.to_vec(),
+            counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicF64::new(0.0),
+            count: AtomicU64::new(0),
+        };
+        self.register(name, help, metric)
+    }
+
+    fn inc_by(&self, name: &str, n: u64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            AtomicMetric::Counter(c) => {
+                c.fetch_add(n, Ordering::Relaxed);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn set(&self, name: &str, value: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            AtomicMetric::Gauge(g) => {
+                g.set(value);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn add(&self, name: &str, delta: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            AtomicMetric::Gauge(g) => {
+                g.add(delta);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    fn observe(&self, name: &str, value: f64) -> Result<(), MetricsError> {
+        self.with(name, |metric| match metric {
+            AtomicMetric::Histogram {
+                bounds,
+                counts,
+                sum,
+                count,
+            } => {
+                if let Some(i) = bounds.iter().position(|&b| value <= b) {
+                    counts[i].fetch_add(1, Ordering::Relaxed);
+                }
+                sum.add(value);
+                count.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            _ => Err(wrong_kind(name, metric.kind())),
+        })
+    }
+
+    // each value is read atomically, but a snapshot taken while other threads record
+    // may see a histogram's `count` and buckets from slightly different moments
+    fn snapshot(&self) -> Snapshot {
+        let metrics = self.metrics.read().unwrap();
+        Snapshot {
+            metrics: metrics
+                .iter()
+                .map(|(name, (help, metric))| MetricSnapshot {
+                    name: name.clone(),
+                    help: help.clone(),
+                    value: match metric {
+                        AtomicMetric::Counter(c) => MetricValue::Counter(c.load(Ordering::Relaxed)),
+                        AtomicMetric::Gauge(g) => MetricValue::Gauge(g.get()),
+                        AtomicMetric::Histogram {
+                            bounds,
+                            counts,
+                            sum,
+                            count,
+                        } => MetricValue::Histogram {
+                            buckets: cumulative(
+                                bounds,
+                                counts.iter().map(|c| c.load(Ordering::Relaxed)),
+                            ),
+                            sum: sum.get(),
+                            count: count.load(Ordering::Relaxed),
+                        },
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+// generic over the backend, so the same code records into either one
+fn register_robot_metrics<M: Metrics>(metrics: &M) -> Result<(), MetricsError> {
+    metrics.register_counter("robot_hardware_errors_total", "Hardware errors seen")?;
+    metrics.register_gauge("robot_battery_percent", "Remaining battery")?;
+    metrics.register_histogram(
+        "robot_move_seconds",
+        "Time taken per move",
+        &[0.05, 0.1, 0.5, 1.0],
+    )
+}
+
+const MOVE_SECONDS: [f64; 5] = [0.02, 0.07, 0.3, 0.09, 2.5];
+
+fn run_robot<M: Metrics>(metrics: &M) -> Result<(), MetricsError> {
+    for (i, secs) in MOVE_SECONDS.iter().enumerate() {
+        metrics.observe("robot_move_seconds", *secs)?;
+        metrics.add("robot_battery_percent", -1.5)?;
+        if i % 2 == 0 {
+            metrics.inc("robot_hardware_errors_total")?;
+        }
+    }
+    Ok(())
+}
+
+fn use_cell_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    let metrics = CellMetrics::new();
+    register_robot_metrics(&metrics)?;
+    metrics.set("robot_battery_percent", 100.0)?;
+    run_robot(&metrics)?;
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(
+        snapshot.get("robot_hardware_errors_total"),
+        Some(&MetricValue::Counter(3))
+    );
+    // a gauge is not a counter, so `inc` is refused rather than silently ignored
+    if let Err(e) = metrics.inc("robot_battery_percent") {
+        println!("{}", e);
+    }
+
+    let mut text = Vec::new();
+    metrics.write_prometheus(&mut text)?;
+    print!("{}", String::from_utf8(text)?);
+    Ok(())
+}
+
+// `AtomicMetrics` is `Sync`, so scoped threads can record into it through a shared ref
+fn use_atomic_metrics() -> Result<(), MetricsError> {
+    let metrics = AtomicMetrics::new();
+    register_robot_metrics(&metrics)?;
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    run_robot(&metrics).unwrap();
+                }
+            });
+        }
+    });
+
+    let snapshot = metrics.snapshot();
+    println!(
+        "8 threads: {:?} errors, battery {:?}",
+        snapshot.get("robot_hardware_errors_total"),
+        snapshot.get("robot_battery_percent")
+    );
+    Ok(())
+}
+
+pub fn use_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    use_cell_metrics()?;
+    use_atomic_metrics()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_metrics_record_the_robot_run() {
+        let metrics = CellMetrics::new();
+        register_robot_metrics(&metrics).unwrap();
+        metrics.set("robot_battery_percent", 100.0).unwrap();
+        run_robot(&metrics).unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.get("robot_hardware_errors_total"),
+            Some(&MetricValue::Counter(3))
+        );
+        assert_eq!(
+            snapshot.get("robot_battery_percent"),
+            Some(&MetricValue::Gauge(92.5))
+        );
+        assert_eq!(
+            snapshot.get("robot_move_seconds"),
+            Some(&MetricValue::Histogram {
+                buckets: vec![(0.05, 1), (0.1, 3), (0.5, 4), (1.0, 4)],
+                sum: MOVE_SECONDS.iter().sum(),
+                count: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn registry_errors() {
+        let metrics = CellMetrics::new();
+        register_robot_metrics(&metrics).unwrap();
+        assert_eq!(
+            metrics.inc("robot_battery_percent"),
+            Err(MetricsError::WrongKind {
+                name: "robot_battery_percent".to_string(),
+                kind: MetricKind::Gauge
+            })
+        );
+        assert!(matches!(
+            metrics.register_counter("robot_hardware_errors_total", ""),
+            Err(MetricsError::AlreadyRegistered(_))
+        ));
+        assert!(matches!(
+            metrics.register_gauge("bad name", ""),
+            Err(MetricsError::InvalidName(_))
+        ));
+        assert!(matches!(
+            metrics.register_histogram("h", "", &[1.0, 0.5]),
+            Err(MetricsError::InvalidBuckets(_))
+        ));
+        assert!(matches!(
+            metrics.observe("missing", 1.0),
+            Err(MetricsError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn prometheus_text() {
+        let metrics = CellMetrics::new();
+        metrics.register_counter("jobs_total", "Jobs done").unwrap();
+        metrics.register_histogram("latency", "", &[1.0]).unwrap();
+        metrics.inc_by("jobs_total", 2).unwrap();
+        metrics.observe("latency", 0.5).unwrap();
+        metrics.observe("latency", 3.0).unwrap();
+
+        let mut text = Vec::new();
+        metrics.write_prometheus(&mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "# HELP jobs_total Jobs done\n\
+             # TYPE jobs_total counter\n\
+             jobs_total 2\n\
+             # TYPE latency histogram\n\
+             latency_bucket{le=\"1\"} 1\n\
+             latency_bucket{le=\"+Inf\"} 2\n\
+             latency_sum 3.5\n\
+             latency_count 2\n"
+        );
+    }
+
+    #[test]
+    fn atomic_metrics_lose_no_updates() {
+        let metrics = AtomicMetrics::new();
+        register_robot_metrics(&metrics).unwrap();
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        run_robot(&metrics).unwrap();
+                    }
+                });
+            }
+        });
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.get("robot_hardware_errors_total"),
+            Some(&MetricValue::Counter(8 * 1000 * 3))
+        );
+        assert_eq!(
+            snapshot.get("robot_battery_percent"),
+            Some(&MetricValue::Gauge(-1.5 * 5.0 * 8000.0))
+        );
+        match snapshot.get("robot_move_seconds") {
+            Some(MetricValue::Histogram { count, buckets, .. }) => {
+                assert_eq!(*count, 5 * 8000);
+                assert_eq!(buckets[3], (1.0, 4 * 8000));
+            }
+            other => panic!("expected a histogram, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+mod interior_mutability;
+mod metrics;
+mod robot_log;
+mod tracked_cell;
+pub use interior_mutability::*;
+pub use metrics::*;
+pub use robot_log::*;
+pub use tracked_cell::*;
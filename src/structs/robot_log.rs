@@ -0,0 +1,400 @@
+// A structured, bounded log for `Robot`
+// - each entry has a sequence number, a monotonic timestamp, a level, a message and optional key/value fields
+// - entries live in a ring buffer: once `capacity` is reached the oldest entry is dropped for every new one
+// - `Robot` keeps it in a `RefCell`, so logging still only needs `&self`
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::RangeBounds;
+use std::time::{Duration, Instant};
+
+// deriving `PartialOrd` and `Ord` orders variants by declaration, so `Level::Warn > Level::Info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub seq: u64,
+    // time since the log was created, taken from `Instant` so it never goes backwards
+    pub elapsed: Duration,
+    pub level: Level,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+// JSON string escaping, enough for RFC 8259
+fn write_json_str<W: Write + ?Sized>(out: &mut W, s: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+// logfmt values are bare unless they are empty or contain spaces, control chars, `=` or quotes
+// - inside quotes the escapes are the JSON ones, so a value never spans lines or hides a char
+fn write_logfmt_value<W: Write + ?Sized>(out: &mut W, s: &str) -> io::Result<()> {
+    let needs_quotes = s.is_empty()
+        || s.chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '=' || c == '"');
+    if !needs_quotes {
+        return out.write_all(s.as_bytes());
+    }
+    out.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+// logfmt keys cannot be quoted, so anything that would end the key early becomes `_`
+fn write_logfmt_key<W: Write + ?Sized>(out: &mut W, key: &str) -> io::Result<()> {
+    if key.is_empty() {
+        return out.write_all(b"_");
+    }
+    for c in key.chars() {
+        if c.is_whitespace() || c.is_control() || c == '=' || c == '"' {
+            out.write_all(b"_")?;
+        } else {
+            write!(out, "{}", c)?;
+        }
+    }
+    Ok(())
+}
+
+impl LogEntry {
+    // one JSON object per line, e.g. `{"seq":3,"t":0.000120,"level":"warn","msg":"low battery","pct":"9"}`
+    pub fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        write!(
+            out,
+            "{{\"seq\":{},\"t\":{:.6},\"level\":\"{}\",\"msg\":",
+            self.seq,
+            self.elapsed.as_secs_f64(),
+            self.level
+        )?;
+        write_json_str(out, &self.message)?;
+        for (key, value) in &self.fields {
+            out.write_all(b",")?;
+            write_json_str(out, key)?;
+            out.write_all(b":")?;
+            write_json_str(out, value)?;
+        }
+        out.write_all(b"}\n")
+    }
+
+    // e.g. `seq=3 t=0.000120 level=warn msg="low battery" pct=9`
+    pub fn write_logfmt<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        write!(
+            out,
+            "seq={} t={:.6} level={} msg=",
+            self.seq,
+            self.elapsed.as_secs_f64(),
+            self.level
+        )?;
+        write_logfmt_value(out, &self.message)?;
+        for (key, value) in &self.fields {
+            out.write_all(b" ")?;
+            write_logfmt_key(out, key)?;
+            out.write_all(b"=")?;
+            write_logfmt_value(out, value)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{} {:>10.6}s {:<5} {}",
+            self.seq,
+            self.elapsed.as_secs_f64(),
+            self.level,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct RobotLog {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+    next_seq: u64,
+    // only entries pushed out by newer ones, `clear` does not count
+    dropped: u64,
+    start: Instant,
+}
+
+impl RobotLog {
+    // `capacity` of zero would drop every entry, so keep at least one
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        RobotLog {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            next_seq: 0,
+            dropped: 0,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // entries pushed out of the ring buffer so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn record(&mut self, level: Level, message: &str, fields: &[(&str, &str)]) -> u64 {
+        let elapsed = self.start.elapsed();
+        self.record_at(elapsed, level, message, fields)
+    }
+
+    // `elapsed` is passed in rather than read from the clock, so we can log at a known time
+    pub fn record_at(
+        &mut self,
+        elapsed: Duration,
+        level: Level,
+        message: &str,
+        fields: &[(&str, &str)],
+    ) -> u64 {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.entries.push_back(LogEntry {
+            seq,
+            elapsed,
+            level,
+            message: message.to_string(),
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        });
+        seq
+    }
+
+    // oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn at_least(&self, level: Level) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().filter(move |e| e.level >= level)
+    }
+
+    // e.g. `log.within(Duration::from_secs(1)..)` or `log.within(..=Duration::from_millis(500))`
+    pub fn within<R>(&self, range: R) -> impl Iterator<Item = &LogEntry>
+    where
+        R: RangeBounds<Duration>,
+    {
+        self.entries
+            .iter()
+            .filter(move |e| range.contains(&e.elapsed))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// export any selection of entries, e.g. the output of `at_least`, to any `Write`
+pub fn write_json_lines<'a, W, I>(out: &mut W, entries: I) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    I: IntoIterator<Item = &'a LogEntry>,
+{
+    for entry in entries {
+        entry.write_json(out)?;
+    }
+    Ok(())
+}
+
+pub fn write_logfmt<'a, W, I>(out: &mut W, entries: I) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    I: IntoIterator<Item = &'a LogEntry>,
+{
+    for entry in entries {
+        entry.write_logfmt(out)?;
+    }
+    Ok(())
+}
+
+// `&RobotLog` can be used in `for` loops and passed to the writers directly
+impl<'a> IntoIterator for &'a RobotLog {
+    type Item = &'a LogEntry;
+    type IntoIter = std::collections::vec_deque::Iter<'a, LogEntry>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+pub fn use_robot_log() -> io::Result<()> {
+    let mut log = RobotLog::with_capacity(3);
+    let ms = Duration::from_millis;
+    log.record_at(ms(0), Level::Info, "boot", &[]);
+    log.record_at(ms(10), Level::Debug, "sensor read", &[("id", "7")]);
+    log.record_at(ms(20), Level::Warn, "low battery", &[("pct", "9")]);
+    log.record_at(
+        ms(30),
+        Level::Error,
+        "motor \"left\" stalled",
+        &[("rpm", "0")],
+    );
+
+    // "boot" was pushed out of the ring buffer
+    println!("{} entries kept, {} dropped", log.len(), log.dropped());
+
+    write_json_lines(&mut io::stdout(), log.at_least(Level::Error))?;
+    write_logfmt(&mut io::stdout(), &log)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> RobotLog {
+        let mut log = RobotLog::with_capacity(3);
+        let ms = Duration::from_millis;
+        log.record_at(ms(0), Level::Info, "boot", &[]);
+        log.record_at(ms(10), Level::Debug, "sensor read", &[("id", "7")]);
+        log.record_at(ms(20), Level::Warn, "low battery", &[("pct", "9")]);
+        log.record_at(
+            ms(30),
+            Level::Error,
+            "motor \"left\" stalled",
+            &[("rpm", "0")],
+        );
+        log
+    }
+
+    #[test]
+    fn ring_buffer_drops_the_oldest() {
+        let log = sample();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.capacity(), 3);
+        assert_eq!(log.dropped(), 1);
+        assert_eq!(log.iter().next().unwrap().seq, 1);
+        assert_eq!((&log).into_iter().last().unwrap().seq, 3);
+        assert_eq!(RobotLog::with_capacity(0).capacity(), 1);
+    }
+
+    #[test]
+    fn logfmt_escapes_control_chars_and_keys() {
+        let mut log = RobotLog::with_capacity(1);
+        log.record_at(
+            Duration::ZERO,
+            Level::Info,
+            "a\rb\tc\u{7}",
+            &[("bad key", "x"), ("k=v", "\u{1b}[31m"), ("", "y")],
+        );
+        let mut logfmt = Vec::new();
+        write_logfmt(&mut logfmt, &log).unwrap();
+        assert_eq!(
+            String::from_utf8(logfmt).unwrap(),
+            "seq=0 t=0.000000 level=info msg=\"a\\rb\\tc\\u0007\" bad_key=x k_v=\"\\u001b[31m\" _=y\n"
+        );
+    }
+
+    #[test]
+    fn clear_is_not_dropping() {
+        let mut log = sample();
+        log.clear();
+        assert!(log.is_empty());
+        assert_eq!(log.dropped(), 1);
+        log.record_at(Duration::from_millis(40), Level::Info, "again", &[]);
+        assert_eq!(log.iter().next().unwrap().seq, 4);
+        assert_eq!(log.dropped(), 1);
+    }
+
+    #[test]
+    fn filters() {
+        let log = sample();
+        let ms = Duration::from_millis;
+        assert_eq!(log.at_least(Level::Warn).count(), 2);
+        assert_eq!(log.at_least(Level::Debug).count(), 3);
+        assert_eq!(log.within(ms(5)..ms(25)).count(), 2);
+        assert_eq!(log.within(ms(30)..).count(), 1);
+        assert_eq!(log.within(..=ms(10)).count(), 1);
+    }
+
+    #[test]
+    fn json_and_logfmt_escape_quotes() {
+        let log = sample();
+        let mut json = Vec::new();
+        write_json_lines(&mut json, log.at_least(Level::Error)).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"seq\":3,\"t\":0.030000,\"level\":\"error\",\"msg\":\"motor \\\"left\\\" stalled\",\"rpm\":\"0\"}\n"
+        );
+        let mut logfmt = Vec::new();
+        write_logfmt(
+            &mut logfmt,
+            log.within(Duration::from_millis(20)..=Duration::from_millis(20)),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(logfmt).unwrap(),
+            "seq=2 t=0.020000 level=warn msg=\"low battery\" pct=9\n"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+// A `RefCell` that remembers where each live borrow was taken
+// - `RefCell::borrow_mut` panics with "already borrowed" but does not say by whom
+// - `#[track_caller]` makes `Location::caller()` return the caller's file, line and column instead of ours
+// - every guard records that location when it is created and removes it when dropped
+// - a conflicting borrow then lists every outstanding borrow, e.g.
+//   "already borrowed at src/main.rs:12:5: 1 outstanding borrow(s): shared at src/main.rs:10:17"
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowKind {
+    Shared,
+    Mutable,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BorrowSite {
+    id: u64,
+    pub kind: BorrowKind,
+    pub location: &'static Location<'static>,
+}
+
+impl fmt::Display for BorrowSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            BorrowKind::Shared => "shared",
+            BorrowKind::Mutable => "mutable",
+        };
+        write!(f, "{} at {}", kind, self.location)
+    }
+}
+
+// returned by `try_borrow` and `try_borrow_mut`, and printed by the panics of `borrow` and `borrow_mut`
+#[derive(Debug, Clone)]
+pub struct TrackedBorrowError {
+    pub attempted: BorrowKind,
+    pub at: &'static Location<'static>,
+    pub holders: Vec<BorrowSite>,
+}
+
+impl fmt::Display for TrackedBorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = match self.attempted {
+            BorrowKind::Shared => "already mutably borrowed",
+            BorrowKind::Mutable => "already borrowed",
+        };
+        write!(
+            f,
+            "{} at {}: {} outstanding borrow(s): ",
+            state,
+            self.at,
+            self.holders.len()
+        )?;
+        for (i, site) in self.holders.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", site)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TrackedBorrowError {}
+
+pub struct TrackedRefCell<T: ?Sized> {
+    // bookkeeping lives in `Cell`/`RefCell` too, so every method can take `&self`
+    // - `sites` is only borrowed inside our own methods, never across a call into user code
+    next_id: Cell<u64>,
+    sites: RefCell<Vec<BorrowSite>>,
+    value: RefCell<T>,
+}
+
+pub struct TrackedRef<'b, T: ?Sized> {
+    value: Ref<'b, T>,
+    sites: &'b RefCell<Vec<BorrowSite>>,
+    id: u64,
+}
+
+pub struct TrackedRefMut<'b, T: ?Sized> {
+    value: RefMut<'b, T>,
+    sites: &'b RefCell<Vec<BorrowSite>>,
+    id: u64,
+}
+
+impl<T> TrackedRefCell<T> {
+    pub fn new(value: T) -> Self {
+        TrackedRefCell {
+            next_id: Cell::new(0),
+            sites: RefCell::new(Vec::new()),
+            value: RefCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+}
+
+impl<T: ?Sized> TrackedRefCell<T> {
+    fn track(&self, kind: BorrowKind, location: &'static Location<'static>) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.sites
+            .borrow_mut()
+            .push(BorrowSite { id, kind, location });
+        id
+    }
+
+    fn conflict(
+        &self,
+        attempted: BorrowKind,
+        at: &'static Location<'static>,
+    ) -> TrackedBorrowError {
+        TrackedBorrowError {
+            attempted,
+            at,
+            holders: self.sites.borrow().clone(),
+        }
+    }
+
+    // every borrow that is alive right now, oldest first
+    pub fn outstanding(&self) -> Vec<BorrowSite> {
+        self.sites.borrow().clone()
+    }
+
+    #[track_caller]
+    pub fn try_borrow(&self) -> Result<TrackedRef<'_, T>, TrackedBorrowError> {
+        let location = Location::caller();
+        match self.value.try_borrow() {
+            Ok(value) => Ok(TrackedRef {
+                value,
+                sites: &self.sites,
+                id: self.track(BorrowKind::Shared, location),
+            }),
+            Err(_) => Err(self.conflict(BorrowKind::Shared, location)),
+        }
+    }
+
+    #[track_caller]
+    pub fn try_borrow_mut(&self) -> Result<TrackedRefMut<'_, T>, TrackedBorrowError> {
+        let location = Location::caller();
+        match self.value.try_borrow_mut() {
+            Ok(value) => Ok(TrackedRefMut {
+                value,
+                sites: &self.sites,
+                id: self.track(BorrowKind::Mutable, location),
+            }),
+            Err(_) => Err(self.conflict(BorrowKind::Mutable, location)),
+        }
+    }
+
+    // with `#[track_caller]` the panic is reported at the caller's line, not inside this function
+    #[track_caller]
+    pub fn borrow(&self) -> TrackedRef<'_, T> {
+        match self.try_borrow() {
+            Ok(r) => r,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[track_caller]
+    pub fn borrow_mut(&self) -> TrackedRefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(r) => r,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // `&mut self` proves there are no other borrows, so nothing to track
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+fn untrack(sites: &RefCell<Vec<BorrowSite>>, id: u64) {
+    sites.borrow_mut().retain(|site| site.id != id);
+}
+
+impl<T: ?Sized> Drop for TrackedRef<'_, T> {
+    fn drop(&mut self) {
+        untrack(self.sites, self.id);
+    }
+}
+
+impl<T: ?Sized> Drop for TrackedRefMut<'_, T> {
+    fn drop(&mut self) {
+        untrack(self.sites, self.id);
+    }
+}
+
+impl<T: ?Sized> Deref for TrackedRef<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: ?Sized> Deref for TrackedRefMut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: ?Sized> DerefMut for TrackedRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedRefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+// same output as `RefCell`'s `Debug`, so `{:?}` on `Robot` looks the same either way
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedRefCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Default> Default for TrackedRefCell<T> {
+    fn default() -> Self {
+        TrackedRefCell::new(T::default())
+    }
+}
+
+pub fn use_tracked_ref_cell() {
+    let log = TrackedRefCell::new(vec!["Initialized".to_string()]);
+
+    let first = log.borrow();
+    let second = log.borrow();
+
+    // both shared borrows are reported, with the lines they were taken on
+    let err = log.try_borrow_mut().unwrap_err();
+    assert_eq!(err.holders.len(), 2);
+    println!("{}", err);
+
+    drop(first);
+    drop(second);
+
+    log.borrow_mut().push("found an hardware error".to_string());
+    let writer = log.borrow_mut();
+    let err = log.try_borrow().unwrap_err();
+    println!("{}", err);
+    drop(writer);
+
+    // `borrow` and `borrow_mut` panic with the same message
+    // let _reader = log.borrow();
+    // log.borrow_mut();
+    println!("{:?}", log);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicts_name_every_holder() {
+        let log = TrackedRefCell::new(vec!["Initialized".to_string()]);
+
+        let (first, first_line) = (log.borrow(), line!());
+        let second = log.borrow();
+        assert_eq!(log.outstanding().len(), 2);
+
+        let err = log.try_borrow_mut().unwrap_err();
+        assert_eq!(err.attempted, BorrowKind::Mutable);
+        assert_eq!(err.holders.len(), 2);
+        assert_eq!(err.holders[0].location.line(), first_line);
+        assert_eq!(err.holders[0].location.file(), file!());
+
+        drop(first);
+        drop(second);
+        assert!(log.outstanding().is_empty());
+
+        log.borrow_mut().push("found an hardware error".to_string());
+        let writer = log.borrow_mut();
+        let err = log.try_borrow().unwrap_err();
+        assert_eq!(err.attempted, BorrowKind::Shared);
+        assert_eq!(err.holders.len(), 1);
+        assert_eq!(err.holders[0].kind, BorrowKind::Mutable);
+        drop(writer);
+
+        assert_eq!(log.into_inner().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 outstanding borrow(s): shared at")]
+    fn borrow_mut_panics_with_the_holders() {
+        let log = TrackedRefCell::new(0);
+        let _reader = log.borrow();
+        log.borrow_mut();
+    }
+}
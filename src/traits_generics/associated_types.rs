@@ -1,6 +1,7 @@
 // we are defining a function to print out all values produced by an iterator
 // - to use `{:?}` on value produced by iterator, value must implement `Debug`
 // -  in trait `Iterator`, the associated type is named `Item`
+use super::{Row, TableRenderer};
 use std::fmt::Debug;
 
 fn dump<I>(iter: I)
@@ -33,3 +34,13 @@ fn dump3(iter: &mut dyn Iterator<Item = String>) {
         println!("{}: {:?}", index, value);
     }
 }
+
+// all three print one `index: {:?}` line per item, `dump_table` prints rows as an aligned table
+// - the item only has to be a `Row`, see `tables.rs` for the renderer and its other formats
+pub fn dump_table<I>(iter: I)
+where
+    I: IntoIterator,
+    I::Item: Row,
+{
+    TableRenderer::new().numbered(true).print(iter).unwrap();
+}
@@ -1,6 +1,6 @@
+use super::Num;
 use std::fmt::{Debug, Display};
 use std::iter;
-use std::ops::Add;
 use std::vec::IntoIter;
 
 // Return type coulc get messy when we use consolidate type
@@ -27,10 +27,7 @@ fn cyclical_zip3(v: Vec<u8>, u: Vec<u8>) -> impl Iterator<Item = u8> {
     Box::new(res)
 }
 
-trait Shape {
-    fn new() -> Self;
-    fn area(&self) -> f64;
-}
+// `trait Shape { fn new() -> Self; fn area(&self) -> f64; }` and its shapes are in `shapes.rs`
 
 // - below does not work because the return type has to be determined at compile time, not run time
 // fn make_shape(shape: &str) -> impl Shape {
@@ -41,6 +38,7 @@ trait Shape {
 //         _ => Square::new(),
 //     }
 // }
+// - `shapes::make_shape` returns `Box<dyn Shape>` instead, which works for any type chosen at run time
 
 // `impl Trait` can also be used in fucntions that take generic arguemnts
 
@@ -58,18 +56,10 @@ trait Greet {
     fn greet(&self) -> String;
 }
 
-trait Float {
-    const ZERO: Self;
-    const ONE: Self;
-}
-
-// impl for certain type
-impl Float for f32 {
-    const ZERO: Self = 0.0;
-    const ONE: Self = 1.0;
-}
+// `Zero { const ZERO: Self; }` and `One { const ONE: Self; }` in `num.rs` are implemented for every primitive number
+// - and `Num` bundles them with the arithmetic operators
 
 // write generic code with those consts
-fn add_one<T: Float + Add<Output = T>>(value: T) -> T {
+fn add_one<T: Num>(value: T) -> T {
     value + T::ONE
 }
@@ -1,8 +1,23 @@
 mod associated_types;
 mod impl_trait;
+mod num;
 mod reverse_engineer_bounds;
+mod rope;
+mod shapes;
+mod string_sets;
 mod subtraits;
+mod tables;
 mod type_associated_functions;
 mod use_traits;
+mod world;
+pub use associated_types::*;
+pub use num::*;
 pub use reverse_engineer_bounds::*;
+pub use rope::*;
+pub use shapes::*;
+pub use string_sets::*;
+pub use subtraits::*;
+pub use tables::*;
+pub use type_associated_functions::*;
 pub use use_traits::*;
+pub use world::*;
@@ -0,0 +1,306 @@
+// A small numeric tower, grown from the `Float` trait with `ZERO` and `ONE` in `impl_trait.rs`
+// and the bounds `dot_g` collected in `reverse_engineer_bounds.rs`
+// - `Zero` and `One`: the two constants generic code keeps needing, `T::default()` only stood in for zero
+// - `Num`: copyable, ordered, closed under `+ - * / %`, and summable, for every primitive number
+// - `Signed`: adds negation and `abs`, for signed integers and floats
+// - `Float`: adds `sqrt`, `EPSILON` and friends, for `f32` and `f64`
+//
+// The crate has no big-integer or decimal type; one would join by implementing these traits too,
+// `Num` has no methods of its own, so it would only need `Zero`, `One` and the operators
+use std::fmt::Debug;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+pub trait Zero {
+    const ZERO: Self;
+}
+
+pub trait One {
+    const ONE: Self;
+}
+
+pub trait Num:
+    Copy
+    + Debug
+    + PartialOrd
+    + Zero
+    + One
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Sum
+    + Product
+{
+    fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+}
+
+pub trait Signed: Num + Neg<Output = Self> {
+    fn abs(self) -> Self {
+        if self.is_negative() {
+            -self
+        } else {
+            self
+        }
+    }
+
+    // -1, 0 or 1, and NaN for a NaN
+    fn signum(self) -> Self;
+
+    fn is_negative(self) -> bool {
+        self < Self::ZERO
+    }
+}
+
+pub trait Float: Signed {
+    const EPSILON: Self;
+    const INFINITY: Self;
+    const NAN: Self;
+
+    fn sqrt(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn floor(self) -> Self;
+    fn is_nan(self) -> bool;
+    fn is_finite(self) -> bool;
+
+    // equal up to a few units of rounding, relative to the larger of the two numbers
+    // - `0.1 + 0.2 == 0.3` is false, `(0.1 + 0.2).approx_eq(0.3)` is true
+    // - `1e-20` and `1e-10` are not equal, however small both are; only zero equals just zero
+    fn approx_eq(self, other: Self) -> bool {
+        let scale = if self.abs() > other.abs() {
+            self.abs()
+        } else {
+            other.abs()
+        };
+        let four = Self::ONE + Self::ONE + Self::ONE + Self::ONE;
+        self == other || (self - other).abs() <= Self::EPSILON * four * scale
+    }
+}
+
+macro_rules! impl_num {
+    ($zero:expr, $one:expr; $($t:ty),*) => {
+        $(
+            impl Zero for $t {
+                const ZERO: Self = $zero;
+            }
+            impl One for $t {
+                const ONE: Self = $one;
+            }
+            impl Num for $t {}
+        )*
+    };
+}
+
+impl_num!(0, 1; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_num!(0.0, 1.0; f32, f64);
+
+// `abs` and `signum` forward to the inherent methods, which the compiler picks over the trait's
+macro_rules! impl_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Signed for $t {
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_signed!(i8, i16, i32, i64, i128, isize, f32, f64);
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                const EPSILON: Self = <$t>::EPSILON;
+                const INFINITY: Self = <$t>::INFINITY;
+                const NAN: Self = <$t>::NAN;
+
+                fn sqrt(self) -> Self {
+                    <$t>::sqrt(self)
+                }
+                fn powi(self, n: i32) -> Self {
+                    <$t>::powi(self, n)
+                }
+                fn floor(self) -> Self {
+                    <$t>::floor(self)
+                }
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+// 1, 2, ..., n for any `Num`
+// - `1..=n` needs the unstable `Step` trait for a generic `T`, so we count with `successors`
+// - `i + 1` is only computed while `i < n`, so `one_to(u8::MAX)` does not overflow
+// - a float stops counting where `i + 1 == i`, 2^24 for `f32` and 2^53 for `f64`
+pub fn one_to<T: Num>(n: T) -> impl Iterator<Item = T> {
+    let first = (T::ONE <= n).then_some(T::ONE);
+    std::iter::successors(first, move |&i| {
+        if i >= n {
+            return None;
+        }
+        let next = i + T::ONE;
+        (next > i).then_some(next)
+    })
+    .take_while(move |&i| i <= n)
+}
+
+// 1 + 2 + ... + n
+pub fn triangle<T: Num>(n: T) -> T {
+    one_to(n).sum()
+}
+
+// 1 * 2 * ... * n, and 1 for 0
+pub fn factorial<T: Num>(n: T) -> T {
+    one_to(n).product()
+}
+
+// Euclid's algorithm, for integers; `%` on floats works too but is rarely what you want
+pub fn gcd<T: Num>(a: T, b: T) -> T {
+    if b.is_zero() {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// exponentiation by squaring, O(log exp) multiplications
+pub fn power<T: Num>(base: T, exp: u32) -> T {
+    match exp {
+        0 => T::ONE,
+        _ if exp.is_multiple_of(2) => {
+            let half = power(base, exp / 2);
+            half * half
+        }
+        _ => base * power(base, exp - 1),
+    }
+}
+
+// the Euclidean length, only for floats since it needs `sqrt`
+pub fn norm<T: Float>(v: &[T]) -> T {
+    v.iter().map(|&x| x * x).sum::<T>().sqrt()
+}
+
+// Newton's method, to show that `Float` is enough to write numeric algorithms generically
+// - the first guess can be far off, each step at worst halves the distance, so even
+//   `f64::MAX` converges in well under `MAX_STEPS`; the cap only stops a guess that flips
+//   between two neighbouring values without ever being `approx_eq`
+pub fn newton_sqrt<T: Float>(x: T) -> T {
+    const MAX_STEPS: usize = 2_000;
+    if x.is_zero() || x.is_nan() || x.is_negative() {
+        return if x.is_zero() { x } else { T::NAN };
+    }
+    // `+inf`, where `x / guess` stays infinite and the next guess is NaN
+    if !x.is_finite() {
+        return x;
+    }
+    let two = T::ONE + T::ONE;
+    let mut guess = if x > T::ONE { x / two } else { T::ONE };
+    for _ in 0..MAX_STEPS {
+        let next = (guess + x / guess) / two;
+        if next.approx_eq(guess) {
+            return next;
+        }
+        guess = next;
+    }
+    guess
+}
+
+pub fn use_num() {
+    // the same code for every primitive
+    println!("triangle: {} {}", triangle(100u32), triangle(4.0));
+    println!(
+        "gcd(1071, 462) = {}, 3^40 = {}",
+        gcd(1071, 462),
+        power(3u64, 40)
+    );
+    println!(
+        "norm = {}, sqrt(2) ~ {}",
+        norm(&[3.0f32, 4.0]),
+        newton_sqrt(2.0f64)
+    );
+    assert!((0.1 + 0.2).approx_eq(0.3));
+
+    // `u8` overflows at 6!, in debug builds the `product` panics
+    // factorial(6u8);
+    println!("20! = {}, 34! = {}", factorial(20u64), factorial(34u128));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangle_and_factorial() {
+        assert_eq!(triangle(100u8 as u32), 5050);
+        assert_eq!(triangle(4.0), 10.0);
+        assert_eq!(factorial(0u64), 1);
+        assert_eq!(factorial(20u64), 2_432_902_008_176_640_000);
+        assert_eq!(factorial(5i8), 120);
+        assert_eq!(
+            factorial(34u128),
+            295_232_799_039_604_140_847_618_609_643_520_000_000
+        );
+        assert_eq!(factorial(5.0f32), 120.0);
+    }
+
+    #[test]
+    fn gcd_and_power() {
+        assert_eq!(gcd(1071, 462), 21);
+        assert_eq!(gcd(48u8, 180), 12);
+        assert_eq!(power(3u64, 40), 12_157_665_459_056_928_801);
+        assert_eq!(power(2.0, 10), 1024.0);
+        assert_eq!(power(-2i32, 3), -8);
+    }
+
+    #[test]
+    fn signed_and_float() {
+        assert_eq!(Signed::abs(-7i16), 7);
+        assert_eq!(Signed::signum(-0.5f64), -1.0);
+        assert!((-3i64).is_negative() && !0i64.is_negative());
+
+        assert_eq!(norm(&[3.0f32, 4.0]), 5.0);
+        assert!((0.1 + 0.2).approx_eq(0.3));
+        assert!(!(0.1f32).approx_eq(0.1001));
+        assert!(newton_sqrt(2.0f64).approx_eq(std::f64::consts::SQRT_2));
+        assert!(newton_sqrt(1e-6f32).approx_eq(1e-3));
+        assert!(Float::is_nan(newton_sqrt(-1.0f64)));
+        assert!(!1e-20f64.approx_eq(1e-10));
+        assert!(!0.0f32.approx_eq(1e-30));
+        assert!(newton_sqrt(1e-20f32).approx_eq(1e-10));
+        assert!(newton_sqrt(1e-40f64).approx_eq(1e-20));
+        assert!(newton_sqrt(f64::MIN_POSITIVE).approx_eq(f64::MIN_POSITIVE.sqrt()));
+        assert_eq!(newton_sqrt(f64::INFINITY), f64::INFINITY);
+        assert!(Float::is_nan(newton_sqrt(f32::NEG_INFINITY)));
+        assert!(newton_sqrt(f64::MAX).approx_eq(f64::MAX.sqrt()));
+    }
+
+    #[test]
+    fn one_to_stops_at_the_top_of_the_type() {
+        assert_eq!(one_to(u8::MAX).count(), 255);
+        assert_eq!(one_to(i8::MAX).last(), Some(127));
+        assert_eq!(triangle(255u32), 32640);
+        assert_eq!(one_to(0u8).count(), 0);
+        assert_eq!(one_to(-3i32).count(), 0);
+        assert_eq!(one_to(2.5).collect::<Vec<_>>(), [1.0, 2.0]);
+        assert_eq!(one_to(f64::NAN).count(), 0);
+        // past 2^24 an `f32` cannot count by one any more
+        assert_eq!(one_to(1e10f32).last(), Some(16777216.0));
+    }
+}
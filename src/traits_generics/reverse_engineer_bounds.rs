@@ -24,9 +24,14 @@ fn dot(v1: &[i64], v2: &[i64]) -> i64 {
 // - step4 we see error that
 // - cannot move out indexed content from slice, triggered by `v1[i]`, `v2[i]`
 // - we add `Copy` to N
-use std::ops::{Add, Mul};
-fn dot_g<N: Add<Output = N> + Mul<Output = N> + Default + Copy>(v1: &[N], v2: &[N]) -> N {
-    let mut total: N = N::default();
+
+// - step5 the bounds pile up in every numeric function we write
+// - `Num` in `num.rs` names the whole pile once: `Copy`, the operators with `Output = N`, and more
+// - `N::ZERO` says what we mean, `N::default()` only happened to be zero
+// - the original signature was `dot_g<N: Add<Output = N> + Mul<Output = N> + Default + Copy>`
+use super::Num;
+fn dot_g<N: Num>(v1: &[N], v2: &[N]) -> N {
+    let mut total = N::ZERO;
     for i in 0..v1.len() {
         total = total + v1[i] * v2[i];
     }
@@ -36,4 +41,5 @@ fn dot_g<N: Add<Output = N> + Mul<Output = N> + Default + Copy>(v1: &[N], v2: &[
 pub fn use_dot_g() {
     assert_eq!(dot_g(&[1, 2, 3, 4], &[1, 1, 1, 1]), 10);
     assert_eq!(dot_g(&[53.0, 7.0], &[1.0, 5.0]), 88.0);
+    assert_eq!(dot_g(&[3u8, 4], &[5, 6]), 39);
 }
@@ -0,0 +1,677 @@
+// A rope: text kept as a balanced binary tree of string chunks
+// - leaves hold up to `MAX_LEAF` bytes, branches cache the byte, char and newline counts below them
+// - insert, remove, split and concatenation touch one root-to-leaf path and rebalance along it, O(log n)
+// - nodes are shared through `Rc` and never mutated, so `clone` is O(1) and edits copy only the path they change
+// - positions are char indexes, like `str::chars().nth(..)` but without the linear scan
+//
+// Balancing is AVL-style on heights: the two children of a branch differ in height by at most one
+use super::{MegaSplicable, Splicable};
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use std::time::Instant;
+
+const MAX_LEAF: usize = 512;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct TextInfo {
+    bytes: usize,
+    chars: usize,
+    newlines: usize,
+}
+
+impl TextInfo {
+    fn of(text: &str) -> Self {
+        TextInfo {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+
+    fn plus(self, other: TextInfo) -> TextInfo {
+        TextInfo {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Leaf {
+        text: String,
+        info: TextInfo,
+    },
+    Branch {
+        left: Rc<Node>,
+        right: Rc<Node>,
+        info: TextInfo,
+        height: usize,
+    },
+}
+
+impl Node {
+    fn leaf(text: &str) -> Rc<Node> {
+        Rc::new(Node::Leaf {
+            text: text.to_string(),
+            info: TextInfo::of(text),
+        })
+    }
+
+    fn info(&self) -> TextInfo {
+        match self {
+            Node::Leaf { info, .. } | Node::Branch { info, .. } => *info,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.info().bytes == 0
+    }
+
+    // only called on nodes taller than some other node, which are never leaves
+    fn children(&self) -> (Rc<Node>, Rc<Node>) {
+        match self {
+            Node::Branch { left, right, .. } => (left.clone(), right.clone()),
+            Node::Leaf { .. } => unreachable!("a leaf has no children"),
+        }
+    }
+
+    fn is_balanced(&self) -> bool {
+        match self {
+            Node::Leaf { text, .. } => text.len() <= MAX_LEAF,
+            Node::Branch {
+                left,
+                right,
+                info,
+                height,
+            } => {
+                left.height().abs_diff(right.height()) <= 1
+                    && *height == 1 + left.height().max(right.height())
+                    && *info == left.info().plus(right.info())
+                    && left.is_balanced()
+                    && right.is_balanced()
+            }
+        }
+    }
+}
+
+fn branch(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    Rc::new(Node::Branch {
+        info: left.info().plus(right.info()),
+        height: 1 + left.height().max(right.height()),
+        left,
+        right,
+    })
+}
+
+// `left` and `right` are balanced and differ in height by at most two
+fn rebalance(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = left.children();
+        if ll.height() >= lr.height() {
+            branch(ll, branch(lr, right))
+        } else {
+            let (lrl, lrr) = lr.children();
+            branch(branch(ll, lrl), branch(lrr, right))
+        }
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = right.children();
+        if rr.height() >= rl.height() {
+            branch(branch(left, rl), rr)
+        } else {
+            let (rll, rlr) = rl.children();
+            branch(branch(left, rll), branch(rlr, rr))
+        }
+    } else {
+        branch(left, right)
+    }
+}
+
+// concatenation: walk down the spine of the taller tree to a subtree of the other's height, O(height difference)
+fn join(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    if left.is_empty() {
+        return right;
+    }
+    if right.is_empty() {
+        return left;
+    }
+    // keep small edits from fragmenting the text into tiny leaves
+    if let (Node::Leaf { text: a, .. }, Node::Leaf { text: b, .. }) = (&*left, &*right) {
+        if a.len() + b.len() <= MAX_LEAF {
+            return Node::leaf(&[a.as_str(), b.as_str()].concat());
+        }
+    }
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = left.children();
+        rebalance(ll, join(lr, right))
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = right.children();
+        rebalance(join(left, rl), rr)
+    } else {
+        branch(left, right)
+    }
+}
+
+// `at` is a byte offset on a char boundary
+fn split(node: &Rc<Node>, at: usize) -> (Rc<Node>, Rc<Node>) {
+    if at == 0 {
+        return (Node::leaf(""), node.clone());
+    }
+    if at >= node.info().bytes {
+        return (node.clone(), Node::leaf(""));
+    }
+    match &**node {
+        Node::Leaf { text, .. } => (Node::leaf(&text[..at]), Node::leaf(&text[at..])),
+        Node::Branch { left, right, .. } => {
+            let mid = left.info().bytes;
+            if at <= mid {
+                let (a, b) = split(left, at);
+                (a, join(b, right.clone()))
+            } else {
+                let (a, b) = split(right, at - mid);
+                (join(left.clone(), a), b)
+            }
+        }
+    }
+}
+
+// a balanced tree over `leaves`, by halving
+fn build(leaves: &[Rc<Node>]) -> Rc<Node> {
+    match leaves {
+        [] => Node::leaf(""),
+        [leaf] => leaf.clone(),
+        _ => {
+            let (left, right) = leaves.split_at(leaves.len() / 2);
+            branch(build(left), build(right))
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Rope {
+    root: Rc<Node>,
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Rope {
+            root: Node::leaf(""),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.root.info().bytes
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.root.info().chars
+    }
+
+    // one more than the number of `\n`, so "" has one line and "a\n" has two, the second empty
+    pub fn len_lines(&self) -> usize {
+        self.root.info().newlines + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+    }
+
+    pub fn height(&self) -> usize {
+        self.root.height()
+    }
+
+    fn check_char_index(&self, char_idx: usize) {
+        assert!(
+            char_idx <= self.len_chars(),
+            "char index {} out of bounds for a rope of {} chars",
+            char_idx,
+            self.len_chars()
+        );
+    }
+
+    // the leaf holding char `char_idx`, with the char's index inside the leaf and the leaf's start
+    fn leaf_at(&self, mut char_idx: usize) -> (&str, usize, TextInfo) {
+        let mut node = &*self.root;
+        let mut before = TextInfo::default();
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return (text, char_idx, before),
+                Node::Branch { left, right, .. } => {
+                    let l = left.info();
+                    if char_idx < l.chars {
+                        node = left;
+                    } else {
+                        char_idx -= l.chars;
+                        before = before.plus(l);
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        self.check_char_index(char_idx);
+        let (text, i, before) = self.leaf_at(char_idx);
+        before.bytes + text.char_indices().nth(i).map_or(text.len(), |(b, _)| b)
+    }
+
+    pub fn char(&self, char_idx: usize) -> char {
+        assert!(
+            char_idx < self.len_chars(),
+            "char index {} out of bounds for a rope of {} chars",
+            char_idx,
+            self.len_chars()
+        );
+        let (text, i, _) = self.leaf_at(char_idx);
+        text.chars().nth(i).unwrap()
+    }
+
+    // the line that char `char_idx` is on, a `\n` belongs to the line it ends
+    pub fn char_to_line(&self, char_idx: usize) -> usize {
+        self.check_char_index(char_idx);
+        let (text, i, before) = self.leaf_at(char_idx);
+        before.newlines + text.chars().take(i).filter(|&c| c == '\n').count()
+    }
+
+    // the char index where line `line` starts
+    pub fn line_to_char(&self, line: usize) -> usize {
+        assert!(
+            line < self.len_lines(),
+            "line {} out of bounds for a rope of {} lines",
+            line,
+            self.len_lines()
+        );
+        if line == 0 {
+            return 0;
+        }
+        // find the `line`-th newline, the line starts right after it
+        let mut node = &*self.root;
+        let mut newlines = line;
+        let mut chars = 0;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => {
+                    let (i, _) = text.match_indices('\n').nth(newlines - 1).unwrap();
+                    return chars + text[..i].chars().count() + 1;
+                }
+                Node::Branch { left, right, .. } => {
+                    let l = left.info();
+                    if newlines <= l.newlines {
+                        node = left;
+                    } else {
+                        newlines -= l.newlines;
+                        chars += l.chars;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    // line `line` including its `\n`, if it has one
+    pub fn line(&self, line: usize) -> Rope {
+        let start = self.line_to_char(line);
+        let end = if line + 1 < self.len_lines() {
+            self.line_to_char(line + 1)
+        } else {
+            self.len_chars()
+        };
+        self.slice(start..end)
+    }
+
+    pub fn append(&mut self, other: Rope) {
+        self.root = join(self.root.clone(), other.root);
+    }
+
+    // keeps `[0, char_idx)` and returns the rest, like `String::split_off`
+    pub fn split_off(&mut self, char_idx: usize) -> Rope {
+        let at = self.char_to_byte(char_idx);
+        let (left, right) = split(&self.root, at);
+        self.root = left;
+        Rope { root: right }
+    }
+
+    pub fn insert(&mut self, char_idx: usize, text: &str) {
+        let right = self.split_off(char_idx);
+        self.append(Rope::from(text));
+        self.append(right);
+    }
+
+    pub fn remove(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end, "range {:?} is reversed", range);
+        let right = self.split_off(range.end);
+        self.split_off(range.start);
+        self.append(right);
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Rope {
+        assert!(range.start <= range.end, "range {:?} is reversed", range);
+        let (_, rest) = split(&self.root, self.char_to_byte(range.start));
+        let end = self.char_to_byte(range.end) - self.char_to_byte(range.start);
+        let (middle, _) = split(&rest, end);
+        Rope { root: middle }
+    }
+
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: vec![&*self.root],
+        }
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+
+    // each line as its own rope, sharing nodes with this one
+    pub fn lines(&self) -> impl Iterator<Item = Rope> + '_ {
+        (0..self.len_lines()).map(|i| self.line(i))
+    }
+
+    fn is_balanced(&self) -> bool {
+        self.root.is_balanced()
+    }
+}
+
+// leaves left to right, depth-first with an explicit stack
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while let Some(node) = self.stack.pop() {
+            match node {
+                Node::Leaf { text, .. } if !text.is_empty() => return Some(text),
+                Node::Leaf { .. } => {}
+                Node::Branch { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Rope::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut leaves = vec![];
+        let mut start = 0;
+        while start < text.len() {
+            let mut end = (start + MAX_LEAF).min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            leaves.push(Node::leaf(&text[start..end]));
+            start = end;
+        }
+        Rope {
+            root: build(&leaves),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        Rope::from(text.as_str())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Rope {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut rope = Rope::new();
+        for chunk in iter {
+            rope.append(Rope::from(chunk));
+        }
+        rope
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rope({:?})", self.to_string())
+    }
+}
+
+// the same text, however it is chunked
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len_bytes() == other.len_bytes() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len_bytes() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+// both ropes stay valid and share their nodes with the result
+impl Splicable for Rope {
+    fn splice(&self, other: &Self) -> Self {
+        Rope {
+            root: join(self.root.clone(), other.root.clone()),
+        }
+    }
+}
+
+impl MegaSplicable for Rope {
+    fn splice(&self, other: &dyn MegaSplicable) -> Box<dyn MegaSplicable> {
+        let other: Rope = other.text_chunks().collect();
+        Box::new(Splicable::splice(self, &other))
+    }
+
+    fn text_chunks(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.chunks())
+    }
+}
+
+fn xorshift(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}
+
+// the same random inserts and removes on a `String` and a `Rope`
+// - the text is ASCII so char and byte offsets agree for `String::insert_str`
+// - `String` moves the whole tail on every edit, the rope rebuilds one path
+// - with `--release`, on 10 MB the rope is about 15 times faster, at 1 MB about twice;
+//   in a debug build its bookkeeping costs more than the `String`'s `memmove`
+pub fn bench_rope(len: usize, edits: usize) {
+    let text = "the quick brown fox jumps over the lazy dog\n"
+        .chars()
+        .cycle()
+        .take(len)
+        .collect::<String>();
+    let positions = {
+        let mut next = xorshift(0x2545_f491_4f6c_dd1d);
+        (0..edits).map(|_| next() as usize).collect::<Vec<_>>()
+    };
+
+    let start = Instant::now();
+    let mut string = text.clone();
+    for (i, &p) in positions.iter().enumerate() {
+        if i % 2 == 0 {
+            string.insert_str(p % (string.len() + 1), "<edit>");
+        } else {
+            let at = p % (string.len() - 3);
+            string.replace_range(at..at + 3, "");
+        }
+    }
+    let string_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut rope = Rope::from(text.as_str());
+    for (i, &p) in positions.iter().enumerate() {
+        if i % 2 == 0 {
+            rope.insert(p % (rope.len_chars() + 1), "<edit>");
+        } else {
+            let at = p % (rope.len_chars() - 3);
+            rope.remove(at..at + 3);
+        }
+    }
+    let rope_time = start.elapsed();
+
+    assert!(rope == string.as_str());
+    println!(
+        "{} edits on {} bytes: String {:?}, Rope {:?} (height {})",
+        edits,
+        len,
+        string_time,
+        rope_time,
+        rope.height()
+    );
+}
+
+pub fn use_rope() {
+    let mut rope = Rope::from("hello world");
+    rope.insert(5, ",");
+    rope.insert(rope.len_chars(), "!");
+    assert_eq!(rope, "hello, world!");
+    let tail = rope.split_off(7);
+    println!("{:?} + {:?}", rope.to_string(), tail.to_string());
+
+    // char indexes, not bytes
+    let text = Rope::from("naïve 日本語\nsecond line\n\nlast");
+    println!(
+        "{} chars in {} bytes, chars 6..9 are {}",
+        text.len_chars(),
+        text.len_bytes(),
+        text.slice(6..9)
+    );
+    for (i, line) in text.lines().enumerate() {
+        println!("{}: {:?}", i, line.to_string());
+    }
+
+    // both ways of splicing
+    let left = Rope::from("left ");
+    let mixed = MegaSplicable::splice(&left, &String::from("and a String"));
+    println!("{}", mixed.text_chunks().collect::<String>());
+
+    bench_rope(1_000_000, 2_000);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits() {
+        let mut rope = Rope::from("hello world");
+        rope.insert(5, ",");
+        rope.insert(rope.len_chars(), "!");
+        assert_eq!(rope, "hello, world!");
+        rope.remove(0..7);
+        assert_eq!(rope, "world!");
+        let tail = rope.split_off(5);
+        assert_eq!(
+            (rope.to_string(), tail.to_string()),
+            ("world".into(), "!".into())
+        );
+    }
+
+    #[test]
+    fn char_and_line_indexes() {
+        let text = Rope::from("naïve 日本語\nsecond line\n\nlast");
+        assert_eq!(text.len_chars(), 27);
+        assert_eq!(text.len_bytes(), 34);
+        assert_eq!(text.char(2), 'ï');
+        assert_eq!(text.char(7), '本');
+        assert_eq!(text.char_to_byte(7), 10);
+        assert_eq!(text.slice(6..9), "日本語");
+        assert_eq!(text.len_lines(), 4);
+        assert_eq!(text.line(1), "second line\n");
+        assert_eq!(text.line(3), "last");
+        assert_eq!(text.line_to_char(2), 22);
+        assert_eq!(text.char_to_line(22), 2);
+        assert_eq!(text.char_to_line(9), 0);
+        let lines = text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines, ["naïve 日本語\n", "second line\n", "\n", "last"]);
+    }
+
+    #[test]
+    fn random_edits_match_a_string() {
+        // spread over many leaves, the tree stays balanced
+        let mut next = xorshift(42);
+        let mut model = "ab€\n".repeat(2000);
+        let mut rope = Rope::from(model.as_str());
+        for _ in 0..2000 {
+            let chars = model.chars().count();
+            let a = next() as usize % (chars + 1);
+            let byte = |s: &str, c: usize| s.char_indices().nth(c).map_or(s.len(), |(b, _)| b);
+            if next().is_multiple_of(3) {
+                let b = (a + next() as usize % 50).min(chars);
+                model.replace_range(byte(&model, a)..byte(&model, b), "");
+                rope.remove(a..b);
+            } else {
+                model.insert_str(byte(&model, a), "é\nx");
+                rope.insert(a, "é\nx");
+            }
+        }
+        assert!(rope == model.as_str());
+        assert!(rope.is_balanced());
+        assert_eq!(
+            rope.len_lines(),
+            model.lines().count() + model.ends_with('\n') as usize
+        );
+        assert_eq!(rope.chars().count(), rope.len_chars());
+        assert!(rope.chunks().all(|c| c.len() <= MAX_LEAF));
+
+        // a clone is a new root over the same nodes, editing it leaves the original alone
+        let before = rope.clone();
+        rope.remove(0..rope.len_chars() / 2);
+        assert!(before == model.as_str() && rope != before);
+    }
+
+    #[test]
+    fn splicing() {
+        let left = Rope::from("left ");
+        let right = Rope::from("right");
+        assert_eq!(Splicable::splice(&left, &right), "left right");
+        let mixed = MegaSplicable::splice(&left, &String::from("and a String"));
+        assert_eq!(mixed.text_chunks().collect::<String>(), "left and a String");
+    }
+}
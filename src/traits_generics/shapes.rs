@@ -0,0 +1,558 @@
+// Concrete shapes for the `Shape` trait that `impl_trait.rs` started
+// - `make_shape` there could not return `impl Shape`, the type has to be known at compile time
+// - returning `Box<dyn Shape>` instead picks the type at run time, at the cost of a heap allocation
+//   and dynamic dispatch
+//
+// Object safety
+// - `dyn Shape` needs a vtable, and a vtable can only hold methods callable through a pointer
+// - `fn new() -> Self` returns an unsized `Self` for `dyn Shape`, so it is marked `where Self: Sized`,
+//   which leaves it out of the vtable; `Circle::new()` still works, `<dyn Shape>::new()` does not
+use crate::enums::{orientation, polygon_area, Orientation, Point2};
+use crate::input_output::{Fs, InMemoryFs, RealFs};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+pub trait Shape: fmt::Display {
+    fn new() -> Self
+    where
+        Self: Sized;
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn bounding_box(&self) -> BoundingBox;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point2<f64>,
+    pub max: Point2<f64>,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    // the smallest box containing both
+    pub fn union(self, other: BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Point2::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point2::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+}
+
+impl fmt::Display for BoundingBox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} to {}", self.min, self.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point2<f64>,
+    pub radius: f64,
+}
+
+// `corner` is the bottom left one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub corner: Point2<f64>,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Square {
+    pub corner: Point2<f64>,
+    pub side: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub vertices: [Point2<f64>; 3],
+}
+
+impl Shape for Circle {
+    fn new() -> Self {
+        Circle {
+            center: Point2::origin(),
+            radius: 1.0,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * std::f64::consts::PI * self.radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let Point2 { x, y } = self.center;
+        let r = self.radius;
+        BoundingBox {
+            min: Point2::new(x - r, y - r),
+            max: Point2::new(x + r, y + r),
+        }
+    }
+}
+
+impl Shape for Rectangle {
+    fn new() -> Self {
+        Rectangle {
+            corner: Point2::origin(),
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: self.corner,
+            max: Point2::new(self.corner.x + self.width, self.corner.y + self.height),
+        }
+    }
+}
+
+// a square is a rectangle, so it borrows the rectangle's formulas
+impl Square {
+    fn as_rectangle(&self) -> Rectangle {
+        Rectangle {
+            corner: self.corner,
+            width: self.side,
+            height: self.side,
+        }
+    }
+}
+
+impl Shape for Square {
+    fn new() -> Self {
+        Square {
+            corner: Point2::origin(),
+            side: 1.0,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        self.as_rectangle().area()
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.as_rectangle().perimeter()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.as_rectangle().bounding_box()
+    }
+}
+
+impl Shape for Triangle {
+    fn new() -> Self {
+        Triangle {
+            vertices: [
+                Point2::origin(),
+                Point2::new(1.0, 0.0),
+                Point2::new(0.0, 1.0),
+            ],
+        }
+    }
+
+    fn area(&self) -> f64 {
+        polygon_area(&self.vertices)
+    }
+
+    fn perimeter(&self) -> f64 {
+        let [a, b, c] = self.vertices;
+        a.distance(b) + b.distance(c) + c.distance(a)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let [a, b, c] = self.vertices;
+        let corner = |p: Point2<f64>| BoundingBox { min: p, max: p };
+        corner(a).union(corner(b)).union(corner(c))
+    }
+}
+
+// `Display` writes the spec `make_shape` reads, so a shape can be printed and parsed back
+fn write_at(f: &mut fmt::Formatter, at: Point2<f64>) -> fmt::Result {
+    if at == Point2::origin() {
+        Ok(())
+    } else {
+        write!(f, " at={},{}", at.x, at.y)
+    }
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "circle r={}", self.radius)?;
+        write_at(f, self.center)
+    }
+}
+
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rect {}x{}", self.width, self.height)?;
+        write_at(f, self.corner)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "square {}", self.side)?;
+        write_at(f, self.corner)
+    }
+}
+
+impl fmt::Display for Triangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "triangle")?;
+        for p in self.vertices {
+            write!(f, " {},{}", p.x, p.y)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeError {
+    Empty,
+    UnknownShape(String),
+    Missing {
+        shape: &'static str,
+        what: &'static str,
+    },
+    InvalidNumber(String),
+    InvalidPoint(String),
+    InvalidSize(String),
+    NotPositive {
+        what: &'static str,
+        value: f64,
+    },
+    DegenerateTriangle,
+    Unexpected(String),
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShapeError::Empty => write!(f, "empty shape spec"),
+            ShapeError::UnknownShape(s) => write!(f, "unknown shape {:?}", s),
+            ShapeError::Missing { shape, what } => write!(f, "{} needs a {}", shape, what),
+            ShapeError::InvalidNumber(s) => write!(f, "{:?} is not a number", s),
+            ShapeError::InvalidPoint(s) => write!(f, "{:?} is not a point, expected x,y", s),
+            ShapeError::InvalidSize(s) => write!(f, "{:?} is not a size, expected WxH", s),
+            ShapeError::NotPositive { what, value } => {
+                write!(f, "{} must be positive, got {}", what, value)
+            }
+            ShapeError::DegenerateTriangle => write!(f, "triangle vertices are collinear"),
+            ShapeError::Unexpected(s) => write!(f, "unexpected argument {:?}", s),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+// the words after the shape name: `key=value` options and plain positional values
+struct SpecArgs<'s> {
+    shape: &'static str,
+    positional: Vec<&'s str>,
+    options: HashMap<&'s str, &'s str>,
+}
+
+impl<'s> SpecArgs<'s> {
+    fn new(shape: &'static str, words: impl Iterator<Item = &'s str>) -> Self {
+        let mut args = SpecArgs {
+            shape,
+            positional: Vec::new(),
+            options: HashMap::new(),
+        };
+        for word in words {
+            match word.split_once('=') {
+                Some((key, value)) => {
+                    args.options.insert(key, value);
+                }
+                None => args.positional.push(word),
+            }
+        }
+        // so `take` can `pop` them in order
+        args.positional.reverse();
+        args
+    }
+
+    // the option under any of `keys`, or else the next positional value
+    fn take(&mut self, keys: &[&str], what: &'static str) -> Result<&'s str, ShapeError> {
+        keys.iter()
+            .find_map(|key| self.options.remove(*key))
+            .or_else(|| self.positional.pop())
+            .ok_or(ShapeError::Missing {
+                shape: self.shape,
+                what,
+            })
+    }
+
+    fn at(&mut self) -> Result<Point2<f64>, ShapeError> {
+        self.options
+            .remove("at")
+            .map_or(Ok(Point2::origin()), parse_point)
+    }
+
+    // anything not used by now is a mistake in the spec
+    fn finish(self) -> Result<(), ShapeError> {
+        let extra = self
+            .positional
+            .last()
+            .or_else(|| self.options.keys().next())
+            .copied();
+        match extra {
+            Some(word) => Err(ShapeError::Unexpected(word.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_number(s: &str) -> Result<f64, ShapeError> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| ShapeError::InvalidNumber(s.to_string()))
+}
+
+fn parse_positive(s: &str, what: &'static str) -> Result<f64, ShapeError> {
+    let value = parse_number(s)?;
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(ShapeError::NotPositive { what, value })
+    }
+}
+
+fn parse_point(s: &str) -> Result<Point2<f64>, ShapeError> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| ShapeError::InvalidPoint(s.to_string()))?;
+    Ok(Point2::new(parse_number(x)?, parse_number(y)?))
+}
+
+// `circle r=2`, `circle 2 at=1,1`, `rect 3x4`, `square 2`, `triangle 0,0 4,0 0,3`
+pub fn make_shape(spec: &str) -> Result<Box<dyn Shape>, ShapeError> {
+    let mut words = spec.split_whitespace();
+    let kind = words.next().ok_or(ShapeError::Empty)?.to_ascii_lowercase();
+    let shape: Box<dyn Shape> = match kind.as_str() {
+        "circle" => {
+            let mut args = SpecArgs::new("circle", words);
+            let radius = parse_positive(args.take(&["r", "radius"], "radius")?, "radius")?;
+            let center = args.at()?;
+            args.finish()?;
+            Box::new(Circle { center, radius })
+        }
+        "rect" | "rectangle" => {
+            let mut args = SpecArgs::new("rectangle", words);
+            let size = args.take(&["size"], "size like 3x4")?;
+            let (w, h) = size
+                .split_once('x')
+                .ok_or_else(|| ShapeError::InvalidSize(size.to_string()))?;
+            let (width, height) = (parse_positive(w, "width")?, parse_positive(h, "height")?);
+            let corner = args.at()?;
+            args.finish()?;
+            Box::new(Rectangle {
+                corner,
+                width,
+                height,
+            })
+        }
+        "square" => {
+            let mut args = SpecArgs::new("square", words);
+            let side = parse_positive(args.take(&["side"], "side")?, "side")?;
+            let corner = args.at()?;
+            args.finish()?;
+            Box::new(Square { corner, side })
+        }
+        "triangle" => {
+            let mut args = SpecArgs::new("triangle", words);
+            let mut vertex = || parse_point(args.take(&[], "vertex")?);
+            let vertices = [vertex()?, vertex()?, vertex()?];
+            args.finish()?;
+            let [a, b, c] = vertices;
+            if orientation(a, b, c) == Orientation::Collinear {
+                return Err(ShapeError::DegenerateTriangle);
+            }
+            Box::new(Triangle { vertices })
+        }
+        _ => return Err(ShapeError::UnknownShape(kind)),
+    };
+    Ok(shape)
+}
+
+// `cargo run -- shapes shapes.txt`
+// - one spec per line, blank lines and lines starting with '#' are skipped
+// - bad lines are reported with their line number and the rest are still counted
+pub fn shapes_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args {
+        [path] => shapes_report(&RealFs, Path::new(path)),
+        _ => Err("usage: shapes <file>".into()),
+    }
+}
+
+// the subcommand's work, reading the file through `fs`
+pub fn shapes_report(fs: &dyn Fs, path: &Path) -> Result<(), Box<dyn Error>> {
+    let text = fs.read_to_string(path)?;
+    let (mut total, mut bounds, mut count, mut bad) = (0.0, None::<BoundingBox>, 0, 0);
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match make_shape(line) {
+            Ok(shape) => {
+                println!(
+                    "{:<28} area {:>10.3}  perimeter {:>10.3}",
+                    shape.to_string(),
+                    shape.area(),
+                    shape.perimeter()
+                );
+                total += shape.area();
+                let b = shape.bounding_box();
+                bounds = Some(bounds.map_or(b, |bounds| bounds.union(b)));
+                count += 1;
+            }
+            Err(e) => {
+                eprintln!("{}:{}: {}", path.display(), i + 1, e);
+                bad += 1;
+            }
+        }
+    }
+    println!("{} shape(s), total area {:.3}", count, total);
+    if let Some(bounds) = bounds {
+        println!("all within {}", bounds);
+    }
+    if bad > 0 {
+        return Err(format!("{} invalid line(s)", bad).into());
+    }
+    Ok(())
+}
+
+// static dispatch: one copy of this function per shape type, `new` is allowed
+fn unit<S: Shape>() -> f64 {
+    S::new().area()
+}
+
+const SPECS: [&str; 5] = [
+    "circle r=2",
+    "rect 3x4 at=-1,2",
+    "Square side=2.5",
+    "triangle 0,0 4,0 0,3",
+    "circle 0.5 at=1,1",
+];
+
+pub fn use_shapes() -> Result<(), Box<dyn Error>> {
+    println!(
+        "unit square {}, unit triangle {}",
+        unit::<Square>(),
+        unit::<Triangle>()
+    );
+
+    // dynamic dispatch: the type behind each `Box` is only known once the spec is read
+    let shapes: Vec<Box<dyn Shape>> = SPECS
+        .iter()
+        .map(|spec| make_shape(spec))
+        .collect::<Result<_, _>>()?;
+    for shape in &shapes {
+        println!(
+            "{:<24} area {:.3}, bounds {}",
+            shape.to_string(),
+            shape.area(),
+            shape.bounding_box()
+        );
+    }
+
+    for spec in ["hexagon 3", "circle r=-1", "triangle 0,0 1,1 2,2"] {
+        println!("{:?}: {}", spec, make_shape(spec).err().unwrap());
+    }
+
+    // the subcommand on a small file
+    let fs = InMemoryFs::new();
+    let path = Path::new("shapes.txt");
+    fs.write(
+        path,
+        b"# a few shapes\ncircle r=1\n\nrect 2x3\nsquare 4 at=2,2\n",
+    )?;
+    shapes_report(&fs, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_dispatch() {
+        assert_eq!(unit::<Square>(), 1.0);
+        assert_eq!(unit::<Triangle>(), 0.5);
+    }
+
+    #[test]
+    fn specs_read_back() {
+        let shapes: Vec<Box<dyn Shape>> =
+            SPECS.iter().map(|spec| make_shape(spec).unwrap()).collect();
+        // printing gives a spec that reads back to the same shape
+        for shape in &shapes {
+            let again = make_shape(&shape.to_string()).unwrap();
+            assert_eq!(again.to_string(), shape.to_string());
+        }
+        assert_eq!(shapes[1].area(), 12.0);
+        assert_eq!(shapes[3].perimeter(), 12.0);
+        assert_eq!(shapes[3].area(), 6.0);
+        let bounds = shapes
+            .iter()
+            .map(|s| s.bounding_box())
+            .reduce(BoundingBox::union)
+            .unwrap();
+        assert_eq!(
+            (bounds.min, bounds.max),
+            (Point2::new(-2.0, -2.0), Point2::new(4.0, 6.0))
+        );
+    }
+
+    #[test]
+    fn bad_specs() {
+        let bad = [
+            "",
+            "hexagon 3",
+            "circle",
+            "circle r=-1",
+            "rect 3by4",
+            "square 2 3",
+            "triangle 0,0 1,1 2,2",
+            "triangle 0,0 1;1 2,0",
+        ];
+        for spec in bad {
+            assert!(make_shape(spec).is_err(), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn report_counts_bad_lines() {
+        let fs = InMemoryFs::new();
+        let path = Path::new("shapes.txt");
+        fs.write(path, b"circle r=1\nhexagon 3\n").unwrap();
+        assert_eq!(
+            shapes_report(&fs, path).unwrap_err().to_string(),
+            "1 invalid line(s)"
+        );
+    }
+}
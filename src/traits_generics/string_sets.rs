@@ -0,0 +1,537 @@
+// Four implementations of the `StringSet` trait from `type_associated_functions.rs`
+// - `SortedVecSet`: a sorted `Vec<String>` and binary search, compact but `add` shifts elements
+// - `HashStringSet`: a `HashSet<String>`, the obvious choice
+// - `TrieStringSet`: one node per distinct prefix, so words sharing prefixes share storage
+// - `BloomStringSet`: bits only, no strings at all, and `contains` may say yes to a string never added
+//
+// A Bloom filter
+// - `k` hash functions each set one bit of `m` for every string added
+// - `contains` checks those `k` bits: any clear bit means "definitely not added",
+//   all set means "probably added", so there are false positives but never false negatives
+// - `m` and `k` are chosen from the expected number of strings and the false positive rate wanted,
+//   adding more strings than planned raises the rate
+use super::StringSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+use std::time::Instant;
+
+// rough heap usage, for comparing the implementations
+// - counts allocated capacity, not allocator overhead
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SortedVecSet {
+    items: Vec<String>,
+}
+
+impl StringSet for SortedVecSet {
+    fn new() -> Self {
+        SortedVecSet::default()
+    }
+
+    // sort once instead of inserting one by one, which would be quadratic
+    fn from_slice(strings: &[&str]) -> Self {
+        let mut items: Vec<String> = strings.iter().map(|s| s.to_string()).collect();
+        items.sort_unstable();
+        items.dedup();
+        SortedVecSet { items }
+    }
+
+    fn contains(&self, string: &str) -> bool {
+        self.items
+            .binary_search_by(|item| item.as_str().cmp(string))
+            .is_ok()
+    }
+
+    fn add(&mut self, string: &str) {
+        if let Err(i) = self
+            .items
+            .binary_search_by(|item| item.as_str().cmp(string))
+        {
+            self.items.insert(i, string.to_string());
+        }
+    }
+}
+
+impl HeapSize for SortedVecSet {
+    fn heap_size(&self) -> usize {
+        self.items.capacity() * size_of::<String>()
+            + self.items.iter().map(String::heap_size).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HashStringSet {
+    items: HashSet<String>,
+}
+
+impl StringSet for HashStringSet {
+    fn new() -> Self {
+        HashStringSet::default()
+    }
+
+    fn from_slice(strings: &[&str]) -> Self {
+        HashStringSet {
+            items: strings.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn contains(&self, string: &str) -> bool {
+        self.items.contains(string)
+    }
+
+    fn add(&mut self, string: &str) {
+        if !self.items.contains(string) {
+            self.items.insert(string.to_string());
+        }
+    }
+}
+
+impl HeapSize for HashStringSet {
+    // one control byte per bucket besides the `String`s, as in hashbrown's table layout
+    fn heap_size(&self) -> usize {
+        self.items.capacity() * (size_of::<String>() + 1)
+            + self.items.iter().map(String::heap_size).sum::<usize>()
+    }
+}
+
+// children are kept sorted by byte, so lookups can binary search them
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    terminal: bool,
+    children: Vec<(u8, TrieNode)>,
+}
+
+impl TrieNode {
+    fn child(&self, byte: u8) -> Option<&TrieNode> {
+        let i = self
+            .children
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()?;
+        Some(&self.children[i].1)
+    }
+
+    fn child_or_insert(&mut self, byte: u8) -> &mut TrieNode {
+        let i = match self.children.binary_search_by_key(&byte, |(b, _)| *b) {
+            Ok(i) => i,
+            Err(i) => {
+                self.children.insert(i, (byte, TrieNode::default()));
+                i
+            }
+        };
+        &mut self.children[i].1
+    }
+}
+
+impl HeapSize for TrieNode {
+    fn heap_size(&self) -> usize {
+        self.children.capacity() * size_of::<(u8, TrieNode)>()
+            + self
+                .children
+                .iter()
+                .map(|(_, child)| child.heap_size())
+                .sum::<usize>()
+    }
+}
+
+// a byte trie: a `str` is UTF-8 bytes, and walking bytes keeps every node's fan-out under 256
+#[derive(Debug, Clone, Default)]
+pub struct TrieStringSet {
+    root: TrieNode,
+}
+
+impl StringSet for TrieStringSet {
+    fn new() -> Self {
+        TrieStringSet::default()
+    }
+
+    fn from_slice(strings: &[&str]) -> Self {
+        let mut set = TrieStringSet::new();
+        for s in strings {
+            set.add(s);
+        }
+        set
+    }
+
+    // only strings that were added end on a `terminal` node, prefixes of them do not
+    fn contains(&self, string: &str) -> bool {
+        string
+            .bytes()
+            .try_fold(&self.root, |node, byte| node.child(byte))
+            .is_some_and(|node| node.terminal)
+    }
+
+    fn add(&mut self, string: &str) {
+        let mut node = &mut self.root;
+        for byte in string.bytes() {
+            node = node.child_or_insert(byte);
+        }
+        node.terminal = true;
+    }
+}
+
+impl HeapSize for TrieStringSet {
+    fn heap_size(&self) -> usize {
+        self.root.heap_size()
+    }
+}
+
+// a Bloom filter cannot be sized for no strings, or for a rate that is not a probability strictly
+// between 0 and 1: 0 would need infinitely many bits and 1 needs none
+#[derive(Debug, Clone, PartialEq)]
+pub enum BloomError {
+    ZeroCapacity,
+    BadFalsePositiveRate(f64),
+}
+
+impl fmt::Display for BloomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BloomError::ZeroCapacity => write!(f, "expected number of strings must be positive"),
+            BloomError::BadFalsePositiveRate(rate) => {
+                write!(
+                    f,
+                    "false positive rate must be between 0 and 1, got {}",
+                    rate
+                )
+            }
+        }
+    }
+}
+
+impl Error for BloomError {}
+
+#[derive(Debug, Clone)]
+pub struct BloomStringSet {
+    bits: Vec<u64>,
+    // number of bits, `bits.len() * 64`
+    m: u64,
+    // number of hash functions
+    k: u32,
+}
+
+impl BloomStringSet {
+    // the capacity `new` plans for
+    const DEFAULT_CAPACITY: usize = 1024;
+    const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+    // the standard sizing
+    // - m = -n ln(p) / ln(2)^2 bits, about 9.6 bits per string for 1%
+    // - k = m / n ln(2) hash functions, about 7 for 1%
+    pub fn with_capacity(expected: usize, false_positive_rate: f64) -> Result<Self, BloomError> {
+        if expected == 0 {
+            return Err(BloomError::ZeroCapacity);
+        }
+        // written so that NaN fails too
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(BloomError::BadFalsePositiveRate(false_positive_rate));
+        }
+        let n = expected as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-n * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let words = m.div_ceil(64);
+        let k = ((words * 64) as f64 / n * ln2).round().max(1.0) as u32;
+        Ok(BloomStringSet {
+            bits: vec![0; words as usize],
+            m: words * 64,
+            k,
+        })
+    }
+
+    // double hashing: `k` bit positions from two hashes, h1 + i * h2
+    // - Kirsch and Mitzenmacher showed this is as good as `k` independent hashes
+    // - `h2` is made odd so that it never degenerates to the same position `k` times
+    fn positions(&self, string: &str) -> impl Iterator<Item = u64> + '_ {
+        let hash = |seed: u64| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            string.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (h1, h2) = (hash(0), hash(1) | 1);
+        (0..self.k as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.m)
+    }
+
+    // fraction of bits set, the false positive rate is about this to the power `k`
+    pub fn fill_ratio(&self) -> f64 {
+        let ones: u32 = self.bits.iter().map(|w| w.count_ones()).sum();
+        ones as f64 / self.m as f64
+    }
+}
+
+impl StringSet for BloomStringSet {
+    fn new() -> Self {
+        BloomStringSet::with_capacity(Self::DEFAULT_CAPACITY, Self::DEFAULT_FALSE_POSITIVE_RATE)
+            .unwrap()
+    }
+
+    fn from_slice(strings: &[&str]) -> Self {
+        let capacity = strings.len().max(Self::DEFAULT_CAPACITY);
+        let mut set =
+            BloomStringSet::with_capacity(capacity, Self::DEFAULT_FALSE_POSITIVE_RATE).unwrap();
+        for s in strings {
+            set.add(s);
+        }
+        set
+    }
+
+    fn contains(&self, string: &str) -> bool {
+        self.positions(string)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    fn add(&mut self, string: &str) {
+        let positions: Vec<u64> = self.positions(string).collect();
+        for bit in positions {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+}
+
+impl HeapSize for BloomStringSet {
+    fn heap_size(&self) -> usize {
+        self.bits.capacity() * size_of::<u64>()
+    }
+}
+
+fn random_words(count: usize, seed: u64) -> Vec<String> {
+    // xorshift, deterministic so every run measures the same words
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count)
+        .map(|_| {
+            let len = 3 + next() % 8;
+            (0..len)
+                .map(|_| (b'a' + (next() % 26) as u8) as char)
+                .collect()
+        })
+        .collect()
+}
+
+fn bench<S: StringSet + HeapSize>(name: &str, words: &[&str], probes: &[&str]) -> usize {
+    let start = Instant::now();
+    let set = S::from_slice(words);
+    let built = start.elapsed();
+    let start = Instant::now();
+    let hits = probes.iter().filter(|p| set.contains(p)).count();
+    let lookups = start.elapsed();
+    println!(
+        "{:<14} {:>8.1} KiB  build {:>9.2?}  {} lookups {:>9.2?}  {} hits",
+        name,
+        set.heap_size() as f64 / 1024.0,
+        built,
+        probes.len(),
+        lookups,
+        hits
+    );
+    hits
+}
+
+// memory and lookup time on a large generated word list
+// - half of the probes were added, half are new words
+// - random words share few prefixes, which is the worst case for the trie's memory
+// - run with `cargo run --release` for numbers that mean anything
+pub fn bench_string_sets(count: usize) {
+    let words = random_words(count, 0x2545_f491_4f6c_dd1d);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let others = random_words(count / 2, 0x9e37_79b9_7f4a_7c15);
+    let probes: Vec<&str> = words[..count / 2]
+        .iter()
+        .copied()
+        .chain(others.iter().map(String::as_str))
+        .collect();
+
+    let exact = bench::<HashStringSet>("HashStringSet", &words, &probes);
+    assert_eq!(
+        bench::<SortedVecSet>("SortedVecSet", &words, &probes),
+        exact
+    );
+    assert_eq!(
+        bench::<TrieStringSet>("TrieStringSet", &words, &probes),
+        exact
+    );
+    let bloom = bench::<BloomStringSet>("BloomStringSet", &words, &probes);
+    // only ever more hits, never fewer
+    assert!(bloom >= exact);
+    println!(
+        "bloom false positives: {} of {} absent probes",
+        bloom - exact,
+        probes.len() - exact
+    );
+}
+
+pub fn use_string_sets() {
+    let mut set = TrieStringSet::from_slice(&["car", "cart", "carton"]);
+    set.add("日本");
+    // a prefix of an added word was not added itself
+    println!(
+        "cart {}, ca {}, 日本 {}",
+        set.contains("cart"),
+        set.contains("ca"),
+        set.contains("日本")
+    );
+
+    // the false positive rate stays near what the filter was sized for
+    let words = random_words(10_000, 7);
+    let refs: Vec<&str> = words.iter().map(String::as_str).collect();
+    let bloom = BloomStringSet::from_slice(&refs);
+    println!(
+        "bloom: k = {}, {:.0}% of bits set",
+        bloom.k,
+        bloom.fill_ratio() * 100.0
+    );
+
+    bench_string_sets(50_000);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The conformance suite, generic over any `StringSet`
+    // - `exact` is false for sets that may report false positives, the "not contained" checks are skipped
+    // - a false negative is a bug for every implementation, so "contained" is always checked
+    fn check_string_set<S: StringSet>(name: &str, exact: bool) {
+        let absent = |set: &S, s: &str| {
+            if exact {
+                assert!(!set.contains(s), "{}: {:?} was never added", name, s);
+            }
+        };
+
+        let mut set = S::new();
+        for s in ["", "a", "car"] {
+            absent(&set, s);
+        }
+
+        // the empty string, prefixes of each other, non-ASCII, and a duplicate
+        let words = [
+            "car",
+            "cart",
+            "carton",
+            "",
+            "ğüşiöç",
+            "日本",
+            "zebra",
+            "car",
+        ];
+        for w in words {
+            set.add(w);
+        }
+        for w in words {
+            assert!(set.contains(w), "{}: lost {:?} after add", name, w);
+        }
+        for s in ["ca", "carts", "cartoon", "日", "Car", "zebra "] {
+            absent(&set, s);
+        }
+
+        // adding again changes nothing
+        set.add("cart");
+        assert!(set.contains("cart"));
+        absent(&set, "cartt");
+
+        let set = S::from_slice(&words);
+        for w in words {
+            assert!(set.contains(w), "{}: from_slice lost {:?}", name, w);
+        }
+        absent(&set, "ca");
+        let mut set = S::from_slice(&[]);
+        absent(&set, "");
+        set.add("");
+        assert!(set.contains(""));
+
+        // many strings, in an order that is neither sorted nor reversed
+        let many: Vec<String> = (0..2000)
+            .map(|i| format!("w{}", (i * 7919) % 2000))
+            .collect();
+        let refs: Vec<&str> = many.iter().map(String::as_str).collect();
+        let mut grown = S::new();
+        for s in &refs {
+            grown.add(s);
+        }
+        let built = S::from_slice(&refs);
+        for s in &refs {
+            assert!(
+                grown.contains(s) && built.contains(s),
+                "{}: lost {:?}",
+                name,
+                s
+            );
+        }
+        for i in 2000..2100 {
+            absent(&grown, &format!("w{}", i));
+            absent(&built, &format!("w{}", i));
+        }
+    }
+
+    #[test]
+    fn sorted_vec_set() {
+        check_string_set::<SortedVecSet>("SortedVecSet", true);
+    }
+
+    #[test]
+    fn hash_string_set() {
+        check_string_set::<HashStringSet>("HashStringSet", true);
+    }
+
+    #[test]
+    fn trie_string_set() {
+        check_string_set::<TrieStringSet>("TrieStringSet", true);
+    }
+
+    #[test]
+    fn bloom_string_set() {
+        check_string_set::<BloomStringSet>("BloomStringSet", false);
+    }
+
+    #[test]
+    fn bloom_false_positive_rate() {
+        // sized for 1%, so well under 2% of 10 000 probes
+        let words = random_words(10_000, 7);
+        let refs: Vec<&str> = words.iter().map(String::as_str).collect();
+        let bloom = BloomStringSet::from_slice(&refs);
+        let exact = HashStringSet::from_slice(&refs);
+        let probes = random_words(10_000, 8);
+        let false_positives = probes
+            .iter()
+            .filter(|p| !exact.contains(p) && bloom.contains(p))
+            .count();
+        assert!(false_positives < 200, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn bloom_sizing_is_validated() {
+        for rate in [0.0, 1.0, -0.5, 2.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                BloomStringSet::with_capacity(100, rate),
+                Err(BloomError::BadFalsePositiveRate(_))
+            ));
+        }
+        assert_eq!(
+            BloomStringSet::with_capacity(0, 0.01).err(),
+            Some(BloomError::ZeroCapacity)
+        );
+        let mut set = BloomStringSet::with_capacity(1, 0.999).unwrap();
+        set.add("one");
+        assert!(set.contains("one"));
+        assert!(BloomStringSet::with_capacity(1000, 1e-12).is_ok());
+    }
+}
@@ -1,30 +1,82 @@
-trait Visible {
-    fn show(&self) {
-        todo!();
+use super::{Frame, Rng};
+use crate::enums::{Point2, Vector2};
+
+pub trait Visible {
+    fn glyph(&self) -> char;
+
+    // one cell at `at`; a creature that covers more cells overrides this
+    fn show(&self, at: Point2<i32>, frame: &mut Frame) {
+        frame.plot(at, self.glyph());
     }
 }
 
 // `trait Creature: Visible` means that every type that implement `Creature` must also implement `Visible`
-trait Creature: Visible {
-    fn position(&self) {
-        todo!();
-    }
+// - so a `&dyn Creature` can call `show` and `glyph` too
+pub trait Creature: Visible {
+    fn name(&self) -> &str;
+    fn position(&self) -> Point2<i32>;
+    fn set_position(&mut self, to: Point2<i32>);
+
+    // the step the creature wants to take this tick, `World` decides whether it gets to
+    // - random choices come from `rng`, so a run is the same for the same seed
+    fn next_move(&mut self, rng: &mut Rng) -> Vector2<i32>;
+
+    // told when the step ran into a wall or another creature
+    fn blocked(&mut self) {}
 }
 
-struct Broom {
+// sweeps left and right, turning around at whatever stops it
+pub struct Broom {
     shape: String,
     length: u32,
+    position: Point2<i32>,
+    dx: i32,
+}
+
+impl Broom {
+    pub fn new(shape: &str, length: u32, position: Point2<i32>) -> Self {
+        Broom {
+            shape: shape.to_string(),
+            length,
+            position,
+            dx: 1,
+        }
+    }
 }
 
 // We can impl in arbitrary order but both of `Creature` and `Visible`
 impl Creature for Broom {
-    fn position(&self) {
-        todo!();
+    fn name(&self) -> &str {
+        &self.shape
+    }
+
+    fn position(&self) -> Point2<i32> {
+        self.position
+    }
+
+    fn set_position(&mut self, to: Point2<i32>) {
+        self.position = to;
+    }
+
+    fn next_move(&mut self, _rng: &mut Rng) -> Vector2<i32> {
+        Vector2::new(self.dx, 0)
+    }
+
+    fn blocked(&mut self) {
+        self.dx = -self.dx;
     }
 }
 
 impl Visible for Broom {
-    fn show(&self) {
-        todo!();
+    fn glyph(&self) -> char {
+        '#'
+    }
+
+    // the handle trails behind the head; only the head takes up space in the world
+    fn show(&self, at: Point2<i32>, frame: &mut Frame) {
+        for i in 1..=self.length as i32 {
+            frame.plot(Point2::new(at.x - i * self.dx, at.y), '=');
+        }
+        frame.plot(at, self.glyph());
     }
 }
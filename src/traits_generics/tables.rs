@@ -0,0 +1,539 @@
+// Renders any iterator of rows as a table, generalizing `dump`, `dump2` and `dump3` in `associated_types.rs`
+// - a row is anything that implements `Row`: tuples of `Display` values, `Vec`s and arrays, or a struct
+// - `TableRenderer` writes it as aligned text, Markdown, CSV or JSON lines to any `io::Write`
+// - named `TableRenderer` because `Table` is already the artist-to-works alias in `references`
+//
+// Text and Markdown need every row before the first line is written, to know the column widths
+// - CSV and JSON lines are written row by row as the iterator produces them
+// - widths count chars, so wide CJK glyphs and combining marks throw the alignment off
+// - only text and Markdown truncate, CSV and JSON lines are for other programs and keep every cell whole
+use super::dump_table;
+use crate::enums::{Json, JsonObject};
+use std::fmt::Display;
+use std::io::{self, Write};
+
+pub trait Row {
+    fn cells(&self) -> Vec<String>;
+
+    // column names, for a type that knows them
+    fn headers() -> Option<Vec<String>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+impl<R: Row> Row for &R {
+    fn cells(&self) -> Vec<String> {
+        (**self).cells()
+    }
+
+    fn headers() -> Option<Vec<String>> {
+        R::headers()
+    }
+}
+
+impl<T: Display> Row for Vec<T> {
+    fn cells(&self) -> Vec<String> {
+        self.iter().map(T::to_string).collect()
+    }
+}
+
+impl<T: Display, const N: usize> Row for [T; N] {
+    fn cells(&self) -> Vec<String> {
+        self.iter().map(T::to_string).collect()
+    }
+}
+
+macro_rules! tuple_row {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: Display),+> Row for ($($t,)+) {
+            fn cells(&self) -> Vec<String> {
+                vec![$(self.$i.to_string()),+]
+            }
+        }
+    };
+}
+
+tuple_row!(A 0);
+tuple_row!(A 0, B 1);
+tuple_row!(A 0, B 1, C 2);
+tuple_row!(A 0, B 1, C 2, D 3);
+tuple_row!(A 0, B 1, C 2, D 3, E 4);
+tuple_row!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    // right if every cell in the column is a number, left otherwise
+    Auto,
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Text,
+    Markdown,
+    Csv,
+    JsonLines,
+}
+
+// a builder like `ListFormatter`, each setter takes `self` and returns it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRenderer {
+    format: TableFormat,
+    headers: Option<Vec<String>>,
+    aligns: Vec<Align>,
+    max_column_width: Option<usize>,
+    max_width: Option<usize>,
+    numbered: bool,
+}
+
+impl Default for TableRenderer {
+    fn default() -> Self {
+        TableRenderer {
+            format: TableFormat::Text,
+            headers: None,
+            aligns: vec![],
+            max_column_width: None,
+            max_width: None,
+            numbered: false,
+        }
+    }
+}
+
+// a truncated cell is never narrower than this, one char and the ellipsis and one more
+const MIN_WIDTH: usize = 3;
+
+impl TableRenderer {
+    pub fn new() -> Self {
+        TableRenderer::default()
+    }
+
+    pub fn format(mut self, format: TableFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // overrides `Row::headers`
+    pub fn headers(mut self, headers: &[&str]) -> Self {
+        self.headers = Some(headers.iter().map(|h| h.to_string()).collect());
+        self
+    }
+
+    // columns past the end of `aligns` are `Align::Auto`
+    pub fn align(mut self, aligns: &[Align]) -> Self {
+        self.aligns = aligns.to_vec();
+        self
+    }
+
+    pub fn max_column_width(mut self, width: usize) -> Self {
+        self.max_column_width = Some(width.max(MIN_WIDTH));
+        self
+    }
+
+    // the widest columns give up chars until each line fits, or every column is down to `MIN_WIDTH`
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    // a leading `#` column counting rows from 0, like `dump`'s `index: `
+    pub fn numbered(mut self, numbered: bool) -> Self {
+        self.numbered = numbered;
+        self
+    }
+
+    pub fn render<W, I>(&self, out: &mut W, rows: I) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+        I: IntoIterator,
+        I::Item: Row,
+    {
+        let mut headers = self.headers.clone().or_else(<I::Item as Row>::headers);
+        if let (true, Some(headers)) = (self.numbered, &mut headers) {
+            headers.insert(0, "#".to_string());
+        }
+        let rows = rows.into_iter().enumerate().map(|(i, row)| {
+            let mut cells = row.cells();
+            if self.numbered {
+                cells.insert(0, i.to_string());
+            }
+            cells
+        });
+        match self.format {
+            TableFormat::Csv => {
+                if let Some(headers) = &headers {
+                    write_csv_line(out, headers)?;
+                }
+                for row in rows {
+                    write_csv_line(out, &row)?;
+                }
+                Ok(())
+            }
+            TableFormat::JsonLines => {
+                for row in rows {
+                    writeln!(out, "{}", json_line(headers.as_deref(), row))?;
+                }
+                Ok(())
+            }
+            TableFormat::Text | TableFormat::Markdown => {
+                self.write_aligned(out, headers, rows.collect())
+            }
+        }
+    }
+
+    pub fn render_to_string<I>(&self, rows: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Row,
+    {
+        let mut out = vec![];
+        // writing to a `Vec<u8>` cannot fail, and every cell came from a `String`
+        self.render(&mut out, rows).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    pub fn print<I>(&self, rows: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Row,
+    {
+        self.render(&mut io::stdout().lock(), rows)
+    }
+
+    fn write_aligned<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        headers: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+    ) -> io::Result<()> {
+        let markdown = self.format == TableFormat::Markdown;
+        let columns = rows
+            .iter()
+            .map(Vec::len)
+            .chain(headers.as_ref().map(Vec::len))
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return Ok(());
+        }
+        let clean = |cell: &str| {
+            let cell = cell.replace(['\n', '\r', '\t'], " ");
+            if markdown {
+                cell.replace('|', "\\|")
+            } else {
+                cell
+            }
+        };
+        let pad = |row: &[String]| {
+            (0..columns)
+                .map(|j| row.get(j).map_or(String::new(), |c| clean(c)))
+                .collect::<Vec<_>>()
+        };
+        // Markdown needs a header row, empty names will do
+        let headers = match headers {
+            Some(headers) => Some(pad(&headers)),
+            None if markdown => Some(vec![String::new(); columns]),
+            None => None,
+        };
+        let rows = rows.iter().map(|row| pad(row)).collect::<Vec<_>>();
+
+        let aligns = (0..columns)
+            .map(
+                |j| match self.aligns.get(j).copied().unwrap_or(Align::Auto) {
+                    Align::Auto
+                        if rows.iter().all(|row| is_number(&row[j])) && !rows.is_empty() =>
+                    {
+                        Align::Right
+                    }
+                    Align::Auto => Align::Left,
+                    align => align,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let min_width = if markdown { MIN_WIDTH } else { 1 };
+        let mut widths = (0..columns)
+            .map(|j| {
+                let widest = rows
+                    .iter()
+                    .chain(&headers)
+                    .map(|row| row[j].chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(min_width);
+                self.max_column_width.map_or(widest, |max| widest.min(max))
+            })
+            .collect::<Vec<_>>();
+        if let Some(max_width) = self.max_width {
+            // text separates columns with two spaces, Markdown writes "| " before each and " |" at the end
+            let overhead = if markdown {
+                3 * columns + 1
+            } else {
+                2 * (columns - 1)
+            };
+            while widths.iter().sum::<usize>() + overhead > max_width {
+                let (j, &widest) = widths
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|&(j, w)| (*w, columns - j))
+                    .unwrap();
+                if widest <= MIN_WIDTH {
+                    break;
+                }
+                widths[j] -= 1;
+            }
+        }
+
+        let line = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .zip(&widths)
+                .zip(&aligns)
+                .map(|((cell, &width), &align)| fit(cell, width, align))
+                .collect::<Vec<_>>();
+            if markdown {
+                format!("| {} |", cells.join(" | "))
+            } else {
+                cells.join("  ").trim_end().to_string()
+            }
+        };
+        if let Some(headers) = &headers {
+            // headers follow their column's alignment, so numbers and their name line up
+            writeln!(out, "{}", line(headers))?;
+            let rules = widths
+                .iter()
+                .zip(&aligns)
+                .map(|(&width, &align)| match (markdown, align) {
+                    (false, _) => "-".repeat(width),
+                    (true, Align::Right) => format!("{}:", "-".repeat(width - 1)),
+                    (true, Align::Center) => format!(":{}:", "-".repeat(width - 2)),
+                    (true, _) => format!(":{}", "-".repeat(width - 1)),
+                })
+                .collect::<Vec<_>>();
+            if markdown {
+                writeln!(out, "| {} |", rules.join(" | "))?;
+            } else {
+                writeln!(out, "{}", rules.join("  "))?;
+            }
+        }
+        for row in &rows {
+            writeln!(out, "{}", line(row))?;
+        }
+        Ok(())
+    }
+}
+
+fn is_number(cell: &str) -> bool {
+    !cell.is_empty() && cell.trim().parse::<f64>().is_ok()
+}
+
+// exactly `width` chars: cut with an ellipsis, or padded on the side(s) `align` says
+fn fit(cell: &str, width: usize, align: Align) -> String {
+    let cut;
+    let shown = if cell.chars().count() > width {
+        let kept = cell
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        cut = format!("{}…", kept.trim_end());
+        &cut
+    } else {
+        cell
+    };
+    let room = width - shown.chars().count();
+    let (left, right) = match align {
+        Align::Right => (room, 0),
+        Align::Center => (room / 2, room - room / 2),
+        Align::Left | Align::Auto => (0, room),
+    };
+    format!("{}{}{}", " ".repeat(left), shown, " ".repeat(right))
+}
+
+// RFC 4180: a field with a comma, quote or line break is quoted, and quotes inside are doubled
+fn write_csv_line<W: Write + ?Sized>(out: &mut W, cells: &[String]) -> io::Result<()> {
+    let fields = cells
+        .iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    writeln!(out, "{}", fields.join(","))
+}
+
+// an object keyed by the headers, or an array without them
+// - cells past the last header are keyed by their column number
+fn json_line(headers: Option<&[String]>, row: Vec<String>) -> Json {
+    match headers {
+        Some(headers) => {
+            let mut object = JsonObject::new();
+            for (j, cell) in row.into_iter().enumerate() {
+                let key = headers.get(j).cloned().unwrap_or_else(|| j.to_string());
+                object.insert(key, cell);
+            }
+            Json::Object(object)
+        }
+        None => Json::Array(row.into_iter().map(Json::String).collect()),
+    }
+}
+
+struct Planet {
+    name: &'static str,
+    moons: u32,
+    // in astronomical units
+    distance: f64,
+}
+
+// a struct row names its own columns
+impl Row for Planet {
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.to_string(),
+            self.moons.to_string(),
+            format!("{:.2}", self.distance),
+        ]
+    }
+
+    fn headers() -> Option<Vec<String>> {
+        Some(vec!["planet".into(), "moons".into(), "distance".into()])
+    }
+}
+
+const PLANETS: [Planet; 3] = [
+    Planet {
+        name: "Mercury",
+        moons: 0,
+        distance: 0.39,
+    },
+    Planet {
+        name: "Earth",
+        moons: 1,
+        distance: 1.0,
+    },
+    Planet {
+        name: "Jupiter",
+        moons: 95,
+        distance: 5.2,
+    },
+];
+
+pub fn use_tables() {
+    print!("{}", TableRenderer::new().render_to_string(&PLANETS));
+    print!(
+        "{}",
+        TableRenderer::new()
+            .format(TableFormat::Markdown)
+            .align(&[Align::Center])
+            .render_to_string(&PLANETS[..2])
+    );
+
+    // tuples and `Vec`s, with the headers given here; fields are quoted only when needed
+    let rows = vec![("a, b", 1), ("say \"hi\"", 2), ("two\nlines", 3)];
+    print!(
+        "{}",
+        TableRenderer::new()
+            .format(TableFormat::Csv)
+            .headers(&["text", "n"])
+            .render_to_string(&rows)
+    );
+
+    // truncation by the whole line; ragged rows are padded
+    let long = vec![
+        vec!["short".to_string(), "a rather long description".to_string()],
+        vec!["x".to_string()],
+    ];
+    print!(
+        "{}",
+        TableRenderer::new()
+            .max_width(16)
+            .numbered(true)
+            .render_to_string(&long)
+    );
+
+    // `dump` with a table instead of `index: {:?}` lines
+    dump_table(["one", "two", "three"].iter().map(|s| (s, s.len())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_and_markdown() {
+        let text = TableRenderer::new().render_to_string(&PLANETS);
+        assert_eq!(
+            text,
+            "planet   moons  distance\n\
+             -------  -----  --------\n\
+             Mercury      0      0.39\n\
+             Earth        1      1.00\n\
+             Jupiter     95      5.20\n"
+        );
+        let markdown = TableRenderer::new()
+            .format(TableFormat::Markdown)
+            .align(&[Align::Center])
+            .render_to_string(&PLANETS[..2]);
+        assert_eq!(
+            markdown,
+            "| planet  | moons | distance |\n\
+             | :-----: | ----: | -------: |\n\
+             | Mercury |     0 |     0.39 |\n\
+             |  Earth  |     1 |     1.00 |\n"
+        );
+    }
+
+    #[test]
+    fn csv_and_json_lines() {
+        let rows = vec![("a, b", 1), ("say \"hi\"", 2), ("two\nlines", 3)];
+        let csv = TableRenderer::new()
+            .format(TableFormat::Csv)
+            .headers(&["text", "n"])
+            .render_to_string(&rows);
+        assert_eq!(
+            csv,
+            "text,n\n\"a, b\",1\n\"say \"\"hi\"\"\",2\n\"two\nlines\",3\n"
+        );
+        // a header for every column is not required, the rest are named by index
+        let json = TableRenderer::new()
+            .format(TableFormat::JsonLines)
+            .headers(&["text"])
+            .render_to_string(&rows[..2]);
+        assert_eq!(
+            json,
+            "{\"text\":\"a, b\",\"1\":\"1\"}\n{\"text\":\"say \\\"hi\\\"\",\"1\":\"2\"}\n"
+        );
+        let json = TableRenderer::new()
+            .format(TableFormat::JsonLines)
+            .render_to_string([vec!["x", "y"]]);
+        assert_eq!(json, "[\"x\",\"y\"]\n");
+    }
+
+    #[test]
+    fn truncation() {
+        let long = vec![
+            vec!["short".to_string(), "a rather long description".to_string()],
+            vec!["x".to_string()],
+        ];
+        let narrow = TableRenderer::new()
+            .max_column_width(10)
+            .render_to_string(&long);
+        assert_eq!(narrow, "short  a rather…\nx\n");
+        let fitted = TableRenderer::new()
+            .max_width(16)
+            .numbered(true)
+            .render_to_string(&long);
+        assert!(
+            fitted.lines().all(|l| l.chars().count() <= 16),
+            "{}",
+            fitted
+        );
+        assert_eq!(fitted, "0  short  a rat…\n1  x\n");
+    }
+}
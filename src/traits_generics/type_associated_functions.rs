@@ -1,7 +1,9 @@
+use super::{HashStringSet, SortedVecSet, TrieStringSet};
+
 // Type-associated functions, aka static methods in other languages
 // any type that implements `StringSet` must implement all the type-associated functions
 
-trait StringSet {
+pub trait StringSet {
     // `new` and `from_slice` does not take `&self` or `self` as parameter
     // - similarly in struct
     fn new() -> Self;
@@ -14,7 +16,7 @@ trait StringSet {
 
 // but above `StringSet` is incompatible with trait object because `new` and `from_slice` use `Self`
 // - to remedy add `Sized` to Self
-trait StringSet2 {
+pub trait StringSet2 {
     fn new() -> Self
     where
         Self: Sized;
@@ -24,9 +26,40 @@ trait StringSet2 {
     fn contains(&self, string: &str) -> bool;
     fn add(&mut self, string: &str);
 }
-fn use_taf() {
+
+// every `StringSet` is a `StringSet2` too, which makes the sets in `string_sets.rs` usable as `&dyn StringSet2`
+// - with both traits in scope `set.contains(..)` is ambiguous on those sets, so we name the trait here
+impl<S: StringSet> StringSet2 for S {
+    fn new() -> Self {
+        <S as StringSet>::new()
+    }
+    fn from_slice(strings: &[&str]) -> Self {
+        <S as StringSet>::from_slice(strings)
+    }
+    fn contains(&self, string: &str) -> bool {
+        StringSet::contains(self, string)
+    }
+    fn add(&mut self, string: &str) {
+        StringSet::add(self, string)
+    }
+}
+
+pub fn use_taf() {
     // we cannot instantiate a trait object from `StringSet`
     // let ss: &dyn StringSet;
     // we can instantiate a trait object from `StringSet2`
-    let ss2: &dyn StringSet2;
+    // - the concrete type is picked at run time, and `new`/`from_slice` are called on it before it is boxed
+    let words = ["taf", "trait", "object"];
+    let mut sets: Vec<Box<dyn StringSet2>> = vec![
+        Box::new(<SortedVecSet as StringSet>::from_slice(&words)),
+        Box::new(<HashStringSet as StringSet>::from_slice(&words)),
+        Box::new(<TrieStringSet as StringSet>::from_slice(&words)),
+    ];
+    for set in &mut sets {
+        // `set` is `&mut Box<dyn StringSet2>`, calls go through the vtable
+        set.add("dyn");
+        assert!(set.contains("dyn") && set.contains("trait") && !set.contains("tra"));
+    }
+    let ss2: &dyn StringSet2 = sets[0].as_ref();
+    println!("{}", ss2.contains("object"));
 }
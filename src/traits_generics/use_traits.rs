@@ -1,6 +1,7 @@
 // A writer without caring about its type can be written like below
-use std::fs::File;
-use std::io::Write;
+use super::Rope;
+use crate::input_output::{CountingWriter, Fs, InMemoryFs, TeeWriter};
+use std::io::{self, Write};
 // `&mut dyn Write` means a mutable ref to any value that implements the `Write` trait
 // so `out` is trait object so this function is a plain function as opposed to generic function
 // multiple traits like `&mut dyn Write + Debug + Hash` is not supported
@@ -26,7 +27,7 @@ where
 
 // A trait can sue `Self` as type
 // - but would be incompatible with trait object
-trait Splicable {
+pub trait Splicable {
     fn splice(&self, other: &Self) -> Self;
 }
 // - not working, since `Splicable` can be use as trait object
@@ -35,18 +36,39 @@ trait Splicable {
 // }
 
 // Or we could design as below
-trait MegaSplicable {
+// - `splice` only sees `other` as a trait object, so the trait must also say how to read one
+pub trait MegaSplicable {
     fn splice(&self, other: &dyn MegaSplicable) -> Box<dyn MegaSplicable>;
+    fn text_chunks(&self) -> Box<dyn Iterator<Item = &str> + '_>;
 }
-fn splice_anthing(left: &dyn MegaSplicable, right: &dyn MegaSplicable) {
-    todo!()
+fn splice_anthing(left: &dyn MegaSplicable, right: &dyn MegaSplicable) -> Box<dyn MegaSplicable> {
+    left.splice(right)
+}
+
+// `Rope` in `rope.rs` is the other implementation
+impl MegaSplicable for String {
+    fn splice(&self, other: &dyn MegaSplicable) -> Box<dyn MegaSplicable> {
+        let mut spliced = self.clone();
+        spliced.extend(other.text_chunks());
+        Box::new(spliced)
+    }
+
+    fn text_chunks(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(std::iter::once(self.as_str()))
+    }
 }
 
 pub fn use_traits() -> std::io::Result<()> {
     // Rust automatically convert ordinary ref to trait object
-    let mut local_file = File::create("hello.txt")?;
-    say_hello_p(&mut local_file)?;
-    say_hello_g(&mut local_file)?;
+    // `Fs::create` already hands back a `Box<dyn Write>`, `&mut *` reborrows what it points at
+    let fs = InMemoryFs::new();
+    let path = std::path::Path::new("hello.txt");
+    {
+        let mut local_file = fs.create(path)?;
+        say_hello_p(&mut *local_file)?;
+        say_hello_g(&mut local_file)?;
+    }
+    assert_eq!(fs.read_to_string(path)?, "hello world\nhello world\n");
 
     let mut bytes = vec![];
     say_hello_p(&mut bytes)?;
@@ -61,5 +83,25 @@ pub fn use_traits() -> std::io::Result<()> {
 
     println!("{:?}", bytes);
 
+    // the adapters in `input_output/writers.rs` are `Write` too, and so is any stack of them
+    let mut counted = CountingWriter::new(TeeWriter::new(vec![], io::sink()));
+    say_hello_p(&mut counted)?;
+    say_hello_g(&mut counted)?;
+    assert_eq!((counted.bytes(), counted.lines()), (24, 2));
+
+    // the left side picks the type of the result
+    let rope = Rope::from("a rope, ");
+    let string = String::from("a string");
+    let spliced = splice_anthing(&rope, &string);
+    assert_eq!(
+        spliced.text_chunks().collect::<String>(),
+        "a rope, a string"
+    );
+    let spliced = splice_anthing(&string, spliced.as_ref());
+    assert_eq!(
+        spliced.text_chunks().collect::<String>(),
+        "a stringa rope, a string"
+    );
+
     Ok(())
 }
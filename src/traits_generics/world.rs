@@ -0,0 +1,577 @@
+// A grid world of `Box<dyn Creature>`, see `subtraits.rs` for the traits
+// - each tick every creature, in spawn order, asks for a step and the world checks it
+// - a step off the grid or onto another creature is refused and the creature is told it was `blocked`
+// - so no two creatures ever share a cell, and a refused step onto a creature is reported as a `Collision`
+// - all randomness comes from one seeded `Rng`, the same seed gives the same run
+use super::{Broom, Creature, Visible};
+use crate::enums::{Point2, Vector2};
+use crate::input_output::{Fs, InMemoryFs, RealFs};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+// xorshift64, deterministic and good enough to move creatures around
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // an all-zero state would stay zero forever, and the xor gives zero for one seed
+        let state = match seed ^ 0x2545_f491_4f6c_dd1d {
+            0 => 0x9e37_79b9_7f4a_7c15,
+            state => state,
+        };
+        Rng { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    // uniform in `0..n`, give or take a bias too small to matter here
+    pub fn below(&mut self, n: u32) -> u32 {
+        (self.next_u64() % n as u64) as u32
+    }
+}
+
+// what the creatures are drawn on, `Display` puts a border around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![' '; width * height],
+        }
+    }
+
+    fn index(&self, at: Point2<i32>) -> Option<usize> {
+        let (x, y) = (usize::try_from(at.x).ok()?, usize::try_from(at.y).ok()?);
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    // anything outside the frame is clipped
+    pub fn plot(&mut self, at: Point2<i32>, glyph: char) {
+        if let Some(i) = self.index(at) {
+            self.cells[i] = glyph;
+        }
+    }
+
+    pub fn get(&self, at: Point2<i32>) -> Option<char> {
+        self.index(at).map(|i| self.cells[i])
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let border = format!("+{}+", "-".repeat(self.width));
+        writeln!(f, "{}", border)?;
+        for row in self.cells.chunks(self.width.max(1)) {
+            writeln!(f, "|{}|", row.iter().collect::<String>())?;
+        }
+        write!(f, "{}", border)
+    }
+}
+
+// random steps, including standing still
+pub struct Wanderer {
+    name: String,
+    glyph: char,
+    position: Point2<i32>,
+}
+
+impl Wanderer {
+    pub fn new(name: &str, glyph: char, position: Point2<i32>) -> Self {
+        Wanderer {
+            name: name.to_string(),
+            glyph,
+            position,
+        }
+    }
+}
+
+impl Visible for Wanderer {
+    fn glyph(&self) -> char {
+        self.glyph
+    }
+}
+
+impl Creature for Wanderer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn position(&self) -> Point2<i32> {
+        self.position
+    }
+
+    fn set_position(&mut self, to: Point2<i32>) {
+        self.position = to;
+    }
+
+    fn next_move(&mut self, rng: &mut Rng) -> Vector2<i32> {
+        const STEPS: [(i32, i32); 5] = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)];
+        let (dx, dy) = STEPS[rng.below(STEPS.len() as u32) as usize];
+        Vector2::new(dx, dy)
+    }
+}
+
+// keeps its velocity and reverses it when blocked
+pub struct Bouncer {
+    name: String,
+    position: Point2<i32>,
+    velocity: Vector2<i32>,
+}
+
+impl Bouncer {
+    pub fn new(name: &str, position: Point2<i32>, velocity: Vector2<i32>) -> Self {
+        Bouncer {
+            name: name.to_string(),
+            position,
+            velocity,
+        }
+    }
+}
+
+impl Visible for Bouncer {
+    fn glyph(&self) -> char {
+        'o'
+    }
+}
+
+impl Creature for Bouncer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn position(&self) -> Point2<i32> {
+        self.position
+    }
+
+    fn set_position(&mut self, to: Point2<i32>) {
+        self.position = to;
+    }
+
+    fn next_move(&mut self, _rng: &mut Rng) -> Vector2<i32> {
+        self.velocity
+    }
+
+    fn blocked(&mut self) {
+        self.velocity = -self.velocity;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldError {
+    OutOfBounds {
+        name: String,
+        at: Point2<i32>,
+    },
+    Occupied {
+        name: String,
+        at: Point2<i32>,
+        by: String,
+    },
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldError::OutOfBounds { name, at } => {
+                write!(f, "{} at {} is outside the world", name, at)
+            }
+            WorldError::Occupied { name, at, by } => {
+                write!(f, "{} cannot spawn at {}, {} is there", name, at, by)
+            }
+        }
+    }
+}
+
+impl Error for WorldError {}
+
+// `mover` tried to step onto `at`, where `other` is
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    pub tick: u64,
+    pub at: Point2<i32>,
+    pub mover: String,
+    pub other: String,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "tick {}: {} bumped into {} at {}",
+            self.tick, self.mover, self.other, self.at
+        )
+    }
+}
+
+pub struct World {
+    width: i32,
+    height: i32,
+    creatures: Vec<Box<dyn Creature>>,
+    rng: Rng,
+    tick: u64,
+}
+
+// drawn by `Broom` and `Bouncer`, so a wanderer showing one of these would be mistaken for them
+const TAKEN_GLYPHS: [char; 3] = ['#', '=', 'o'];
+
+impl World {
+    pub fn new(width: u16, height: u16, seed: u64) -> Self {
+        World {
+            width: width as i32,
+            height: height as i32,
+            creatures: vec![],
+            rng: Rng::new(seed),
+            tick: 0,
+        }
+    }
+
+    // a mix of creatures on random free cells, the same ones for the same seed
+    // - wanderers take letters in turn, skipping `TAKEN_GLYPHS`
+    pub fn populate(width: u16, height: u16, seed: u64, count: usize) -> Self {
+        let mut world = World::new(width, height, seed);
+        let free_cells = width as usize * height as usize;
+        for i in 0..count.min(free_cells) {
+            let creature: Box<dyn Creature> = loop {
+                let at = Point2::new(
+                    world.rng.below(width as u32) as i32,
+                    world.rng.below(height as u32) as i32,
+                );
+                if world.occupant(at).is_some() {
+                    continue;
+                }
+                break match i % 4 {
+                    0 => Box::new(Broom::new(&format!("broom{}", i), 2, at)),
+                    1 => Box::new(Bouncer::new(&format!("ball{}", i), at, Vector2::new(1, 1))),
+                    _ => {
+                        let glyph = ('a'..='z')
+                            .filter(|c| !TAKEN_GLYPHS.contains(c))
+                            .cycle()
+                            .nth(i)
+                            .unwrap();
+                        Box::new(Wanderer::new(&format!("wanderer{}", i), glyph, at))
+                    }
+                };
+            };
+            world.spawn(creature).unwrap();
+        }
+        world
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn creatures(&self) -> &[Box<dyn Creature>] {
+        &self.creatures
+    }
+
+    fn in_bounds(&self, at: Point2<i32>) -> bool {
+        (0..self.width).contains(&at.x) && (0..self.height).contains(&at.y)
+    }
+
+    fn occupant(&self, at: Point2<i32>) -> Option<usize> {
+        self.creatures.iter().position(|c| c.position() == at)
+    }
+
+    pub fn spawn(&mut self, creature: Box<dyn Creature>) -> Result<(), WorldError> {
+        let at = creature.position();
+        if !self.in_bounds(at) {
+            return Err(WorldError::OutOfBounds {
+                name: creature.name().to_string(),
+                at,
+            });
+        }
+        if let Some(i) = self.occupant(at) {
+            return Err(WorldError::Occupied {
+                name: creature.name().to_string(),
+                at,
+                by: self.creatures[i].name().to_string(),
+            });
+        }
+        self.creatures.push(creature);
+        Ok(())
+    }
+
+    pub fn tick(&mut self) -> Vec<Collision> {
+        self.tick += 1;
+        let mut collisions = vec![];
+        for i in 0..self.creatures.len() {
+            let step = self.creatures[i].next_move(&mut self.rng);
+            if step == Vector2::new(0, 0) {
+                continue;
+            }
+            let to = self.creatures[i].position() + step;
+            if !self.in_bounds(to) {
+                self.creatures[i].blocked();
+                continue;
+            }
+            match self.occupant(to) {
+                Some(j) => {
+                    collisions.push(Collision {
+                        tick: self.tick,
+                        at: to,
+                        mover: self.creatures[i].name().to_string(),
+                        other: self.creatures[j].name().to_string(),
+                    });
+                    self.creatures[i].blocked();
+                }
+                None => self.creatures[i].set_position(to),
+            }
+        }
+        collisions
+    }
+
+    pub fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.width as usize, self.height as usize);
+        for creature in &self.creatures {
+            creature.show(creature.position(), &mut frame);
+        }
+        // a creature's own cell shows its glyph, even if another one drew over it
+        for creature in &self.creatures {
+            frame.plot(creature.position(), creature.glyph());
+        }
+        frame
+    }
+}
+
+// `simulate [ticks] [--seed N] [--size WxH] [--creatures N] [--out file]`
+pub fn simulate_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    simulate(&RealFs, args)
+}
+
+// the subcommand's work, `--out` is written through `fs`
+pub fn simulate(fs: &dyn Fs, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (mut ticks, mut seed, mut size, mut count, mut out) =
+        (10u64, 1u64, (40u16, 12u16), 6usize, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--seed" => seed = value("--seed")?.parse()?,
+            "--creatures" => count = value("--creatures")?.parse()?,
+            "--out" => out = Some(value("--out")?.clone()),
+            "--size" => {
+                let text = value("--size")?;
+                size = text
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or_else(|| format!("{:?} is not a size, expected WxH", text))?;
+            }
+            _ if !arg.starts_with("--") => ticks = arg.parse()?,
+            _ => {
+                return Err(format!(
+                    "unknown option {:?}\nusage: simulate [ticks] [--seed N] [--size WxH] [--creatures N] [--out file]",
+                    arg
+                )
+                .into())
+            }
+        }
+    }
+
+    let mut world = World::populate(size.0, size.1, seed, count);
+    let mut text = format!("tick 0\n{}\n", world.frame());
+    let mut total = 0;
+    for _ in 0..ticks {
+        let collisions = world.tick();
+        text += &format!("tick {}\n", world.ticks());
+        for c in &collisions {
+            text += &format!("{}\n", c);
+        }
+        text += &format!("{}\n", world.frame());
+        total += collisions.len();
+    }
+    match out {
+        Some(path) => {
+            fs.write(Path::new(&path), text.as_bytes())?;
+            println!("{} frames written to {}", ticks + 1, path);
+        }
+        None => print!("{}", text),
+    }
+    println!(
+        "{} creature(s), {} tick(s), {} collision(s)",
+        world.creatures().len(),
+        ticks,
+        total
+    );
+    Ok(())
+}
+
+pub fn use_world() {
+    // two brooms sweeping toward each other meet in the middle and turn around
+    let mut world = World::new(9, 1, 0);
+    world
+        .spawn(Box::new(Broom::new("left", 2, Point2::new(2, 0))))
+        .unwrap();
+    let mut right = Broom::new("right", 2, Point2::new(6, 0));
+    right.blocked();
+    world.spawn(Box::new(right)).unwrap();
+    println!("{}", world.frame());
+    for _ in 0..4 {
+        for collision in world.tick() {
+            println!("{}", collision);
+        }
+    }
+    println!("{}", world.frame());
+
+    let mut world = World::populate(20, 6, 3, 5);
+    for _ in 0..3 {
+        for collision in world.tick() {
+            println!("{}", collision);
+        }
+    }
+    println!("{}", world.frame());
+
+    // the subcommand, with its `--out` file kept in memory
+    let fs = InMemoryFs::new();
+    let args = ["2", "--size", "6x3", "--creatures", "2", "--out", "run.txt"].map(String::from);
+    simulate(&fs, &args).unwrap();
+    print!("{}", fs.read_to_string(Path::new("run.txt")).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brooms() -> World {
+        let mut world = World::new(9, 1, 0);
+        world
+            .spawn(Box::new(Broom::new("left", 2, Point2::new(2, 0))))
+            .unwrap();
+        let mut right = Broom::new("right", 2, Point2::new(6, 0));
+        right.blocked();
+        world.spawn(Box::new(right)).unwrap();
+        world
+    }
+
+    #[test]
+    fn brooms_meet_and_turn_around() {
+        let mut world = brooms();
+        assert_eq!(
+            world.frame().to_string(),
+            "+---------+\n|==#   #==|\n+---------+"
+        );
+        assert!(world.tick().is_empty());
+        // `left` moves first to 4, then `right` tries 4 as well
+        let collisions = world.tick();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(
+            collisions[0].to_string(),
+            "tick 2: right bumped into left at (4, 0)"
+        );
+        // `right` turned around, so now `left` runs into its back and turns too
+        assert_eq!(world.tick()[0].mover, "left");
+        assert!(world.tick().is_empty());
+        assert_eq!(
+            world.frame().to_string(),
+            "+---------+\n|   #===# |\n+---------+"
+        );
+    }
+
+    #[test]
+    fn spawn_errors() {
+        let mut world = brooms();
+        assert_eq!(
+            world.spawn(Box::new(Wanderer::new("w", 'w', Point2::new(9, 0)))),
+            Err(WorldError::OutOfBounds {
+                name: "w".into(),
+                at: Point2::new(9, 0)
+            })
+        );
+        assert!(world
+            .spawn(Box::new(Wanderer::new("w", 'w', Point2::new(2, 0))))
+            .is_err());
+    }
+
+    #[test]
+    fn runs_are_deterministic() {
+        // the same seed gives the same run, and creatures never overlap
+        let run = |seed| {
+            let mut world = World::populate(30, 10, seed, 12);
+            let mut frames = vec![];
+            for _ in 0..200 {
+                world.tick();
+                let positions = world
+                    .creatures()
+                    .iter()
+                    .map(|c| c.position())
+                    .collect::<Vec<_>>();
+                for (i, p) in positions.iter().enumerate() {
+                    assert!(!positions[i + 1..].contains(p), "two creatures at {}", p);
+                }
+                frames.push(world.frame());
+            }
+            frames
+        };
+        assert!(run(7) == run(7));
+        assert!(run(7) != run(8));
+    }
+
+    #[test]
+    fn wanderers_never_look_like_brooms_or_balls() {
+        // 60 creatures give 30 wanderers, enough to wrap around the alphabet
+        let world = World::populate(20, 10, 3, 60);
+        let wanderers = world
+            .creatures()
+            .iter()
+            .filter(|c| c.name().starts_with("wanderer"))
+            .map(|c| c.glyph())
+            .collect::<Vec<_>>();
+        assert_eq!(wanderers.len(), 30);
+        for glyph in wanderers {
+            assert!(glyph.is_ascii_lowercase(), "wanderer drawn as {:?}", glyph);
+            assert!(
+                !TAKEN_GLYPHS.contains(&glyph),
+                "wanderer drawn as {:?}",
+                glyph
+            );
+        }
+    }
+
+    #[test]
+    fn no_seed_gives_a_stuck_rng() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        assert_ne!(rng.next_u64(), 0);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+        // used to retry cell (0, 0) forever
+        let world = World::populate(4, 2, 2685821657736338717, 6);
+        assert_eq!(world.creatures().len(), 6);
+    }
+
+    #[test]
+    fn simulate_writes_frames_through_fs() {
+        let fs = InMemoryFs::new();
+        let args = ["3", "--seed", "5", "--size", "8x4", "--out", "/frames.txt"].map(String::from);
+        simulate(&fs, &args).unwrap();
+        let text = fs.read_to_string(Path::new("/frames.txt")).unwrap();
+        assert!(text.starts_with("tick 0\n+--------+\n"));
+        assert!(text.contains("tick 3\n"));
+        simulate(&fs, &args).unwrap();
+        assert_eq!(fs.read_to_string(Path::new("/frames.txt")).unwrap(), text);
+        assert!(simulate(&fs, &["--size".to_string(), "0x2".to_string()]).is_err());
+    }
+}